@@ -0,0 +1,17 @@
+// Writes the C header for the `ffi` feature to `include/binox.h`.
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    #[cfg(feature = "ffi")]
+    {
+        println!("cargo:rerun-if-changed=src/ffi.rs");
+        println!("cargo:rerun-if-changed=cbindgen.toml");
+        let dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+        let config = cbindgen::Config::from_file(format!("{dir}/cbindgen.toml")).unwrap();
+        cbindgen::Builder::new()
+            .with_src(format!("{dir}/src/ffi.rs"))
+            .with_config(config)
+            .generate()
+            .expect("could not generate the C header")
+            .write_to_file(format!("{dir}/include/binox.h"));
+    }
+}
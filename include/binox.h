@@ -0,0 +1,128 @@
+/* The C interface of binox. Generated by cbindgen from src/ffi.rs; do not edit. */
+
+#ifndef BINOX_H
+#define BINOX_H
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/* A board. Create one with binox_new, binox_from_string or binox_generate. */
+typedef struct Binox Binox;
+
+#define BINOX_EMPTY 0
+
+#define BINOX_X 1
+
+#define BINOX_O 2
+
+/**
+ * The message of the last failed call on this thread, or null. The string
+ * stays valid until the next failing call.
+ */
+const char *binox_last_error(void);
+
+/**
+ * An empty board of the given size, or null if the size is not supported.
+ */
+Binox *binox_new(uint8_t size);
+
+/**
+ * Reads a puzzle string such as `XXO.O..X.O..X..O`, or returns null if it is
+ * not a valid puzzle.
+ *
+ * # Safety
+ *
+ * `puzzle` must be a valid null-terminated string.
+ */
+Binox *binox_from_string(const char *puzzle);
+
+/**
+ * Generates a puzzle with a unique solution. The same seed always gives the
+ * same puzzle.
+ */
+Binox *binox_generate(uint8_t size, uint64_t seed, bool perfect);
+
+/**
+ * Releases a board. Null is ignored.
+ *
+ * # Safety
+ *
+ * `binox` must come from this library and not be used afterwards.
+ */
+void binox_free(Binox *binox);
+
+/**
+ * # Safety
+ *
+ * `binox` must be a valid board.
+ */
+uint8_t binox_size(const Binox *binox);
+
+/**
+ * The contents of a cell as `BINOX_EMPTY`, `BINOX_X` or `BINOX_O`, or -1 if
+ * the cell is out of range.
+ *
+ * # Safety
+ *
+ * `binox` must be a valid board.
+ */
+int binox_get_cell(const Binox *binox, uint8_t row, uint8_t col);
+
+/**
+ * Places `BINOX_X` or `BINOX_O`, or clears the cell with `BINOX_EMPTY`.
+ * Returns 0, or -1 if the cell is out of range or given.
+ *
+ * # Safety
+ *
+ * `binox` must be a valid board.
+ */
+int binox_set_cell(Binox *binox, uint8_t row, uint8_t col, int cell);
+
+/**
+ * Whether the board breaks no rule so far.
+ *
+ * # Safety
+ *
+ * `binox` must be a valid board.
+ */
+bool binox_is_valid(const Binox *binox);
+
+/**
+ * # Safety
+ *
+ * `binox` must be a valid board.
+ */
+bool binox_is_solved(const Binox *binox);
+
+/**
+ * The solved board as a new board, or null if the puzzle has no solution or
+ * more than one.
+ *
+ * # Safety
+ *
+ * `binox` must be a valid board.
+ */
+Binox *binox_solve(const Binox *binox);
+
+/**
+ * The board as a puzzle string, which `binox_from_string` reads back. The
+ * string must be released with `binox_string_free`.
+ *
+ * # Safety
+ *
+ * `binox` must be a valid board.
+ */
+char *binox_to_string(const Binox *binox);
+
+/**
+ * Releases a string returned by `binox_to_string`. Null is ignored.
+ *
+ * # Safety
+ *
+ * `text` must come from `binox_to_string` and not be used afterwards.
+ */
+void binox_string_free(char *text);
+
+#endif  /* BINOX_H */
@@ -0,0 +1,96 @@
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use crate::puzzle_set::PuzzleSet;
+
+pub const AUTOSAVE_INTERVAL: Duration = Duration::from_secs(30);
+const RECOVERY_FILE_NAME: &str = ".binox_recovery.binoxset";
+
+/// The recovery file lives in the home directory, or the working directory if
+/// no home directory is known.
+pub fn recovery_path() -> PathBuf {
+    match env::var_os("HOME").or_else(|| env::var_os("USERPROFILE")) {
+        Some(home) => PathBuf::from(home).join(RECOVERY_FILE_NAME),
+        None => PathBuf::from(RECOVERY_FILE_NAME),
+    }
+}
+
+fn recovery_string(puzzles: &PuzzleSet) -> String {
+    format!("{}\n{}", puzzles.selected(), puzzles.manifest_string())
+}
+
+fn parse_recovery(contents: &str) -> Option<PuzzleSet> {
+    let (selected, manifest) = contents.split_once('\n')?;
+    let mut puzzles = PuzzleSet::parse_manifest(manifest).ok()?;
+    if puzzles.is_empty() {
+        return None;
+    }
+    puzzles.select(selected.trim().parse().ok()?).ok()?;
+    Some(puzzles)
+}
+
+pub fn save_recovery(puzzles: &PuzzleSet) -> Result<(), String> {
+    let path = recovery_path();
+    fs::write(&path, recovery_string(puzzles))
+        .map_err(|_| format!("could not write recovery file {}", path.display()))
+}
+
+pub fn load_recovery() -> Option<PuzzleSet> {
+    parse_recovery(&fs::read_to_string(recovery_path()).ok()?)
+}
+
+pub fn clear_recovery() {
+    let _ = fs::remove_file(recovery_path());
+}
+
+/// Decides when the session is due to be written to the recovery file.
+pub struct Autosave {
+    last_save: Instant,
+    interval: Duration,
+}
+
+impl Autosave {
+    pub fn new() -> Self {
+        Autosave::with_interval(AUTOSAVE_INTERVAL)
+    }
+
+    pub fn with_interval(interval: Duration) -> Self {
+        Autosave {
+            last_save: Instant::now(),
+            interval,
+        }
+    }
+
+    pub fn is_due(&self) -> bool {
+        self.last_save.elapsed() >= self.interval
+    }
+
+    pub fn save(&mut self, puzzles: &PuzzleSet) -> Result<(), String> {
+        self.last_save = Instant::now();
+        save_recovery(puzzles)
+    }
+}
+
+impl Default for Autosave {
+    fn default() -> Self {
+        Autosave::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn recovery_round_trip() {
+        let mut puzzles = PuzzleSet::from_lines("session".into(), "X...\n.o..\n", None);
+        puzzles.select(1).unwrap();
+        let recovered = parse_recovery(&recovery_string(&puzzles)).unwrap();
+        assert_eq!(recovered, puzzles);
+        assert_eq!(recovered.selected(), 1);
+        assert!(parse_recovery("7\n[session]\nX...\n").is_none());
+        assert!(parse_recovery("garbage").is_none());
+    }
+}
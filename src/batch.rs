@@ -0,0 +1,275 @@
+use std::fs;
+use std::path::Path;
+
+use crate::binox::{Binox, BinoxSolution};
+use crate::difficulty::Difficulty;
+use crate::puzzle_set::{puzzle_lines, PuzzleSet, SET_EXTENSION};
+
+/// The boards are boxed, since a board keeps room for the biggest size.
+pub enum SolveOutcome {
+    Solved(Box<Binox>),
+    Ambiguous(Box<Binox>, Box<Binox>),
+    NoSolution,
+}
+
+impl SolveOutcome {
+    /// One line of batch output: the solution string, or a diagnostic.
+    pub fn line(&self) -> String {
+        match self {
+            SolveOutcome::Solved(a) => a.as_string(),
+            SolveOutcome::Ambiguous(..) => "ambiguous".into(),
+            SolveOutcome::NoSolution => "no solution".into(),
+        }
+    }
+}
+
+pub fn solve_puzzle(puzzle: &str) -> SolveOutcome {
+    match Binox::new_from_string(puzzle.into()).solve(true) {
+        BinoxSolution::Zero => SolveOutcome::NoSolution,
+        BinoxSolution::One(a) => SolveOutcome::Solved(Box::new(a)),
+        BinoxSolution::Multiple(a, b) => SolveOutcome::Ambiguous(Box::new(a), Box::new(b)),
+    }
+}
+
+pub fn solve_set(puzzles: &PuzzleSet) -> Vec<SolveOutcome> {
+    puzzles
+        .entries
+        .iter()
+        .map(|entry| solve_puzzle(&entry.puzzle))
+        .collect()
+}
+
+/// The answer key of a puzzle set: one solved grid per line, in the order of
+/// the puzzles, for printing beside a pack or for grading. A puzzle without
+/// exactly one solution gets the diagnostic of `SolveOutcome::line` instead,
+/// and its number is returned with the text so the caller can flag it.
+pub fn answer_key(puzzles: &PuzzleSet) -> (String, Vec<usize>) {
+    let mut text = String::new();
+    let mut flagged = Vec::new();
+    for (i, outcome) in solve_set(puzzles).iter().enumerate() {
+        if !matches!(outcome, SolveOutcome::Solved(_)) {
+            flagged.push(i);
+        }
+        text.push_str(&outcome.line());
+        text.push('\n');
+    }
+    (text, flagged)
+}
+
+/// Where the answer key of a puzzle file goes by default: `pack_answers.binox`
+/// next to `pack.binox` or `pack.binoxset`.
+pub fn answer_key_path(filename: &str) -> String {
+    let path = Path::new(filename);
+    let stem = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("puzzles");
+    path.with_file_name(format!("{stem}_answers.binox"))
+        .to_string_lossy()
+        .to_string()
+}
+
+/// Solves piped text, one puzzle per line, as in
+/// `echo "<puzzle>" | binox solve -`. Comments and `title:` and `size:`
+/// lines are skipped as in puzzle files. A malformed puzzle gives its error
+/// instead of an outcome.
+pub fn solve_text(text: &str) -> Vec<Result<SolveOutcome, String>> {
+    puzzle_lines(text)
+        .into_iter()
+        .map(|line| Binox::parse_strict(line.puzzle).map(|_| solve_puzzle(line.puzzle)))
+        .collect()
+}
+
+/// Reads the puzzle strings of a legacy or v2 puzzle file together with their
+/// line numbers.
+pub fn numbered_puzzles(filename: &str) -> Result<Vec<(usize, String)>, String> {
+    let contents =
+        fs::read_to_string(filename).map_err(|_| format!("file not found: {filename}"))?;
+    if !filename.ends_with(&format!(".{SET_EXTENSION}")) {
+        return Ok(puzzle_lines(&contents)
+            .into_iter()
+            .map(|line| (line.line, line.puzzle.into()))
+            .collect());
+    }
+    Ok(contents
+        .lines()
+        .enumerate()
+        .skip(1)
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| (i + 1, line.split('\t').next().unwrap_or_default().into()))
+        .collect())
+}
+
+/// Checks that a puzzle string is well formed, that its givens follow the
+/// rules, and that it has exactly one solution.
+pub fn validate_puzzle(puzzle: &str) -> Result<(), String> {
+    let binox = Binox::parse_strict(puzzle)?;
+    if !binox.is_valid() {
+        return Err("the given cells break the rules".into());
+    }
+    match binox.solve(true) {
+        BinoxSolution::Zero => Err("puzzle has no solution".into()),
+        BinoxSolution::One(_) => Ok(()),
+        BinoxSolution::Multiple(..) => Err("puzzle has more than one solution".into()),
+    }
+}
+
+/// Grades every puzzle of a set from its clues and orders them from easiest
+/// to hardest, by grade and then by rating score. Puzzles that rate the same keep their
+/// order.
+pub fn sort_by_difficulty(puzzles: &mut PuzzleSet) {
+    let mut rated: Vec<_> = puzzles
+        .entries
+        .drain(..)
+        .map(|mut entry| {
+            let rating = entry.clues().rate();
+            entry.difficulty = Some(rating.difficulty);
+            (rating.score, entry)
+        })
+        .collect();
+    rated.sort_by(|(a, first), (b, second)| {
+        first
+            .difficulty
+            .cmp(&second.difficulty)
+            .then(a.total_cmp(b))
+    });
+    let name = puzzles.name.clone();
+    *puzzles = PuzzleSet::new(name, rated.into_iter().map(|(_, entry)| entry).collect());
+}
+
+/// Splits a graded set into one set per difficulty, leaving out the
+/// difficulties it has no puzzles of.
+pub fn split_by_difficulty(puzzles: &PuzzleSet) -> Vec<(Difficulty, PuzzleSet)> {
+    Difficulty::ALL
+        .into_iter()
+        .filter_map(|difficulty| {
+            let entries: Vec<_> = puzzles
+                .entries
+                .iter()
+                .filter(|entry| entry.difficulty == Some(difficulty))
+                .cloned()
+                .collect();
+            let name = format!("{} {difficulty}", puzzles.name);
+            (!entries.is_empty()).then(|| (difficulty, PuzzleSet::new(name, entries)))
+        })
+        .collect()
+}
+
+/// Regrades a puzzle file and rewrites it ordered by difficulty, or with
+/// `split`, writes one file per difficulty next to it, as `pack_easy.binox`
+/// for `pack.binox`. Returns the files written and how many puzzles each has.
+pub fn sort_file(filename: &str, split: bool) -> Result<Vec<(String, usize)>, String> {
+    let mut puzzles = PuzzleSet::load(filename)?;
+    if puzzles.is_empty() {
+        return Err(format!("{filename} has no puzzles"));
+    }
+    sort_by_difficulty(&mut puzzles);
+    if !split {
+        puzzles.write(filename)?;
+        return Ok(vec![(filename.into(), puzzles.len())]);
+    }
+    let path = Path::new(filename);
+    let stem = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("puzzles");
+    let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("binox");
+    let mut written = Vec::new();
+    for (difficulty, set) in split_by_difficulty(&puzzles) {
+        let output = path.with_file_name(format!("{stem}_{difficulty}.{extension}"));
+        let output = output.to_string_lossy().to_string();
+        set.write(&output)?;
+        written.push((output, set.len()));
+    }
+    Ok(written)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::binox::{BinoxCell, Symmetry};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn outcomes() {
+        assert_eq!(solve_puzzle("xxOOOOxxOXOxxoXo").line(), "xxOOOOxxOXOxxoXo");
+        assert_eq!(solve_puzzle("xxx.............").line(), "no solution");
+        assert_eq!(solve_puzzle("................").line(), "ambiguous");
+    }
+
+    #[test]
+    fn answer_keys() {
+        let puzzles = PuzzleSet::from_lines(
+            "pack".into(),
+            "xxOOOOxxOXOxxoXo\n................\nX......O...X..XX....X..O....X..XX...\n",
+            None,
+        );
+        let (text, flagged) = answer_key(&puzzles);
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0], "xxOOOOxxOXOxxoXo");
+        assert_eq!(lines[1], "ambiguous");
+        assert!(!lines[2].contains('.'));
+        assert_eq!(flagged, [1]);
+        assert_eq!(
+            answer_key_path("dir/pack.binoxset"),
+            "dir/pack_answers.binox"
+        );
+    }
+
+    #[test]
+    fn piped_text() {
+        let lines: Vec<String> =
+            solve_text("# from the club\ntitle: first\nxxOOOOxxOXOxxoXo\n\nsize: 4\n  xxx.............  # broken\nxxOOOOxxOXOqxoXo\n")
+                .into_iter()
+                .map(|outcome| outcome.map_or_else(|e| e, |o| o.line()))
+                .collect();
+        assert_eq!(
+            lines,
+            [
+                "xxOOOOxxOXOxxoXo",
+                "no solution",
+                "invalid character 'q' at position 12"
+            ]
+        );
+    }
+
+    #[test]
+    fn sorting() {
+        let easy = "X......O...X..XX....X..O....X..XX...";
+        let mut rng = StdRng::seed_from_u64(3);
+        let (hard, _) = Binox::generate_with_rng(8, true, 0, Symmetry::None, &mut rng).unwrap();
+        let hard_difficulty = hard.rate().difficulty;
+        // Nearly finished by a player, which must not make it any easier.
+        let mut played = hard.clone();
+        played.solve_in_place(false);
+        let (row, col) = played.changed_cells(&hard)[0];
+        played.set_cell(row, col, BinoxCell::EMPTY).unwrap();
+        let played = played.as_string();
+        let mut puzzles =
+            PuzzleSet::from_lines("pack".into(), &format!("{played}\n{easy}\n"), None);
+        sort_by_difficulty(&mut puzzles);
+        assert_eq!(puzzles.entries[0].puzzle, easy);
+        assert_eq!(puzzles.entries[1].difficulty, Some(hard_difficulty));
+        assert!(puzzles.entries[0].difficulty <= puzzles.entries[1].difficulty);
+        let split = split_by_difficulty(&puzzles);
+        assert_eq!(split.iter().map(|(_, set)| set.len()).sum::<usize>(), 2);
+        assert!(split.iter().all(|(difficulty, set)| set
+            .entries
+            .iter()
+            .all(|entry| entry.difficulty == Some(*difficulty))));
+    }
+
+    #[test]
+    fn validation() {
+        assert!(validate_puzzle("xxOOOOxxOXOxxoXo").is_ok());
+        assert!(validate_puzzle("xxOOOOxxOXOxxoX").is_err());
+        assert_eq!(
+            validate_puzzle("xxOOOOxxOXOqxoXo"),
+            Err("invalid character 'q' at position 12".into())
+        );
+        assert!(validate_puzzle("xxx.............").is_err());
+        assert!(validate_puzzle("................").is_err());
+    }
+}
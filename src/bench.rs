@@ -0,0 +1,162 @@
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use crate::binox::{Binox, Symmetry};
+use crate::difficulty::Difficulty;
+
+/// What a benchmark times.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Operation {
+    Generate,
+    Presolve,
+    Solve,
+}
+
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Operation::Generate => "generate",
+            Operation::Presolve => "presolve",
+            Operation::Solve => "solve",
+        })
+    }
+}
+
+pub struct BenchOptions {
+    pub sizes: Vec<u8>,
+    pub difficulties: Vec<Difficulty>,
+    /// Rounds run before timing starts, and not counted.
+    pub warmup: usize,
+    pub repetitions: usize,
+    /// Seed for the puzzles, so that runs on different builds time the same
+    /// work.
+    pub seed: u64,
+}
+
+/// The times of one operation on puzzles of one size and difficulty.
+pub struct Measurement {
+    pub size: u8,
+    pub difficulty: Difficulty,
+    pub operation: Operation,
+    pub times: Vec<Duration>,
+}
+
+impl Measurement {
+    pub fn mean(&self) -> Duration {
+        self.times.iter().sum::<Duration>() / self.times.len().max(1) as u32
+    }
+
+    pub fn min(&self) -> Duration {
+        self.times.iter().copied().min().unwrap_or_default()
+    }
+
+    pub fn max(&self) -> Duration {
+        self.times.iter().copied().max().unwrap_or_default()
+    }
+}
+
+fn timed<T>(work: impl FnOnce() -> T) -> (T, Duration) {
+    let start = Instant::now();
+    let result = work();
+    (result, start.elapsed())
+}
+
+/// Generates, presolves and solves puzzles of every size and difficulty,
+/// calling `progress` before each combination.
+pub fn run(
+    options: &BenchOptions,
+    mut progress: impl FnMut(u8, Difficulty),
+) -> Result<Vec<Measurement>, &'static str> {
+    let mut measurements = Vec::new();
+    for &size in &options.sizes {
+        for &difficulty in &options.difficulties {
+            progress(size, difficulty);
+            let mut rng = StdRng::seed_from_u64(options.seed);
+            let mut times = [Vec::new(), Vec::new(), Vec::new()];
+            for round in 0..options.warmup + options.repetitions {
+                let (generated, generate) = timed(|| {
+                    Binox::generate_with_rng(
+                        size,
+                        true,
+                        difficulty.extras(size),
+                        Symmetry::None,
+                        &mut rng,
+                    )
+                });
+                let (puzzle, _) = generated?;
+                let (_, presolve) = timed(|| puzzle.clone().presolve());
+                let (_, solve) = timed(|| puzzle.solve(true));
+                if round >= options.warmup {
+                    for (list, time) in times.iter_mut().zip([generate, presolve, solve]) {
+                        list.push(time);
+                    }
+                }
+            }
+            let operations = [Operation::Generate, Operation::Presolve, Operation::Solve];
+            for (operation, times) in operations.into_iter().zip(times) {
+                measurements.push(Measurement {
+                    size,
+                    difficulty,
+                    operation,
+                    times,
+                });
+            }
+        }
+    }
+    Ok(measurements)
+}
+
+/// The measurements as a table with one row each.
+pub fn table(measurements: &[Measurement]) -> String {
+    let mut text = format!(
+        "{:<7}  {:<10}  {:<9}  {:>10}  {:>10}  {:>10}\n",
+        "size", "difficulty", "operation", "mean", "min", "max"
+    );
+    for m in measurements {
+        text.push_str(&format!(
+            "{:<7}  {:<10}  {:<9}  {:>10}  {:>10}  {:>10}\n",
+            format!("{0}x{0}", m.size),
+            m.difficulty.name(),
+            m.operation.to_string(),
+            format!("{:.2?}", m.mean()),
+            format!("{:.2?}", m.min()),
+            format!("{:.2?}", m.max()),
+        ));
+    }
+    text
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn measurements() {
+        let options = BenchOptions {
+            sizes: vec![4, 6],
+            difficulties: vec![Difficulty::Easy],
+            warmup: 1,
+            repetitions: 2,
+            seed: 3,
+        };
+        let mut seen = Vec::new();
+        let measurements = run(&options, |size, difficulty| seen.push((size, difficulty))).unwrap();
+        assert_eq!(seen, [(4, Difficulty::Easy), (6, Difficulty::Easy)]);
+        assert_eq!(measurements.len(), 2 * 3);
+        assert!(measurements.iter().all(|m| m.times.len() == 2));
+        assert!(measurements
+            .iter()
+            .all(|m| m.min() <= m.mean() && m.mean() <= m.max()));
+
+        let table = table(&measurements);
+        assert_eq!(table.lines().count(), 1 + 6);
+        assert!(table
+            .lines()
+            .nth(1)
+            .unwrap()
+            .starts_with("4x4      easy        generate"));
+    }
+}
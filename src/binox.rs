@@ -1,59 +1,127 @@
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+#[cfg(feature = "color")]
+use core::cmp::Ordering;
 use core::fmt;
-use std::ops::Add;
+use core::hash::{Hash, Hasher};
 
 use crate::binox::row::BinRow;
-use crate::binox::BinoxSolution::*;
+#[cfg(feature = "color")]
+use crate::marks::Marks;
+pub use crate::puzzle::{seed_from_str, PresolveResult, SolveStatus, Symmetry};
+use crate::puzzle::{Puzzle, Solution};
+use crate::theme::current_theme;
+#[cfg(feature = "color")]
+use crate::theme::BoardStyle;
 
+#[cfg(feature = "color")]
 use colored::*;
-use rand::prelude::SliceRandom;
 use rand::Rng;
 
-mod row;
+pub mod analysis;
+pub mod grids;
+pub mod logic;
+pub mod propagation;
+pub mod row;
+pub mod rules;
+mod speech;
+
+/// The largest board side. Every board keeps room for this many lines, so
+/// that copying one never allocates; the lines past its size stay empty.
+const MAX_SIZE: usize = 16;
 
 #[derive(Clone, Debug)]
 pub struct Binox {
     size: u8,
-    x_rows: Vec<BinRow>,
-    o_rows: Vec<BinRow>,
-    x_cols: Vec<BinRow>,
-    o_cols: Vec<BinRow>,
-    default_rows: Vec<BinRow>,
+    x_rows: [BinRow; MAX_SIZE],
+    o_rows: [BinRow; MAX_SIZE],
+    x_cols: [BinRow; MAX_SIZE],
+    o_cols: [BinRow; MAX_SIZE],
+    default_rows: [BinRow; MAX_SIZE],
+    /// Pairs of lines, among the rows or among the columns, that have half of
+    /// their cells of a symbol in the same places. Kept up to date as cells
+    /// change, so checking for duplicate lines needs no scan.
+    duplicates: u16,
+    /// The solution of the clues, once [`Binox::solution`] has looked for it.
+    solution: CachedSolution,
+}
+
+/// What a board knows about the solution of its clues. Forgotten whenever a
+/// clue changes.
+#[derive(Clone, Copy, Debug)]
+enum CachedSolution {
+    Unknown,
+    /// The cells holding X in each row of the only solution.
+    Unique([BinRow; MAX_SIZE]),
+    NotUnique,
 }
 
-#[derive(PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum BinoxCell {
     X,
     O,
     EMPTY,
 }
 
-pub enum PresolveResult {
-    Good,
-    Bad,
+/// Why a cell could not be changed, with the cell's row and column.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BinoxError {
+    /// The cell is not on the board, which has `size` cells a side.
+    OutOfRange { row: u8, col: u8, size: u8 },
+    /// The cell is a given, which cannot be modified.
+    Given { row: u8, col: u8 },
+    /// The cell is empty, and only filled cells can be locked.
+    Empty { row: u8, col: u8 },
+    /// The cell is not a given, so it cannot be unlocked.
+    NotLocked { row: u8, col: u8 },
 }
 
-pub enum BinoxSolution {
-    Zero,
-    One(Binox),
-    Multiple(Binox, Binox),
+impl BinoxError {
+    /// The row and column of the cell.
+    pub fn cell(&self) -> (u8, u8) {
+        match *self {
+            BinoxError::OutOfRange { row, col, .. }
+            | BinoxError::Given { row, col }
+            | BinoxError::Empty { row, col }
+            | BinoxError::NotLocked { row, col } => (row, col),
+        }
+    }
 }
 
-impl Add for BinoxSolution {
-    type Output = Self;
-
-    fn add(self, rhs: Self) -> Self::Output {
-        match self {
-            Multiple(..) => self,
-            One(a) => match rhs {
-                Zero => One(a),
-                One(b) => Multiple(a, b),
-                Multiple(..) => rhs,
-            },
-            Zero => rhs,
+impl fmt::Display for BinoxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            BinoxError::OutOfRange { row, col, size } => write!(
+                f,
+                "row {row}, column {col} is outside the {size}x{size} board"
+            ),
+            BinoxError::Given { row, col } => write!(
+                f,
+                "cell {}{row} is a given and cannot be modified",
+                column_name(col)
+            ),
+            BinoxError::Empty { row, col } => write!(
+                f,
+                "cell {}{row} is empty; only filled cells can be locked",
+                column_name(col)
+            ),
+            BinoxError::NotLocked { row, col } => {
+                write!(f, "cell {}{row} is not locked", column_name(col))
+            }
         }
     }
 }
 
+impl From<BinoxError> for String {
+    fn from(error: BinoxError) -> Self {
+        error.to_string()
+    }
+}
+
+/// The outcome of solving a board.
+pub type BinoxSolution = Solution<Binox>;
+
 impl From<BinoxCell> for char {
     fn from(cell: BinoxCell) -> Self {
         match cell {
@@ -64,19 +132,16 @@ impl From<BinoxCell> for char {
     }
 }
 
+#[cfg(feature = "color")]
 impl From<BinoxCell> for ColoredString {
     fn from(cell: BinoxCell) -> Self {
-        match cell {
-            BinoxCell::X => "X".red(),
-            BinoxCell::O => "O".blue(),
-            BinoxCell::EMPTY => " ".into(),
-        }
+        current_theme().paint(cell)
     }
 }
 
 impl Binox {
     pub fn new(size: u8) -> Result<Self, &'static str> {
-        if size > 16 {
+        if size as usize > MAX_SIZE {
             return Err("size must be at most 16");
         }
         if size < 4 {
@@ -85,24 +150,22 @@ impl Binox {
         if size % 2 == 1 {
             return Err("size must be even");
         }
+        let blank = [BinRow::new(size).unwrap(); MAX_SIZE];
         Ok(Binox {
             size,
-            x_rows: vec![BinRow::new(size).unwrap(); size.into()],
-            o_rows: vec![BinRow::new(size).unwrap(); size.into()],
-            x_cols: vec![BinRow::new(size).unwrap(); size.into()],
-            o_cols: vec![BinRow::new(size).unwrap(); size.into()],
-            default_rows: vec![BinRow::new(size).unwrap(); size.into()],
+            x_rows: blank,
+            o_rows: blank,
+            x_cols: blank,
+            o_cols: blank,
+            default_rows: blank,
+            duplicates: 0,
+            solution: CachedSolution::Unknown,
         })
     }
 
     pub fn new_from_string(str: String) -> Self {
-        let mut size = (str.len() as f64).sqrt().floor() as u8;
-        if size < 4 {
-            size = 4;
-        }
-        if size > 16 {
-            size = 16;
-        }
+        let mut size = str.len().isqrt() as u8;
+        size = size.clamp(4, 16);
         if size % 2 == 1 {
             size += 1;
         }
@@ -138,45 +201,102 @@ impl Binox {
         binox
     }
 
-    fn set_x(&mut self, row: u8, col: u8) -> Result<(), &'static str> {
-        if row >= self.size || col >= self.size {
-            return Err("attempted to set x out of range");
+    /// Parses a puzzle string, rejecting strings that `new_from_string` would
+    /// silently pad, truncate or misread. Errors name the offending position.
+    pub fn parse_strict(str: &str) -> Result<Self, String> {
+        let length = str.chars().count();
+        let size = length.isqrt();
+        if size * size != length {
+            return Err(format!("puzzle has {length} cells, which is not a square"));
+        }
+        let size = u8::try_from(size).map_err(|_| "size must be at most 16".to_string())?;
+        let mut binox = Binox::new(size)?;
+        for (i, c) in str.chars().enumerate() {
+            let (row, col) = ((i / size as usize) as u8, (i % size as usize) as u8);
+            match c {
+                'x' | 'X' => binox.set_x(row, col)?,
+                'o' | 'O' => binox.set_o(row, col)?,
+                '.' | ' ' => (),
+                _ => return Err(format!("invalid character '{c}' at position {}", i + 1)),
+            };
+            if c.is_uppercase() {
+                binox.set_default(row, col, true)?;
+            }
         }
-        self.x_rows[row as usize].set_one(col).unwrap();
-        self.o_rows[row as usize].set_zero(col).unwrap();
-        self.x_cols[col as usize].set_one(row).unwrap();
-        self.o_cols[col as usize].set_zero(row).unwrap();
+        Ok(binox)
+    }
+
+    pub fn size(&self) -> u8 {
+        self.size
+    }
+
+    fn set_x(&mut self, row: u8, col: u8) -> Result<(), BinoxError> {
+        self.in_range(row, col)?;
+        self.fill(row, col, true, false);
         Ok(())
     }
 
-    fn set_o(&mut self, row: u8, col: u8) -> Result<(), &'static str> {
-        if row >= self.size || col >= self.size {
-            return Err("attempted to set o out of range");
-        }
-        self.x_rows[row as usize].set_zero(col).unwrap();
-        self.o_rows[row as usize].set_one(col).unwrap();
-        self.x_cols[col as usize].set_zero(row).unwrap();
-        self.o_cols[col as usize].set_one(row).unwrap();
+    fn set_o(&mut self, row: u8, col: u8) -> Result<(), BinoxError> {
+        self.in_range(row, col)?;
+        self.fill(row, col, false, true);
         Ok(())
     }
 
-    fn set_empty(&mut self, row: u8, col: u8) -> Result<(), &'static str> {
-        if row >= self.size || col >= self.size {
-            return Err("attempted to set empty out of range");
-        }
-        self.x_rows[row as usize].set_zero(col).unwrap();
-        self.o_rows[row as usize].set_zero(col).unwrap();
-        self.x_cols[col as usize].set_zero(row).unwrap();
-        self.o_cols[col as usize].set_zero(row).unwrap();
+    fn set_empty(&mut self, row: u8, col: u8) -> Result<(), BinoxError> {
+        self.in_range(row, col)?;
+        self.fill(row, col, false, false);
         Ok(())
     }
 
-    pub fn set_cell(&mut self, row: u8, col: u8, cell: BinoxCell) -> Result<(), &'static str> {
+    fn in_range(&self, row: u8, col: u8) -> Result<(), BinoxError> {
         if row >= self.size || col >= self.size {
-            return Err("attempted to set cell out of range");
+            return Err(BinoxError::OutOfRange {
+                row,
+                col,
+                size: self.size,
+            });
         }
+        Ok(())
+    }
+
+    /// Writes a cell's symbol bits into its row and column, keeping the count
+    /// of duplicate lines current.
+    fn fill(&mut self, row: u8, col: u8, x: bool, o: bool) {
+        let (r, c) = (row as usize, col as usize);
+        self.duplicates -= self.twins(r, c);
+        self.x_rows[r].set_bool(col, x).unwrap();
+        self.o_rows[r].set_bool(col, o).unwrap();
+        self.x_cols[c].set_bool(row, x).unwrap();
+        self.o_cols[c].set_bool(row, o).unwrap();
+        self.duplicates += self.twins(r, c);
+    }
+
+    /// How many other lines duplicate the four lines through a cell.
+    fn twins(&self, row: usize, col: usize) -> u16 {
+        let size = self.size as usize;
+        let half = self.size / 2;
+        let count = |lines: &[BinRow], index: usize| {
+            let line = lines[index];
+            if line.count != half {
+                return 0;
+            }
+            lines
+                .iter()
+                .enumerate()
+                .filter(|&(i, other)| i != index && other.count == half && other.data == line.data)
+                .count() as u16
+        };
+        count(&self.x_rows[..size], row)
+            + count(&self.o_rows[..size], row)
+            + count(&self.x_cols[..size], col)
+            + count(&self.o_cols[..size], col)
+    }
+
+    /// Places a symbol, or empties the cell, unless the cell is a clue.
+    pub fn set_cell(&mut self, row: u8, col: u8, cell: BinoxCell) -> Result<(), BinoxError> {
+        self.in_range(row, col)?;
         if self.is_default(row, col).unwrap() {
-            return Err("this cell cannot be modified.");
+            return Err(BinoxError::Given { row, col });
         }
         match cell {
             BinoxCell::X => self.set_x(row, col),
@@ -185,17 +305,43 @@ impl Binox {
         }
     }
 
-    fn set_default(&mut self, row: u8, col: u8, value: bool) -> Result<(), &'static str> {
-        if row >= self.size || col >= self.size {
-            return Err("attempted to set default out of range");
-        }
+    fn set_default(&mut self, row: u8, col: u8, value: bool) -> Result<(), BinoxError> {
+        self.in_range(row, col)?;
         self.default_rows[row as usize]
             .set_bool(col, value)
             .unwrap();
+        self.solution = CachedSolution::Unknown;
         Ok(())
     }
 
-    fn get_cell(&self, row: u8, col: u8) -> Result<BinoxCell, &'static str> {
+    /// Turns a filled cell into a clue that cannot be changed.
+    pub fn lock(&mut self, row: u8, col: u8) -> Result<(), BinoxError> {
+        self.in_range(row, col)?;
+        if self.get_cell(row, col).unwrap() == BinoxCell::EMPTY {
+            return Err(BinoxError::Empty { row, col });
+        }
+        self.set_default(row, col, true)
+    }
+
+    /// Turns a clue back into an ordinary cell.
+    pub fn unlock(&mut self, row: u8, col: u8) -> Result<(), BinoxError> {
+        self.in_range(row, col)?;
+        if !self.is_default(row, col).unwrap() {
+            return Err(BinoxError::NotLocked { row, col });
+        }
+        self.set_default(row, col, false)
+    }
+
+    /// Unlocks every clue, so the puzzle itself can be changed.
+    pub fn unlock_all(&mut self) {
+        for row in 0..self.size {
+            for col in 0..self.size {
+                self.set_default(row, col, false).unwrap();
+            }
+        }
+    }
+
+    pub fn get_cell(&self, row: u8, col: u8) -> Result<BinoxCell, &'static str> {
         if row >= self.size || col >= self.size {
             return Err("attempted to get cell out of range");
         }
@@ -209,7 +355,7 @@ impl Binox {
         }
     }
 
-    fn is_default(&self, row: u8, col: u8) -> Result<bool, &'static str> {
+    pub fn is_default(&self, row: u8, col: u8) -> Result<bool, &'static str> {
         if row >= self.size || col >= self.size {
             return Err("attempted to get default out of range");
         }
@@ -217,51 +363,14 @@ impl Binox {
     }
 
     pub fn is_valid_simple(&self) -> bool {
-        [&self.x_rows, &self.o_cols, &self.x_cols, &self.o_cols]
+        let size = self.size as usize;
+        [&self.x_rows, &self.o_rows, &self.x_cols, &self.o_cols]
             .iter()
-            .flat_map(|&x| x)
-            .all(|row| row.is_valid_simple())
+            .all(|lines| BinRow::all_valid_simple(&lines[..size]))
     }
 
     pub fn is_valid(&self) -> bool {
-        if ![&self.x_rows, &self.o_cols, &self.x_cols, &self.o_cols]
-            .iter()
-            .flat_map(|&x| x)
-            .all(|row| row.is_valid())
-        {
-            return false;
-        }
-        let mut sorted_x_rows = self.x_rows.clone();
-        let mut sorted_o_rows = self.o_rows.clone();
-        let mut sorted_x_cols = self.x_cols.clone();
-        let mut sorted_o_cols = self.o_cols.clone();
-        sorted_x_rows.sort();
-        sorted_o_rows.sort();
-        sorted_x_cols.sort();
-        sorted_o_cols.sort();
-        for i in 0..(self.size - 1) {
-            if sorted_x_rows[i as usize].data == sorted_x_rows[(i + 1) as usize].data
-                && sorted_x_rows[i as usize].count == self.size / 2
-            {
-                return false;
-            }
-            if sorted_o_rows[i as usize].data == sorted_o_rows[(i + 1) as usize].data
-                && sorted_o_rows[i as usize].count == self.size / 2
-            {
-                return false;
-            }
-            if sorted_x_cols[i as usize].data == sorted_x_cols[(i + 1) as usize].data
-                && sorted_x_cols[i as usize].count == self.size / 2
-            {
-                return false;
-            }
-            if sorted_o_cols[i as usize].data == sorted_o_cols[(i + 1) as usize].data
-                && sorted_o_cols[i as usize].count == self.size / 2
-            {
-                return false;
-            }
-        }
-        true
+        Puzzle::is_valid(self)
     }
 
     pub fn is_full(&self) -> bool {
@@ -290,6 +399,17 @@ impl Binox {
         result
     }
 
+    /// The board as a new puzzle string: only the clues, or with `promote`,
+    /// every filled cell as a clue.
+    pub fn puzzle_string(&self, promote: bool) -> String {
+        if promote {
+            return self.as_string().to_uppercase();
+        }
+        let mut clues = self.clone();
+        clues.reset();
+        clues.as_string()
+    }
+
     pub fn reset(&mut self) {
         for row in 0..self.size {
             for col in 0..self.size {
@@ -301,235 +421,459 @@ impl Binox {
     }
 
     pub fn presolve(&mut self) -> PresolveResult {
+        Puzzle::presolve(self)
+    }
+
+    pub fn solve(&self, multiple: bool) -> BinoxSolution {
+        Puzzle::solve(self, multiple)
+    }
+
+    /// Fills the board with a solution; see [`Puzzle::solve_in_place`].
+    pub fn solve_in_place(&mut self, multiple: bool) -> SolveStatus {
+        Puzzle::solve_in_place(self, multiple)
+    }
+
+    /// The number of ways this board can be completed, counting no further
+    /// than `limit`.
+    pub fn count_solutions(&self, limit: usize) -> usize {
+        Puzzle::count_solutions(self, limit)
+    }
+
+    /// The only solution of the puzzle given by this board's clues, ignoring
+    /// the player's entries, or `None` if there is not exactly one.
+    pub fn unique_solution(&self) -> Option<Binox> {
+        match self.solution {
+            CachedSolution::Unique(x_rows) => return Some(self.filled_with(&x_rows)),
+            CachedSolution::NotUnique => return None,
+            CachedSolution::Unknown => (),
+        }
+        let mut clues = self.clone();
+        clues.reset();
+        match clues.solve(true) {
+            Solution::One(solution) => Some(solution),
+            _ => None,
+        }
+    }
+
+    /// Like [`Binox::unique_solution`], but searches only the first time: the
+    /// answer is kept with the board, and its copies, until a clue changes.
+    pub fn solution(&mut self) -> Option<Binox> {
+        let solution = self.unique_solution();
+        self.solution = match &solution {
+            Some(solution) => CachedSolution::Unique(solution.x_rows),
+            None => CachedSolution::NotUnique,
+        };
+        solution
+    }
+
+    /// This board with every cell filled: X where `x_rows` has it, else O.
+    fn filled_with(&self, x_rows: &[BinRow; MAX_SIZE]) -> Binox {
+        let mut filled = self.clone();
         for row in 0..self.size {
             for col in 0..self.size {
-                if self.get_cell(row, col).unwrap() == BinoxCell::EMPTY {
-                    self.set_x(row, col).unwrap();
-                    let x_valid = self.is_valid();
-                    self.set_o(row, col).unwrap();
-                    let o_valid = self.is_valid();
-                    match (x_valid, o_valid) {
-                        (true, false) => self.set_x(row, col).unwrap(),
-                        (false, true) => self.set_o(row, col).unwrap(),
-                        (false, false) => {
-                            self.set_empty(row, col).unwrap();
-                            return PresolveResult::Bad;
-                        }
-                        (true, true) => self.set_empty(row, col).unwrap(),
-                    }
+                if x_rows[row as usize].get(col).unwrap() {
+                    filled.set_x(row, col).unwrap();
+                } else {
+                    filled.set_o(row, col).unwrap();
                 }
             }
         }
-        PresolveResult::Good
+        filled
     }
 
-    fn presolve_simple(&mut self) -> PresolveResult {
+    /// Whether `solution` is a finished board that agrees with every clue.
+    pub fn fits(&self, solution: &Binox) -> bool {
+        solution.size == self.size
+            && solution.is_solved()
+            && (0..self.size).all(|row| {
+                (0..self.size).all(|col| {
+                    !self.is_default(row, col).unwrap()
+                        || self.get_cell(row, col) == solution.get_cell(row, col)
+                })
+            })
+    }
+
+    /// The cells whose symbol differs between two boards of the same size.
+    pub fn changed_cells(&self, other: &Binox) -> Vec<(u8, u8)> {
+        Puzzle::changed_cells(self, other)
+    }
+
+    /// Whether two boards hold the same symbols in the same cells, whichever
+    /// of them are clues, as when a finished board is checked against an
+    /// answer key.
+    pub fn same_filling(&self, other: &Binox) -> bool {
+        self.size == other.size && self.x_rows == other.x_rows && self.o_rows == other.o_rows
+    }
+
+    /// The cells filled by the player that differ from `solution`.
+    pub fn differences(&self, solution: &Binox) -> Vec<(u8, u8)> {
+        let mut result = Vec::new();
         for row in 0..self.size {
             for col in 0..self.size {
-                if self.get_cell(row, col).unwrap() == BinoxCell::EMPTY {
-                    self.set_x(row, col).unwrap();
-                    let x_valid = self.is_valid_simple();
-                    self.set_o(row, col).unwrap();
-                    let o_valid = self.is_valid_simple();
-                    match (x_valid, o_valid) {
-                        (true, false) => self.set_x(row, col).unwrap(),
-                        (false, true) => self.set_o(row, col).unwrap(),
-                        (false, false) => {
-                            self.set_empty(row, col).unwrap();
-                            return PresolveResult::Bad;
-                        }
-                        (true, true) => self.set_empty(row, col).unwrap(),
-                    }
+                let cell = self.get_cell(row, col).unwrap();
+                if cell != BinoxCell::EMPTY
+                    && !self.is_default(row, col).unwrap()
+                    && solution.get_cell(row, col) != Ok(cell)
+                {
+                    result.push((row, col));
                 }
             }
         }
-        PresolveResult::Good
+        result
     }
 
-    pub fn solve(&self, multiple: bool) -> BinoxSolution {
-        match (self.is_full(), self.is_valid()) {
-            (true, true) => return One(self.clone()),
-            (false, true) => (),
-            (_, false) => return Zero,
-        }
-        let mut x_clone = self.clone();
-        match x_clone.presolve() {
-            PresolveResult::Good => (),
-            PresolveResult::Bad => return Zero,
-        };
-        let (mut empty_cell_row, mut empty_cell_column) = (0, 0);
-        'a: for row in alternated_range(self.size) {
-            for col in alternated_range(self.size) {
-                if x_clone.get_cell(row, col).unwrap() == BinoxCell::EMPTY {
-                    (empty_cell_row, empty_cell_column) = (row, col);
-                    break 'a;
-                }
+    #[cfg(feature = "std")]
+    pub fn generate(size: u8, perfect: bool, extras: usize) -> Result<Binox, &'static str> {
+        Ok(Binox::generate_with_solution(size, perfect, extras)?.0)
+    }
+
+    /// Generates a puzzle like `generate` and also returns its unique solution.
+    #[cfg(feature = "std")]
+    pub fn generate_with_solution(
+        size: u8,
+        perfect: bool,
+        extras: usize,
+    ) -> Result<(Binox, Binox), &'static str> {
+        Binox::generate_with_rng(
+            size,
+            perfect,
+            extras,
+            Symmetry::None,
+            &mut rand::thread_rng(),
+        )
+    }
+
+    /// Generates a puzzle and its unique solution using the given random number
+    /// generator, so that a seeded generator always produces the same puzzle.
+    /// The clues are placed according to `symmetry`.
+    pub fn generate_with_rng<R: Rng>(
+        size: u8,
+        perfect: bool,
+        extras: usize,
+        symmetry: Symmetry,
+        rng: &mut R,
+    ) -> Result<(Binox, Binox), &'static str> {
+        Puzzle::generate_with_rng(size, perfect, extras, symmetry, rng)
+    }
+}
+
+/// An empty 8x8 board.
+impl Default for Binox {
+    fn default() -> Self {
+        Binox::new(8).unwrap()
+    }
+}
+
+/// Boards are equal when they have the same size, the same symbols in the
+/// same cells and the same clues.
+impl PartialEq for Binox {
+    fn eq(&self, other: &Self) -> bool {
+        self.size == other.size
+            && self.x_rows == other.x_rows
+            && self.o_rows == other.o_rows
+            && self.default_rows == other.default_rows
+    }
+}
+
+impl Eq for Binox {}
+
+impl Hash for Binox {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.size.hash(state);
+        let size = self.size as usize;
+        for lines in [&self.x_rows, &self.o_rows, &self.default_rows] {
+            for line in &lines[..size] {
+                line.data.hash(state);
             }
         }
-        let mut o_clone = x_clone.clone();
-        x_clone.set_x(empty_cell_row, empty_cell_column).unwrap();
-        o_clone.set_o(empty_cell_row, empty_cell_column).unwrap();
-        let x_solved = x_clone.solve(multiple);
-        match (x_solved, multiple) {
-            (Zero, true) => o_clone.solve(true),
-            (Zero, false) => o_clone.solve(false),
-            (One(a), true) => One(a) + o_clone.solve(false),
-            (One(a), false) => One(a),
-            (Multiple(a, b), true) => Multiple(a, b),
-            (Multiple(a, _), false) => One(a),
-        }
     }
+}
 
-    pub fn generate(size: u8, perfect: bool, extras: usize) -> Result<Binox, &'static str> {
-        //phase 1 - add some symbols randomly to get started
-        let mut binox = Binox::new(size)?;
-        let mut rows = (0u8..size).collect::<Vec<u8>>();
-        let cols = (0u8..size).collect::<Vec<u8>>();
-        rows.shuffle(&mut rand::thread_rng());
-        for i in 0..size {
-            if rand::random() {
-                binox.set_x(rows[i as usize], cols[i as usize]).unwrap();
-            } else {
-                binox.set_o(rows[i as usize], cols[i as usize]).unwrap();
-            }
+impl Puzzle for Binox {
+    type Cell = BinoxCell;
+
+    const SYMBOLS: [BinoxCell; 2] = [BinoxCell::X, BinoxCell::O];
+    const EMPTY: BinoxCell = BinoxCell::EMPTY;
+
+    fn blank(size: u8) -> Result<Self, &'static str> {
+        Binox::new(size)
+    }
+
+    fn size(&self) -> u8 {
+        self.size
+    }
+
+    fn cell(&self, row: u8, col: u8) -> BinoxCell {
+        self.get_cell(row, col).unwrap()
+    }
+
+    fn put(&mut self, row: u8, col: u8, cell: BinoxCell) {
+        if self.default_rows[row as usize].get(col) == Ok(true) {
+            self.solution = CachedSolution::Unknown;
+        }
+        match cell {
+            BinoxCell::X => self.set_x(row, col),
+            BinoxCell::O => self.set_o(row, col),
+            BinoxCell::EMPTY => self.set_empty(row, col),
         }
+        .unwrap()
+    }
 
-        //phase 2 - continue adding symbols until there is only one solution
-        loop {
-            match binox.solve(true) {
-                Zero => return Err("something went wrong"),
-                One(_) => break,
-                Multiple(a, b) => {
-                    let diff = a.get_differences(b)?;
-                    if diff.is_empty() {
-                        break;
-                    }
-                    let pair = diff
-                        .get(rand::thread_rng().gen_range(0..diff.len()))
-                        .ok_or("something went wrong")?;
-                    if rand::random() {
-                        binox.set_x(pair.0, pair.1)?;
-                    } else {
-                        binox.set_o(pair.0, pair.1)?;
-                    }
+    fn lock_filled(&mut self) {
+        for row in 0..self.size {
+            for col in 0..self.size {
+                if self.get_cell(row, col).unwrap() != BinoxCell::EMPTY {
+                    self.set_default(row, col, true).unwrap();
                 }
             }
         }
+    }
 
-        //phase 3 - remove symbols that are not needed to find the solution
-        for row in 0..size {
-            for col in 0..size {
-                if binox.get_cell(row, col)? != BinoxCell::EMPTY {
-                    let current_cell = binox.get_cell(row, col)?;
-                    let mut clone = binox.clone();
-                    clone.set_empty(row, col)?;
-                    clone.presolve();
-                    if clone.get_cell(row, col)? == current_cell {
-                        binox.set_empty(row, col)?;
-                    }
-                }
-            }
+    /// Every line has at most half of its cells of each symbol and no three
+    /// identical symbols in a row.
+    fn lines_valid(&self) -> bool {
+        let size = self.size as usize;
+        [&self.x_rows, &self.o_rows, &self.x_cols, &self.o_cols]
+            .iter()
+            .all(|lines| BinRow::all_valid(&lines[..size]))
+    }
+
+    /// No two finished rows, and no two finished columns, are the same.
+    ///
+    /// A line with half of its cells of one symbol has all of them, so two
+    /// such lines with the same cells of that symbol end up the same.
+    fn global_valid(&self) -> bool {
+        self.duplicates == 0
+    }
+
+    fn is_valid_simple(&self) -> bool {
+        Binox::is_valid_simple(self)
+    }
+
+    fn is_full(&self) -> bool {
+        Binox::is_full(self)
+    }
+
+    /// Runs the propagation engine with every line technique.
+    fn propagate(&mut self) -> PresolveResult {
+        if propagation::propagate(self, &propagation::LOGIC).contradiction {
+            PresolveResult::Bad
+        } else {
+            PresolveResult::Good
         }
+    }
+}
 
-        //phase 3 - if perfect generation is set, remove even more symbols that are not needed to find the solution
-        if perfect {
-            for row in 0..size {
-                for col in 0..size {
-                    if binox.get_cell(row, col)? != BinoxCell::EMPTY {
-                        let current_cell = binox.get_cell(row, col)?;
-                        binox.set_empty(row, col)?;
-                        if let Multiple(..) = binox.solve(true) {
-                            binox.set_cell(row, col, current_cell)?;
+/// Replaces how a cell is drawn, given its row, column and contents.
+#[cfg(feature = "color")]
+type Overlay<'a> = dyn Fn(u8, u8, BinoxCell) -> Option<ColoredString> + 'a;
+
+/// The letter naming a column in board drawings and cell coordinates.
+pub fn column_name(col: u8) -> char {
+    (b'a' + col) as char
+}
+
+impl Binox {
+    /// Draws the board with ASCII characters only and no color codes, for
+    /// pasting into plain text. Given cells are upper case, placed symbols are
+    /// lower case and empty cells are dots. Symbols from the theme are used if
+    /// they are ASCII.
+    pub fn render_plain(&self) -> String {
+        let theme = current_theme();
+        let mut result = String::from("   |");
+        for i in 0..self.size {
+            result.push_str(&format!(" {} |", column_name(i)));
+        }
+        let separator = "---+".repeat(self.size as usize + 1);
+        result.push('\n');
+        result.push_str(&separator);
+        for i in 0..self.size {
+            result.push_str(&format!("\n{i:>2} |"));
+            for j in 0..self.size {
+                let c = match self.get_cell(i, j).unwrap() {
+                    BinoxCell::EMPTY => '.',
+                    cell => {
+                        let symbol = match theme.symbol(cell) {
+                            symbol if symbol.is_ascii() => symbol,
+                            _ => cell.into(),
+                        };
+                        if self.is_default(i, j).unwrap() {
+                            symbol.to_ascii_uppercase()
+                        } else {
+                            symbol.to_ascii_lowercase()
                         }
                     }
-                }
+                };
+                result.push_str(&format!(" {c} |"));
             }
+            result.push('\n');
+            result.push_str(&separator);
         }
+        result
+    }
+}
 
-        //phase 5 - add more cells if specified
-        if extras > 0 {
-            let mut clone = binox.clone();
-            clone.presolve_simple();
-            let mut empties = clone.get_empties();
-            let num = if empties.len() > extras {
-                extras
-            } else {
-                empties.len()
-            };
-            empties.shuffle(&mut rand::thread_rng());
-            clone = match clone.solve(true) {
-                Zero => return Err("something went wrong"),
-                One(a) => a,
-                Multiple(a, _) => a,
-            };
-
-            for (row, col) in empties.iter().take(num) {
-                binox.set_cell(*row, *col, clone.get_cell(*row, *col).unwrap())?;
+#[cfg(feature = "color")]
+impl Binox {
+    /// Adds the X and O counts of each row to the end of its line, and of each
+    /// column below the drawing. A count is green once the line has its
+    /// quota and red past it. `rendered` must be a drawing of this board.
+    pub fn with_tallies(&self, rendered: &str) -> String {
+        let theme = current_theme();
+        let quota = self.size / 2;
+        let tally = |count: u8| {
+            let text = count.to_string();
+            match count.cmp(&quota) {
+                Ordering::Less => text.normal(),
+                Ordering::Equal => text.green(),
+                Ordering::Greater => text.red().bold(),
+            }
+        };
+        let count = |is_row: bool, index: u8, cell: BinoxCell| {
+            (0..self.size)
+                .filter(|&i| {
+                    let (row, col) = if is_row { (index, i) } else { (i, index) };
+                    self.get_cell(row, col).unwrap() == cell
+                })
+                .count() as u8
+        };
+        let symbols = [BinoxCell::X, BinoxCell::O].map(|cell| (cell, theme.symbol(cell)));
+        let mut lines = Vec::new();
+        let mut row = 0;
+        for line in rendered.lines() {
+            let is_row_line = row < self.size
+                && line.chars().nth(3).is_some_and(|c| c == '|' || c == '│')
+                && line.get(..2).and_then(|n| n.trim().parse().ok()) == Some(row);
+            if !is_row_line {
+                lines.push(line.to_string());
+                continue;
             }
+            let mut line = format!("{line} ");
+            for (cell, symbol) in symbols {
+                line.push_str(&format!(
+                    " {symbol}{}/{quota}",
+                    tally(count(true, row, cell))
+                ));
+            }
+            lines.push(line);
+            row += 1;
+        }
+        let header = rendered.lines().next().unwrap_or_default();
+        let positions: Vec<usize> = (0..self.size)
+            .filter_map(|col| header.chars().position(|c| c == column_name(col)))
+            .collect();
+        for (cell, symbol) in symbols {
+            let mut line = format!(" {symbol}");
+            let mut width = 2;
+            for (col, &position) in positions.iter().enumerate() {
+                line.push_str(&" ".repeat(position.saturating_sub(width)));
+                line.push_str(&tally(count(false, col as u8, cell)).to_string());
+                width = position + 1;
+            }
+            lines.push(line);
         }
+        lines.join("\n")
+    }
 
-        binox.make_cells_unmodifiable();
-        Ok(binox)
+    /// Draws the board with box-drawing characters and no lines between rows.
+    /// Given cells are shaded.
+    pub fn render_boxed(&self) -> String {
+        self.render_boxed_with(&|_, _, _| None)
     }
 
-    fn get_differences(&self, other: Binox) -> Result<Vec<(u8, u8)>, &'static str> {
-        if self.size != other.size {
-            return Err("must be same size");
-        }
-        let mut result = Vec::new();
-        for row in 0..self.size {
-            for col in 0..self.size {
-                if self.get_cell(row, col) != other.get_cell(row, col) {
-                    result.push((row, col));
-                }
+    /// Draws the board in `style` with pencil marks, the cells changed by the
+    /// most recent move in reverse video, and the cells of a hint highlighted.
+    pub fn render_marked(
+        &self,
+        marks: &Marks,
+        recent: &[(u8, u8)],
+        hinted: &[(u8, u8)],
+        style: BoardStyle,
+    ) -> String {
+        let theme = current_theme();
+        self.render_with(style, &|row, col, cell| {
+            let glyph = marks.glyph(row, col, cell);
+            if hinted.contains(&(row, col)) {
+                let glyph = glyph.unwrap_or_else(|| theme.paint(cell));
+                Some(glyph.on_color(theme.highlight_color()).bold())
+            } else if recent.contains(&(row, col)) {
+                Some(glyph.unwrap_or_else(|| theme.paint(cell)).reversed())
+            } else {
+                glyph
             }
-        }
-        Ok(result)
+        })
     }
 
-    fn get_empties(&self) -> Vec<(u8, u8)> {
-        let mut result = Vec::new();
-        for row in 0..self.size {
-            for col in 0..self.size {
-                if self.get_cell(row, col).unwrap() == BinoxCell::EMPTY {
-                    result.push((row, col));
-                }
+    /// Draws the board in `style` with the given cells highlighted.
+    pub fn render_highlighted(&self, cells: &[(u8, u8)], style: BoardStyle) -> String {
+        let theme = current_theme();
+        self.render_with(style, &|row, col, cell| {
+            cells
+                .contains(&(row, col))
+                .then(|| theme.paint(cell).on_color(theme.highlight_color()).bold())
+        })
+    }
+
+    fn render_with(&self, style: BoardStyle, overlay: &Overlay) -> String {
+        match style {
+            BoardStyle::Classic => {
+                let mut result = String::new();
+                self.write_classic(&mut result, overlay).unwrap();
+                result
             }
+            BoardStyle::Boxed => self.render_boxed_with(overlay),
         }
-        result
     }
 
-    fn make_cells_unmodifiable(&mut self) {
-        for row in 0..self.size {
-            for col in 0..self.size {
-                if self.get_cell(row, col).unwrap() != BinoxCell::EMPTY {
-                    self.set_default(row, col, true).unwrap();
+    fn render_boxed_with(&self, overlay: &Overlay) -> String {
+        let theme = current_theme();
+        let mut result = String::from("   │");
+        for i in 0..self.size {
+            result.push_str(&format!(" {} ", column_name(i)));
+        }
+        result.push_str(&format!("\n───┼{}", "───".repeat(self.size as usize)));
+        for i in 0..self.size {
+            result.push_str(&format!("\n{i:>2} │"));
+            for j in 0..self.size {
+                let cell = self.get_cell(i, j).unwrap();
+                let symbol = match (overlay(i, j, cell), cell) {
+                    (Some(symbol), _) => symbol,
+                    (None, BinoxCell::EMPTY) => "·".normal(),
+                    (None, cell) => theme.paint(cell),
+                };
+                if self.is_default(i, j).unwrap() {
+                    result.push_str(&format!("░{}░", theme.given(symbol)));
+                } else {
+                    result.push_str(&format!(" {symbol} "));
                 }
             }
         }
+        result
     }
 }
 
-fn alternated_range(n: u8) -> std::vec::IntoIter<u8> {
-    let mut result = Vec::new();
-    for i in 0..n {
-        if i % 2 == 0 {
-            result.push(i);
-        }
-    }
-    for i in 0..n {
-        if i % 2 == 1 {
-            result.push(i);
-        }
+#[cfg(feature = "color")]
+impl fmt::Display for Binox {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_classic(f, &|_, _, _| None)
     }
-    result.into_iter()
 }
 
+/// Without colors the board is drawn as by [`Binox::render_plain`].
+#[cfg(not(feature = "color"))]
 impl fmt::Display for Binox {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.render_plain())
+    }
+}
+
+#[cfg(feature = "color")]
+impl Binox {
+    fn write_classic(&self, f: &mut impl fmt::Write, overlay: &Overlay) -> fmt::Result {
+        let theme = current_theme();
         write!(f, "   |")?;
         for i in 0..self.size {
-            write!(f, "{i:>2} |")?;
+            write!(f, " {} |", column_name(i))?;
         }
         writeln!(f)?;
         for _ in 0..self.size + 1 {
@@ -540,9 +884,10 @@ impl fmt::Display for Binox {
             writeln!(f)?;
             write!(f, "{i:>2} |")?;
             for j in 0..self.size {
-                let mut c: ColoredString = self.get_cell(i, j).unwrap().into();
+                let cell = self.get_cell(i, j).unwrap();
+                let mut c = overlay(i, j, cell).unwrap_or_else(|| theme.paint(cell));
                 if self.is_default(i, j).unwrap() {
-                    c = c.bold();
+                    c = theme.given(c);
                 }
                 write!(f, " {} |", c)?;
             }
@@ -559,6 +904,103 @@ impl fmt::Display for Binox {
 #[cfg(test)]
 mod test {
     use super::*;
+    #[test]
+    fn plain_rendering() {
+        let mut b = Binox::new_from_string("X...............".into());
+        b.set_cell(0, 1, BinoxCell::O).unwrap();
+        let plain = b.render_plain();
+        assert!(plain.is_ascii());
+        assert!(!plain.contains('\x1b'));
+        assert_eq!(plain.lines().nth(2).unwrap(), " 0 | X | o | . | . |");
+        assert_eq!(plain.lines().count(), 10);
+    }
+
+    #[test]
+    fn large_board_labels() {
+        let plain = Binox::new(16).unwrap().render_plain();
+        let lines: Vec<&str> = plain.lines().collect();
+        assert!(lines[0].ends_with("| k | l | m | n | o | p |"));
+        assert!(lines.iter().all(|line| line.len() == lines[0].len()));
+        assert!(lines[2].starts_with(" 0 |") && lines[22].starts_with("10 |"));
+    }
+
+    #[test]
+    #[cfg(feature = "color")]
+    fn tallies() {
+        colored::control::set_override(false);
+        let b = Binox::new_from_string("XX.O..O.XXX.....".into());
+        let tallied = b.with_tallies(&b.render_plain());
+        let lines: Vec<&str> = tallied.lines().collect();
+        assert_eq!(lines.len(), 12);
+        assert_eq!(lines[2], " 0 | X | X | . | O |  X2/2 O1/2");
+        assert_eq!(lines[6], " 2 | X | X | X | . |  X3/2 O0/2");
+        assert_eq!(lines[10], " X   2   2   1   0");
+        assert_eq!(lines[11], " O   0   0   1   1");
+    }
+
+    #[test]
+    #[cfg(feature = "color")]
+    fn boxed_rendering() {
+        colored::control::set_override(false);
+        let mut b = Binox::new_from_string("X...............".into());
+        b.set_cell(0, 1, BinoxCell::O).unwrap();
+        let boxed = b.render_boxed();
+        assert_eq!(boxed.lines().count(), 6);
+        assert_eq!(boxed.lines().nth(2).unwrap(), " 0 │░X░ O  ·  · ");
+    }
+
+    #[test]
+    fn mistakes_against_solution() {
+        let mut b = Binox::new_from_string("XXO.O..X.O..X..O".into());
+        let solution = b.unique_solution().unwrap();
+        assert!(b.fits(&solution));
+        assert!(!b.fits(&Binox::new_from_string("xxooooxxxxooooxx".into())));
+        b.set_cell(0, 3, BinoxCell::X).unwrap();
+        b.set_cell(1, 1, BinoxCell::X).unwrap();
+        assert_eq!(b.differences(&solution), vec![(0, 3)]);
+        assert_eq!(b.changed_cells(&solution).len(), 7);
+        assert!(Binox::new(4).unwrap().unique_solution().is_none());
+    }
+
+    #[test]
+    fn solution_counting() {
+        let b = Binox::new_from_string("XXO.O..X.O..X..O".into());
+        assert_eq!(b.count_solutions(100), 1);
+        let empty = Binox::new(4).unwrap();
+        assert_eq!(empty.count_solutions(1000), 72);
+        assert_eq!(empty.count_solutions(10), 10);
+        assert_eq!(empty.count_solutions(0), 0);
+        let bad = Binox::new_from_string("xxx.............".into());
+        assert_eq!(bad.count_solutions(10), 0);
+    }
+
+    #[test]
+    fn locking() {
+        let mut b = Binox::new_from_string("X.o.............".into());
+        assert_eq!(b.lock(0, 1), Err(BinoxError::Empty { row: 0, col: 1 }));
+        b.lock(0, 2).unwrap();
+        let error = b.set_cell(0, 2, BinoxCell::X).unwrap_err();
+        assert_eq!(error, BinoxError::Given { row: 0, col: 2 });
+        assert_eq!(
+            error.to_string(),
+            "cell c0 is a given and cannot be modified"
+        );
+        let error = b.set_cell(5, 1, BinoxCell::X).unwrap_err();
+        assert_eq!(error.cell(), (5, 1));
+        assert_eq!(
+            error.to_string(),
+            "row 5, column 1 is outside the 4x4 board"
+        );
+        assert_eq!(b.as_string(), "X.O.............");
+        b.unlock(0, 0).unwrap();
+        assert!(b.unlock(0, 0).is_err());
+        b.unlock_all();
+        assert_eq!(b.as_string(), "x.o.............");
+        b.lock(0, 0).unwrap();
+        assert_eq!(b.puzzle_string(false), "X...............");
+        assert_eq!(b.puzzle_string(true), "X.O.............");
+    }
+
     #[test]
     fn full_valid_solved() {
         let b = Binox::new_from_string("xx x            ".into());
@@ -578,4 +1020,104 @@ mod test {
         assert!(b.is_valid());
         assert!(b.is_solved());
     }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn seeded_symmetric_generation() {
+        use rand::rngs::StdRng;
+        use rand::SeedableRng;
+        let generate = |seed| {
+            let mut rng = StdRng::seed_from_u64(seed);
+            Binox::generate_with_rng(8, false, 2, Symmetry::Rotational, &mut rng).unwrap()
+        };
+        let (a, _) = generate(7);
+        let (b, _) = generate(7);
+        assert_eq!(a.as_string(), b.as_string());
+        for row in 0..8 {
+            for col in 0..8 {
+                assert_eq!(
+                    a.get_cell(row, col).unwrap() == BinoxCell::EMPTY,
+                    a.get_cell(7 - row, 7 - col).unwrap() == BinoxCell::EMPTY
+                );
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn generated_solution_matches() {
+        let (binox, solution) = Binox::generate_with_solution(6, false, 0).unwrap();
+        assert!(solution.is_solved());
+        for row in 0..6 {
+            for col in 0..6 {
+                let cell = binox.get_cell(row, col).unwrap();
+                if cell != BinoxCell::EMPTY {
+                    assert!(cell == solution.get_cell(row, col).unwrap());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn cached_solution() {
+        let mut binox = Binox::new_from_string("X......O...X..XX....X..O....X..XX...".into());
+        let solution = binox.solution().unwrap();
+        assert!(matches!(binox.solution, CachedSolution::Unique(_)));
+        binox.set_cell(0, 1, BinoxCell::X).unwrap();
+        let copy = binox.clone();
+        assert_eq!(
+            copy.unique_solution().unwrap().as_string(),
+            solution.as_string()
+        );
+        binox.unlock_all();
+        assert!(matches!(binox.solution, CachedSolution::Unknown));
+        assert!(binox.solution().is_none());
+        assert!(matches!(binox.solution, CachedSolution::NotUnique));
+    }
+
+    #[test]
+    fn equality_and_hashing() {
+        use std::collections::HashSet;
+
+        let puzzle = Binox::new_from_string("X......O...X..XX....X..O....X..XX...".into());
+        let mut played = puzzle.clone();
+        played.set_cell(0, 1, BinoxCell::O).unwrap();
+        assert_ne!(played, puzzle);
+        played.set_cell(0, 1, BinoxCell::EMPTY).unwrap();
+        played.solution();
+        assert_eq!(played, puzzle);
+        let mut locked = Binox::new_from_string("x...............".into());
+        assert_ne!(locked, Binox::new_from_string("X...............".into()));
+        locked.lock(0, 0).unwrap();
+        assert_eq!(locked, Binox::new_from_string("X...............".into()));
+        let boards: HashSet<Binox> = [puzzle, played, Binox::default(), Binox::new(8).unwrap()]
+            .into_iter()
+            .collect();
+        assert_eq!(boards.len(), 2);
+    }
+
+    #[test]
+    fn filling_comparison() {
+        let key = Binox::new_from_string("XOXOOXOXXOOXOXXO".into());
+        let played = Binox::new_from_string("XoxOoxOxXooxoxXo".into());
+        assert_ne!(played, key);
+        assert!(played.same_filling(&key) && key.same_filling(&played));
+        let mut wrong = played.clone();
+        wrong.set_cell(0, 1, BinoxCell::X).unwrap();
+        assert!(!wrong.same_filling(&key));
+        assert!(!Binox::new(4).unwrap().same_filling(&Binox::new(6).unwrap()));
+    }
+
+    #[test]
+    fn duplicate_tracking() {
+        let mut binox = Binox::new_from_string("xoxo....xo......".into());
+        assert!(binox.is_valid());
+        binox.set_cell(2, 2, BinoxCell::X).unwrap();
+        assert!(!binox.is_valid());
+        binox.set_cell(2, 2, BinoxCell::EMPTY).unwrap();
+        assert!(binox.is_valid());
+        binox.set_cell(2, 2, BinoxCell::X).unwrap();
+        binox.set_cell(0, 2, BinoxCell::EMPTY).unwrap();
+        assert!(binox.is_valid());
+    }
 }
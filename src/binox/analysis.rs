@@ -0,0 +1,166 @@
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use crate::binox::propagation::{Line, LOGIC};
+use crate::binox::{Binox, BinoxCell};
+use crate::puzzle::{PresolveResult, Puzzle, SearchStats};
+
+/// The largest backdoor looked for. Larger ones take too long to find.
+const MAX_BACKDOOR: usize = 3;
+
+/// Measures of how hard a puzzle is for a solver, rather than for a player.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Analysis {
+    /// The fewest cells that, given their solution values, let propagation
+    /// finish the puzzle without guessing. 0 for a puzzle propagation solves
+    /// on its own, and `None` when more than 3 cells are needed or the puzzle
+    /// does not have exactly one solution.
+    pub backdoor: Option<usize>,
+    /// How many rounds propagation takes when every round fills all the
+    /// cells that can be deduced from the board as it was at its start.
+    pub propagation_depth: usize,
+    /// What the search for a solution did.
+    pub search: SearchStats,
+    /// The average number of symbols the search tried at each guess: 1 if
+    /// every first guess was right, up to 2 if it was always wrong. 0 if the
+    /// search never guessed.
+    pub branching_factor: f64,
+}
+
+impl Binox {
+    /// Measures the puzzle, or gives up with `None` once `stop` is set.
+    ///
+    /// Most of the time goes to the backdoor: every set of up to 3 empty
+    /// cells is given its solution values and propagated, so a board with
+    /// `e` empty cells can take around `e³ / 6` propagations. That is
+    /// instant up to 8x8 but can take minutes on a sparse 14x14 or larger,
+    /// which is what `stop` is for.
+    pub fn analyze(&self, stop: &AtomicBool) -> Option<Analysis> {
+        let mut search = SearchStats::default();
+        self.search_with_stats(false, stop, &mut search)?;
+        let backdoor = match self.unique_solution() {
+            Some(solution) => {
+                let mut found = None;
+                for k in 0..=MAX_BACKDOOR {
+                    if self.has_backdoor(&solution, k, 0, stop)? {
+                        found = Some(k);
+                        break;
+                    }
+                }
+                found
+            }
+            None => None,
+        };
+        Some(Analysis {
+            backdoor,
+            propagation_depth: self.propagation_depth(),
+            search,
+            branching_factor: if search.guesses == 0 {
+                0.0
+            } else {
+                search.branches as f64 / search.guesses as f64
+            },
+        })
+    }
+
+    /// Whether giving at most `cells` more cells their solution values lets
+    /// propagation finish the board. Only cells from the `from`th on, in
+    /// reading order, are given, so that each set of cells is tried once.
+    /// `None` if `stop` was set before the answer was found.
+    fn has_backdoor(
+        &self,
+        solution: &Binox,
+        cells: usize,
+        from: usize,
+        stop: &AtomicBool,
+    ) -> Option<bool> {
+        if stop.load(Ordering::Relaxed) {
+            return None;
+        }
+        let mut board = self.clone();
+        if let PresolveResult::Bad = board.propagate() {
+            return Some(false);
+        }
+        if board.is_full() {
+            return Some(true);
+        }
+        if cells == 0 {
+            return Some(false);
+        }
+        let size = self.size as usize;
+        for (row, col) in board.empty_cells() {
+            let index = row as usize * size + col as usize;
+            if index < from {
+                continue;
+            }
+            let mut given = board.clone();
+            given.put(row, col, solution.cell(row, col));
+            if given.has_backdoor(solution, cells - 1, index + 1, stop)? {
+                return Some(true);
+            }
+        }
+        Some(false)
+    }
+
+    fn propagation_depth(&self) -> usize {
+        let mut board = self.clone();
+        let lines: Vec<Line> = (0..self.size)
+            .flat_map(|index| [true, false].map(|is_row| Line { is_row, index }))
+            .collect();
+        let mut rounds = 0;
+        loop {
+            let start = board.clone();
+            let mut changed = false;
+            for &line in &lines {
+                for propagator in LOGIC {
+                    let Ok(deductions) = propagator.propagate(&start, line) else {
+                        return rounds;
+                    };
+                    for deduction in deductions {
+                        if board.cell(deduction.row, deduction.col) == BinoxCell::EMPTY {
+                            board.apply_deduction(&deduction);
+                            changed = true;
+                        }
+                    }
+                }
+            }
+            if !changed {
+                return rounds;
+            }
+            rounds += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn hardness() {
+        let easy = Binox::new_from_string("X......O...X..XX....X..O....X..XX...".into());
+        let stop = AtomicBool::new(false);
+        let analysis = easy.analyze(&stop).unwrap();
+        assert_eq!(analysis.backdoor, Some(0));
+        assert!(analysis.propagation_depth > 0);
+        assert_eq!(analysis.search.guesses, 0);
+        assert_eq!(analysis.branching_factor, 0.0);
+
+        let guessed = Binox::new_from_string(
+            "..........O.X.X.X......O.....XX.O..O.................O..OO...O.....O.O..X.....X...X..X.X....O...XX.......X..........O.....XX..O...X....X.X.X...O".into(),
+        );
+        let analysis = guessed.analyze(&stop).unwrap();
+        assert_eq!(analysis.backdoor, Some(1));
+        assert_eq!(analysis.search.guesses, 1);
+        assert_eq!(analysis.branching_factor, 1.0);
+
+        let blank = Binox::new(6).unwrap().analyze(&stop).unwrap();
+        assert_eq!(blank.backdoor, None);
+        assert_eq!(blank.propagation_depth, 0);
+        assert!(blank.search.guesses > 0 && blank.search.depth > 0);
+        assert!((1.0..=2.0).contains(&blank.branching_factor));
+
+        stop.store(true, Ordering::Relaxed);
+        assert_eq!(guessed.analyze(&stop), None);
+    }
+}
@@ -0,0 +1,227 @@
+use alloc::collections::BTreeMap;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::binox::logic::{full_mask, valid_lines};
+use crate::binox::{Binox, BinoxCell};
+
+/// The largest size whose boards can be counted or listed.
+const LARGEST_COUNTED: u8 = 8;
+
+/// Whether a line has three set bits in a row, that is, three of the symbol
+/// the mask marks next to each other.
+fn has_triple(mask: u16) -> bool {
+    mask & mask << 1 & mask >> 1 != 0
+}
+
+/// What counting needs to know about the rows placed so far, packed into
+/// bytes. Grids that agree on it can be finished in exactly the same ways.
+/// Since it records which rows are used, there are about as many of them as
+/// sets of rows, so this is not a column-profile count.
+///
+/// For each column there is a byte with its number of X cells, its last
+/// symbol in bit 4, and how many times that symbol repeats at the end in bits
+/// 5 and up; then for each column, which group of identical columns it is in
+/// so far, numbered by first appearance; then a bit for each valid line that
+/// is already a row.
+type Profile = Vec<u8>;
+
+/// The profile after adding a row, or `None` if the row breaks a column or
+/// was used already. `placed` is the number of rows before it.
+fn with_row(profile: &Profile, size: u8, index: usize, row: u16, placed: u8) -> Option<Profile> {
+    let size = size as usize;
+    let (columns, rest) = profile.split_at(size);
+    let (groups, used) = rest.split_at(size);
+    if used[index / 8] & (1 << (index % 8)) != 0 {
+        return None;
+    }
+    let half = size as u8 / 2;
+    let mut next = Vec::with_capacity(profile.len());
+    for (col, &column) in columns.iter().enumerate() {
+        let x = (row >> col & 1) as u8;
+        let (xs, last, run) = (column & 15, column >> 4 & 1, column >> 5);
+        let (xs, os) = (xs + x, placed + 1 - xs - x);
+        let run = if placed > 0 && x == last { run + 1 } else { 1 };
+        if xs > half || os > half || run > 2 {
+            return None;
+        }
+        next.push(xs | x << 4 | run << 5);
+    }
+    let mut names: Vec<(u8, u8)> = Vec::new();
+    for (col, &group) in groups.iter().enumerate() {
+        let key = (group, (row >> col & 1) as u8);
+        let name = match names.iter().position(|&name| name == key) {
+            Some(name) => name,
+            None => {
+                names.push(key);
+                names.len() - 1
+            }
+        };
+        next.push(name as u8);
+    }
+    next.extend_from_slice(used);
+    next[2 * size + index / 8] |= 1 << (index % 8);
+    Some(next)
+}
+
+impl Binox {
+    /// How many complete boards of the size follow every rule. This is a
+    /// search that adds rows one at a time and counts partial grids together
+    /// when they can be finished in the same ways, which includes having used
+    /// the same rows. That keeps it exponential: 8x8 takes seconds, and the
+    /// partial 10x10 grids do not fit in memory, so only sizes up to 8 can be
+    /// counted.
+    pub fn count_valid_grids(size: u8) -> Result<u128, &'static str> {
+        Binox::new(size)?;
+        if size > LARGEST_COUNTED {
+            return Err("boards can only be counted up to size 8");
+        }
+        let lines = valid_lines(size);
+        let mut start = vec![0; 2 * size as usize];
+        start.resize(start.len() + lines.len().div_ceil(8), 0);
+        let mut profiles = BTreeMap::new();
+        profiles.insert(start, 1u128);
+        for placed in 0..size {
+            let mut next = BTreeMap::new();
+            for (profile, count) in profiles {
+                for (index, &row) in lines.iter().enumerate() {
+                    // Swapping every X and O gives another valid board, so only
+                    // the boards with an X in the corner are counted.
+                    if placed == 0 && row & 1 == 0 {
+                        continue;
+                    }
+                    if let Some(profile) = with_row(&profile, size, index, row, placed) {
+                        *next.entry(profile).or_insert(0) += count;
+                    }
+                }
+            }
+            profiles = next;
+        }
+        let groups = size as usize..2 * size as usize;
+        let distinct = profiles
+            .iter()
+            .filter(|(profile, _)| {
+                profile[groups.clone()]
+                    .iter()
+                    .enumerate()
+                    .all(|(col, &group)| col == group as usize)
+            })
+            .map(|(_, count)| count)
+            .sum::<u128>();
+        Ok(2 * distinct)
+    }
+
+    /// Every complete board of the size that follows every rule, in order of
+    /// their rows. Only sizes up to 8 can be listed.
+    pub fn valid_grids(size: u8) -> Result<ValidGrids, &'static str> {
+        Binox::new(size)?;
+        if size > LARGEST_COUNTED {
+            return Err("boards can only be listed up to size 8");
+        }
+        Ok(ValidGrids {
+            size,
+            lines: valid_lines(size),
+            rows: Vec::new(),
+            next: 0,
+        })
+    }
+}
+
+/// The iterator returned by [`Binox::valid_grids`].
+pub struct ValidGrids {
+    size: u8,
+    lines: &'static [u16],
+    /// The indices in `lines` of the rows placed so far.
+    rows: Vec<usize>,
+    /// The next line to try as the following row.
+    next: usize,
+}
+
+impl ValidGrids {
+    fn column(&self, col: u8, extra: usize) -> u16 {
+        self.rows
+            .iter()
+            .chain([&extra])
+            .enumerate()
+            .fold(0, |mask, (row, &line)| {
+                mask | (self.lines[line] >> col & 1) << row
+            })
+    }
+
+    /// Whether the line can be the next row.
+    fn fits(&self, line: usize) -> bool {
+        if self.rows.contains(&line) {
+            return false;
+        }
+        let placed = self.rows.len() as u8 + 1;
+        (0..self.size).all(|col| {
+            let x = self.column(col, line);
+            let o = full_mask(placed) & !x;
+            x.count_ones() <= self.size as u32 / 2
+                && o.count_ones() <= self.size as u32 / 2
+                && !has_triple(x)
+                && !has_triple(o)
+        })
+    }
+
+    fn board(&self) -> Binox {
+        let mut binox = Binox::new(self.size).unwrap();
+        for (row, &line) in self.rows.iter().enumerate() {
+            for col in 0..self.size {
+                let cell = if self.lines[line] >> col & 1 == 1 {
+                    BinoxCell::X
+                } else {
+                    BinoxCell::O
+                };
+                binox.set_cell(row as u8, col, cell).unwrap();
+            }
+        }
+        binox
+    }
+}
+
+impl Iterator for ValidGrids {
+    type Item = Binox;
+
+    fn next(&mut self) -> Option<Binox> {
+        loop {
+            if self.next >= self.lines.len() {
+                self.next = self.rows.pop()? + 1;
+                continue;
+            }
+            let line = self.next;
+            if !self.fits(line) {
+                self.next += 1;
+                continue;
+            }
+            self.rows.push(line);
+            self.next = 0;
+            if self.rows.len() < self.size as usize {
+                continue;
+            }
+            let board = self.board();
+            self.next = self.rows.pop().unwrap() + 1;
+            if board.is_valid() {
+                return Some(board);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn counting_grids() {
+        assert_eq!(Binox::count_valid_grids(4), Ok(72));
+        assert_eq!(Binox::count_valid_grids(6), Ok(4140));
+        assert_eq!(Binox::count_valid_grids(5).err(), Some("size must be even"));
+
+        let grids: Vec<Binox> = Binox::valid_grids(6).unwrap().collect();
+        assert_eq!(grids.len(), 4140);
+        assert!(grids.iter().all(|grid| grid.is_solved()));
+        assert!(Binox::valid_grids(10).is_err());
+        assert!(Binox::count_valid_grids(10).is_err());
+    }
+}
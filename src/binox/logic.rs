@@ -0,0 +1,565 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+use alloc::{format, vec};
+use core::fmt;
+use core::str::FromStr;
+
+use rand::Rng;
+
+use spin::Once;
+
+use crate::binox::propagation::{propagate, propagator, Line, LOGIC};
+use crate::binox::{column_name, Binox, BinoxCell, Symmetry};
+use crate::difficulty::Difficulty;
+use crate::theme::current_theme;
+
+/// How many puzzles of each size [`Technique::example`] solves looking for
+/// the technique.
+const EXAMPLE_ATTEMPTS: usize = 20;
+
+/// The logical techniques used to deduce cells, from simplest to hardest.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Technique {
+    /// Two identical neighbouring symbols force the opposite symbol next to them.
+    Pair,
+    /// Two identical symbols with one cell between them force the opposite symbol in between.
+    Gap,
+    /// A line that already has half of its cells of one symbol is completed with the other.
+    Count,
+    /// Every valid way to complete the line agrees on the cell.
+    LineAnalysis,
+    /// Every way to complete the line that does not duplicate a finished line agrees on the cell.
+    Uniqueness,
+    /// Placing the other symbol leads to a contradiction using the simpler techniques.
+    Trial,
+}
+
+impl Technique {
+    pub const ALL: [Technique; 6] = [
+        Technique::Pair,
+        Technique::Gap,
+        Technique::Count,
+        Technique::LineAnalysis,
+        Technique::Uniqueness,
+        Technique::Trial,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Technique::Pair => "pair",
+            Technique::Gap => "gap",
+            Technique::Count => "count",
+            Technique::LineAnalysis => "line analysis",
+            Technique::Uniqueness => "uniqueness",
+            Technique::Trial => "trial",
+        }
+    }
+
+    /// What the technique is, in a few sentences for players.
+    pub fn description(&self) -> &'static str {
+        match self {
+            Technique::Pair => {
+                "Three identical symbols in a row are not allowed, so two identical \
+                 symbols side by side force the other symbol on both sides of them."
+            }
+            Technique::Gap => {
+                "Two identical symbols with one empty cell between them force the \
+                 other symbol into the gap, which would otherwise make three in a row."
+            }
+            Technique::Count => {
+                "Every line holds as many X's as O's, so once a line has all of one \
+                 symbol, its empty cells take the other."
+            }
+            Technique::LineAnalysis => {
+                "List every way to finish a line without breaking a rule within it. \
+                 A cell that gets the same symbol in all of them must hold that symbol."
+            }
+            Technique::Uniqueness => {
+                "No two finished rows, and no two finished columns, may be the same. \
+                 Leaving out the ways to finish a line that would copy a finished line \
+                 can make the rest agree on a cell."
+            }
+            Technique::Trial => {
+                "Put a symbol in a cell and follow the simpler techniques. If that \
+                 leads to a broken rule, the cell takes the other symbol."
+            }
+        }
+    }
+
+    /// A board whose next deduction uses this technique, found by solving
+    /// puzzles generated with `rng`, together with that deduction.
+    pub fn example<R: Rng>(&self, rng: &mut R) -> Option<(Binox, Deduction)> {
+        for size in [6, 8] {
+            for _ in 0..EXAMPLE_ATTEMPTS {
+                let (mut board, _) =
+                    Binox::generate_with_rng(size, true, 0, Symmetry::None, rng).ok()?;
+                while let Some(deduction) = board.next_deduction() {
+                    if deduction.technique == *self {
+                        return Some((board, deduction));
+                    }
+                    board.apply_deduction(&deduction);
+                }
+            }
+        }
+        None
+    }
+
+    /// How hard a single use of this technique is, used to score puzzles.
+    pub fn weight(&self) -> f64 {
+        match self {
+            Technique::Pair | Technique::Gap => 1.0,
+            Technique::Count => 2.0,
+            Technique::LineAnalysis => 4.0,
+            Technique::Uniqueness => 6.0,
+            Technique::Trial => 12.0,
+        }
+    }
+}
+
+impl FromStr for Technique {
+    type Err = &'static str;
+
+    /// Parses a technique name, with a hyphen or underscore allowed in place
+    /// of the space in "line analysis".
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.trim().to_lowercase().replace(['-', '_'], " ");
+        Technique::ALL
+            .into_iter()
+            .find(|technique| technique.name() == name)
+            .ok_or("unknown technique")
+    }
+}
+
+impl fmt::Display for Technique {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(self.name())
+    }
+}
+
+/// A single cell that can be filled by logic, with the cells that justify it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Deduction {
+    pub row: u8,
+    pub col: u8,
+    pub cell: BinoxCell,
+    pub technique: Technique,
+    pub reasons: Vec<(u8, u8)>,
+}
+
+impl Deduction {
+    /// The row or column that holds the reasoning, as `(is_row, index)`.
+    pub fn line(&self) -> (bool, u8) {
+        if self.reasons.iter().all(|&(row, _)| row == self.row) {
+            (true, self.row)
+        } else if self.reasons.iter().all(|&(_, col)| col == self.col) {
+            (false, self.col)
+        } else {
+            match self.reasons.first() {
+                Some(&(_, col)) if col == self.col => (false, self.col),
+                _ => (true, self.row),
+            }
+        }
+    }
+
+    /// Why the cell must hold its symbol, in words.
+    pub fn explanation(&self) -> String {
+        let theme = current_theme();
+        let cell = theme.symbol(self.cell);
+        let other = theme.symbol(opposite(&self.cell));
+        let line = match self.line() {
+            (true, row) => format!("row {row}"),
+            (false, col) => format!("column {}", column_name(col)),
+        };
+        match self.technique {
+            Technique::Pair => {
+                format!("a cell beside two adjacent {other}'s must be {cell}")
+            }
+            Technique::Gap => format!("the gap between two {other}'s must be {cell}"),
+            Technique::Count => format!("{line} already has all of its {other}'s"),
+            Technique::LineAnalysis => {
+                format!("every valid way to complete {line} has {cell} here")
+            }
+            Technique::Uniqueness => {
+                format!("{other} here would make {line} the same as a finished line")
+            }
+            Technique::Trial => format!("{other} here leads to a contradiction"),
+        }
+    }
+}
+
+/// The result of solving a puzzle with logic alone.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Rating {
+    pub difficulty: Difficulty,
+    /// Average technique weight per deduced cell.
+    pub score: f64,
+    /// How many cells each technique was used for, in technique order.
+    pub techniques: Vec<(Technique, usize)>,
+    pub clues: usize,
+    pub cells: usize,
+    /// False if the puzzle could not be finished without guessing.
+    pub solved_logically: bool,
+}
+
+impl Rating {
+    pub fn hardest_technique(&self) -> Option<Technique> {
+        self.techniques.last().map(|&(technique, _)| technique)
+    }
+}
+
+/// Every complete valid line of the given size, as bit patterns where a one is an X.
+pub(super) fn valid_lines(size: u8) -> &'static [u16] {
+    static LINES: Once<Vec<Vec<u16>>> = Once::new();
+    let lines = LINES.call_once(|| {
+        (0..=16u32)
+            .map(|size| {
+                if size < 4 || size % 2 == 1 {
+                    return Vec::new();
+                }
+                let full = (1u32 << size) - 1;
+                (0..=full)
+                    .filter(|&line| {
+                        let other = !line & full;
+                        line.count_ones() == size / 2
+                            && line & line << 1 & line >> 1 == 0
+                            && other & other << 1 & other >> 1 == 0
+                    })
+                    .map(|line| line as u16)
+                    .collect()
+            })
+            .collect()
+    });
+    &lines[size as usize]
+}
+
+pub(super) fn full_mask(size: u8) -> u16 {
+    ((1u32 << size) - 1) as u16
+}
+
+pub(super) fn opposite(cell: &BinoxCell) -> BinoxCell {
+    match cell {
+        BinoxCell::X => BinoxCell::O,
+        _ => BinoxCell::X,
+    }
+}
+
+impl Binox {
+    /// The x and o bit patterns of a row (or column if `is_row` is false).
+    pub(super) fn line(&self, is_row: bool, index: u8) -> (u16, u16) {
+        if is_row {
+            (
+                self.x_rows[index as usize].data,
+                self.o_rows[index as usize].data,
+            )
+        } else {
+            (
+                self.x_cols[index as usize].data,
+                self.o_cols[index as usize].data,
+            )
+        }
+    }
+
+    pub(super) fn line_cell(is_row: bool, index: u8, position: u8) -> (u8, u8) {
+        if is_row {
+            (index, position)
+        } else {
+            (position, index)
+        }
+    }
+
+    /// The valid completions of a line, optionally excluding completions that
+    /// duplicate a finished parallel line.
+    pub(super) fn line_completions(&self, is_row: bool, index: u8, unique: bool) -> Vec<u16> {
+        let (x, o) = self.line(is_row, index);
+        let full = full_mask(self.size);
+        let finished: Vec<u16> = if unique {
+            (0..self.size)
+                .filter(|&i| i != index)
+                .map(|i| self.line(is_row, i))
+                .filter(|&(x, o)| x | o == full)
+                .map(|(x, _)| x)
+                .collect()
+        } else {
+            Vec::new()
+        };
+        valid_lines(self.size)
+            .iter()
+            .copied()
+            .filter(|&line| line & x == x && line & o == 0 && !finished.contains(&line))
+            .collect()
+    }
+
+    /// True if some line can no longer be completed without breaking a rule.
+    fn has_contradiction(&self) -> bool {
+        !self.is_valid()
+            || (0..self.size).any(|i| {
+                self.line_completions(true, i, true).is_empty()
+                    || self.line_completions(false, i, true).is_empty()
+            })
+    }
+
+    pub(super) fn apply_deduction(&mut self, deduction: &Deduction) {
+        match deduction.cell {
+            BinoxCell::X => self.set_x(deduction.row, deduction.col).unwrap(),
+            BinoxCell::O => self.set_o(deduction.row, deduction.col).unwrap(),
+            BinoxCell::EMPTY => self.set_empty(deduction.row, deduction.col).unwrap(),
+        }
+    }
+
+    /// Finds the first cell that can be deduced with the given technique.
+    pub fn find_deduction(&self, technique: Technique) -> Option<Deduction> {
+        let Some(propagator) = propagator(technique) else {
+            return self.trial_deduction();
+        };
+        (0..self.size).find_map(|index| {
+            [true, false].into_iter().find_map(|is_row| {
+                propagator
+                    .propagate(self, Line { is_row, index })
+                    .ok()?
+                    .into_iter()
+                    .find(|deduction| deduction.technique == technique)
+            })
+        })
+    }
+
+    /// Every cell forced by the pair, gap and count techniques on the board as
+    /// it is, without following on from the cells they fill. Empty if the
+    /// board breaks a rule.
+    pub fn forced_cells(&self) -> Vec<Deduction> {
+        if !self.is_valid() {
+            return Vec::new();
+        }
+        let mut result: Vec<Deduction> = Vec::new();
+        for technique in [Technique::Pair, Technique::Gap, Technique::Count] {
+            let propagator = propagator(technique).unwrap();
+            for index in 0..self.size {
+                for is_row in [true, false] {
+                    let Ok(found) = propagator.propagate(self, Line { is_row, index }) else {
+                        return Vec::new();
+                    };
+                    for deduction in found.into_iter().filter(|d| d.technique == technique) {
+                        match result
+                            .iter()
+                            .find(|d| (d.row, d.col) == (deduction.row, deduction.col))
+                        {
+                            Some(d) if d.cell != deduction.cell => return Vec::new(),
+                            Some(_) => (),
+                            None => result.push(deduction),
+                        }
+                    }
+                }
+            }
+        }
+        result
+    }
+
+    /// The symbols that could still go in each empty cell, as `(row, col,
+    /// symbols)`. A symbol is possible if both the row and the column of the
+    /// cell can still be completed with it. A cell with no symbols shows that a
+    /// mistake has been made.
+    pub fn compute_candidates(&self) -> Vec<(u8, u8, Vec<BinoxCell>)> {
+        let rows: Vec<Vec<u16>> = (0..self.size)
+            .map(|i| self.line_completions(true, i, true))
+            .collect();
+        let cols: Vec<Vec<u16>> = (0..self.size)
+            .map(|i| self.line_completions(false, i, true))
+            .collect();
+        // Whether some completion has an X (or an O) at the position.
+        let possible = |lines: &[u16], position: u8, x: bool| {
+            lines.iter().any(|line| (line & (1 << position) != 0) == x)
+        };
+        let mut result = Vec::new();
+        for row in 0..self.size {
+            for col in 0..self.size {
+                if self.get_cell(row, col).unwrap() != BinoxCell::EMPTY {
+                    continue;
+                }
+                let symbols = [(BinoxCell::X, true), (BinoxCell::O, false)]
+                    .into_iter()
+                    .filter(|&(_, x)| {
+                        possible(&rows[row as usize], col, x)
+                            && possible(&cols[col as usize], row, x)
+                    })
+                    .map(|(cell, _)| cell)
+                    .collect();
+                result.push((row, col, symbols));
+            }
+        }
+        result
+    }
+
+    /// Finds a cell that can be deduced using the simplest possible technique.
+    pub fn next_deduction(&self) -> Option<Deduction> {
+        if self.is_full() || self.has_contradiction() {
+            return None;
+        }
+        Technique::ALL
+            .iter()
+            .find_map(|&technique| self.find_deduction(technique))
+    }
+
+    fn trial_deduction(&self) -> Option<Deduction> {
+        for row in 0..self.size {
+            for col in 0..self.size {
+                if self.get_cell(row, col).unwrap() != BinoxCell::EMPTY {
+                    continue;
+                }
+                for guess in [BinoxCell::X, BinoxCell::O] {
+                    let mut clone = self.clone();
+                    clone.apply_deduction(&Deduction {
+                        row,
+                        col,
+                        cell: guess,
+                        technique: Technique::Trial,
+                        reasons: Vec::new(),
+                    });
+                    let propagation = propagate(&mut clone, &LOGIC);
+                    if propagation.contradiction {
+                        let mut reasons = vec![(row, col)];
+                        reasons.extend(propagation.deductions.iter().map(|d| (d.row, d.col)));
+                        return Some(Deduction {
+                            row,
+                            col,
+                            cell: opposite(&guess),
+                            technique: Technique::Trial,
+                            reasons,
+                        });
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    fn count_clues(&self) -> usize {
+        (0..self.size)
+            .map(|i| (self.x_rows[i as usize].count + self.o_rows[i as usize].count) as usize)
+            .sum()
+    }
+
+    /// Solves the puzzle using logic alone and grades it by the hardest
+    /// technique that was needed.
+    pub fn rate(&self) -> Rating {
+        let mut board = self.clone();
+        let mut counts = [0usize; Technique::ALL.len()];
+        while let Some(deduction) = board.next_deduction() {
+            board.apply_deduction(&deduction);
+            counts[deduction.technique as usize] += 1;
+        }
+        let solved_logically = board.is_solved();
+        let techniques: Vec<(Technique, usize)> = Technique::ALL
+            .iter()
+            .zip(counts)
+            .filter(|&(_, count)| count > 0)
+            .map(|(&technique, count)| (technique, count))
+            .collect();
+        let deduced: usize = counts.iter().sum();
+        let score = if deduced == 0 {
+            0.0
+        } else {
+            techniques
+                .iter()
+                .map(|&(technique, count)| technique.weight() * count as f64)
+                .sum::<f64>()
+                / deduced as f64
+        };
+        let difficulty = if solved_logically {
+            Difficulty::from_score(score)
+        } else {
+            Difficulty::Expert
+        };
+        Rating {
+            difficulty,
+            score,
+            techniques,
+            clues: self.count_clues(),
+            cells: self.size as usize * self.size as usize,
+            solved_logically,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn valid_line_counts() {
+        assert_eq!(valid_lines(4).len(), 6);
+        assert_eq!(valid_lines(6).len(), 14);
+    }
+
+    #[test]
+    fn simple_techniques() {
+        let b = Binox::new_from_string("xx..............".into());
+        let d = b.find_deduction(Technique::Pair).unwrap();
+        assert_eq!((d.row, d.col, d.cell), (0, 2, BinoxCell::O));
+        assert_eq!(d.reasons, vec![(0, 0), (0, 1)]);
+        let b = Binox::new_from_string("x.x.............".into());
+        let d = b.find_deduction(Technique::Gap).unwrap();
+        assert_eq!((d.row, d.col, d.cell), (0, 1, BinoxCell::O));
+        let b = Binox::new_from_string("x..x............".into());
+        let d = b.find_deduction(Technique::Count).unwrap();
+        assert_eq!((d.row, d.col, d.cell), (0, 1, BinoxCell::O));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn examples() {
+        use rand::rngs::StdRng;
+        use rand::SeedableRng;
+
+        assert_eq!("Line-Analysis".parse(), Ok(Technique::LineAnalysis));
+        assert!("guess".parse::<Technique>().is_err());
+        for technique in Technique::ALL {
+            let (board, deduction) = technique.example(&mut StdRng::seed_from_u64(1)).unwrap();
+            assert_eq!(board.next_deduction(), Some(deduction.clone()));
+            assert_eq!(deduction.technique, technique);
+        }
+    }
+
+    #[test]
+    fn candidates() {
+        let b = Binox::new_from_string("XX..X...........".into());
+        let candidates = b.compute_candidates();
+        assert_eq!(candidates.len(), 13);
+        assert_eq!(candidates[0], (0, 2, vec![BinoxCell::O]));
+        assert_eq!(candidates[2], (1, 1, vec![BinoxCell::X, BinoxCell::O]));
+        assert_eq!(candidates[5], (2, 0, vec![BinoxCell::O]));
+    }
+
+    #[test]
+    fn forced_cells_single_pass() {
+        let b = Binox::new_from_string("xx......x.......".into());
+        let mut forced: Vec<_> = b
+            .forced_cells()
+            .iter()
+            .map(|d| (d.row, d.col, d.cell))
+            .collect();
+        forced.sort_by_key(|&(row, col, _)| (row, col));
+        assert_eq!(
+            forced,
+            vec![
+                (0, 2, BinoxCell::O),
+                (0, 3, BinoxCell::O),
+                (1, 0, BinoxCell::O),
+                (3, 0, BinoxCell::O)
+            ]
+        );
+        assert!(Binox::new_from_string("xxx.............".into())
+            .forced_cells()
+            .is_empty());
+    }
+
+    #[test]
+    fn rating_solves_sample() {
+        let b = Binox::new_from_string("X......O...X..XX....X..O....X..XX...".into());
+        let rating = b.rate();
+        assert!(rating.solved_logically);
+        assert_eq!(rating.clues, 10);
+        assert_eq!(
+            rating.techniques.iter().map(|(_, n)| n).sum::<usize>(),
+            36 - 10
+        );
+    }
+}
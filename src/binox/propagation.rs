@@ -0,0 +1,398 @@
+use alloc::collections::VecDeque;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::binox::logic::{full_mask, opposite, Deduction, Technique};
+use crate::binox::{Binox, BinoxCell};
+use crate::puzzle::Puzzle;
+
+/// A row, or a column if `is_row` is false.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Line {
+    pub is_row: bool,
+    pub index: u8,
+}
+
+impl Line {
+    fn cell(&self, position: u8) -> (u8, u8) {
+        Binox::line_cell(self.is_row, self.index, position)
+    }
+
+    /// The cells of the line whose bits are set in `mask`.
+    fn cells(&self, size: u8, mask: u16) -> Vec<(u8, u8)> {
+        (0..size)
+            .filter(|&p| mask & (1 << p) != 0)
+            .map(|p| self.cell(p))
+            .collect()
+    }
+
+    fn deduction(
+        &self,
+        position: u8,
+        cell: BinoxCell,
+        technique: Technique,
+        reasons: Vec<(u8, u8)>,
+    ) -> Deduction {
+        let (row, col) = self.cell(position);
+        Deduction {
+            row,
+            col,
+            cell,
+            technique,
+            reasons,
+        }
+    }
+}
+
+/// Found when a line can no longer be completed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Contradiction;
+
+/// What a propagator deduces: a cell and the symbol it takes.
+pub trait Fill<P: Puzzle> {
+    fn fill(&self) -> ((u8, u8), P::Cell);
+}
+
+impl Fill<Binox> for Deduction {
+    fn fill(&self) -> ((u8, u8), BinoxCell) {
+        ((self.row, self.col), self.cell)
+    }
+}
+
+impl<P: Puzzle> Fill<P> for ((u8, u8), P::Cell) {
+    fn fill(&self) -> ((u8, u8), P::Cell) {
+        *self
+    }
+}
+
+/// A rule that deduces cells of one line at a time. The binox techniques
+/// explain their deductions; rules for other puzzles only give the cells.
+pub trait Propagator<P: Puzzle = Binox, D: Fill<P> = Deduction>: Sync {
+    /// The cells of the line the rule forces on the board as it is, or a
+    /// contradiction if the line already breaks the rule. A cell may be
+    /// deduced more than once, by different techniques.
+    fn propagate(&self, board: &P, line: Line) -> Result<Vec<D>, Contradiction>;
+
+    /// Whether finishing a line can give the rule something new to deduce in
+    /// the lines parallel to it, and not only in the lines crossing it.
+    fn watches_parallel_lines(&self) -> bool {
+        false
+    }
+}
+
+/// No three identical symbols in a row: a cell beside two identical symbols
+/// (a pair) or between them (a gap) takes the other symbol.
+pub struct NoTriple;
+
+impl Propagator for NoTriple {
+    fn propagate(&self, board: &Binox, line: Line) -> Result<Vec<Deduction>, Contradiction> {
+        let (x, o) = board.line(line.is_row, line.index);
+        if [x, o].iter().any(|&mask| mask & mask << 1 & mask >> 1 != 0) {
+            return Err(Contradiction);
+        }
+        let size = board.size();
+        let empty = full_mask(size) & !(x | o);
+        let mut result = Vec::new();
+        for position in (0..size).filter(|&p| empty & (1 << p) != 0) {
+            let p = position as i16;
+            for (mask, symbol) in [(x, BinoxCell::X), (o, BinoxCell::O)] {
+                let has = |p: i16| p >= 0 && p < size as i16 && mask & (1 << p) != 0;
+                let cell = opposite(&symbol);
+                if has(p - 1) && has(p - 2) {
+                    let reasons = line.cells(size, (1 << (p - 1)) | (1 << (p - 2)));
+                    result.push(line.deduction(position, cell, Technique::Pair, reasons));
+                } else if has(p + 1) && has(p + 2) {
+                    let reasons = line.cells(size, (1 << (p + 1)) | (1 << (p + 2)));
+                    result.push(line.deduction(position, cell, Technique::Pair, reasons));
+                }
+                if has(p - 1) && has(p + 1) {
+                    let reasons = line.cells(size, (1 << (p - 1)) | (1 << (p + 1)));
+                    result.push(line.deduction(position, cell, Technique::Gap, reasons));
+                }
+            }
+        }
+        Ok(result)
+    }
+}
+
+/// Half of every line is each symbol: once a line has all of one symbol, its
+/// other cells take the other.
+pub struct Quota;
+
+impl Propagator for Quota {
+    fn propagate(&self, board: &Binox, line: Line) -> Result<Vec<Deduction>, Contradiction> {
+        let (x, o) = board.line(line.is_row, line.index);
+        let size = board.size();
+        let half = size as u32 / 2;
+        if x.count_ones() > half || o.count_ones() > half {
+            return Err(Contradiction);
+        }
+        let empty = full_mask(size) & !(x | o);
+        let full = [(x, BinoxCell::X), (o, BinoxCell::O)]
+            .into_iter()
+            .find(|&(mask, _)| mask.count_ones() == half);
+        let Some((mask, symbol)) = full else {
+            return Ok(Vec::new());
+        };
+        Ok((0..size)
+            .filter(|&p| empty & (1 << p) != 0)
+            .map(|p| {
+                line.deduction(
+                    p,
+                    opposite(&symbol),
+                    Technique::Count,
+                    line.cells(size, mask),
+                )
+            })
+            .collect())
+    }
+}
+
+/// Every valid way to complete the line agrees on a cell. With `unique`, ways
+/// that would repeat a finished parallel line are left out.
+pub struct Completions {
+    pub unique: bool,
+}
+
+impl Propagator for Completions {
+    fn propagate(&self, board: &Binox, line: Line) -> Result<Vec<Deduction>, Contradiction> {
+        let completions = board.line_completions(line.is_row, line.index, self.unique);
+        if completions.is_empty() {
+            return Err(Contradiction);
+        }
+        let (x, o) = board.line(line.is_row, line.index);
+        let size = board.size();
+        let empty = full_mask(size) & !(x | o);
+        if empty == 0 {
+            return Ok(Vec::new());
+        }
+        let all_x = completions.iter().fold(full_mask(size), |a, &l| a & l);
+        let any_x = completions.iter().fold(0, |a, &l| a | l);
+        let mut reasons = line.cells(size, x | o);
+        if self.unique {
+            for i in (0..size).filter(|&i| i != line.index) {
+                let (lx, lo) = board.line(line.is_row, i);
+                if lx | lo == full_mask(size) && lx & x == x && lx & o == 0 {
+                    let other = Line {
+                        is_row: line.is_row,
+                        index: i,
+                    };
+                    reasons.extend((0..size).map(|p| other.cell(p)));
+                }
+            }
+        }
+        let technique = if self.unique {
+            Technique::Uniqueness
+        } else {
+            Technique::LineAnalysis
+        };
+        Ok((0..size)
+            .filter(|&p| empty & (1 << p) != 0)
+            .filter_map(|p| {
+                let cell = if all_x & (1 << p) != 0 {
+                    BinoxCell::X
+                } else if any_x & (1 << p) == 0 {
+                    BinoxCell::O
+                } else {
+                    return None;
+                };
+                Some(line.deduction(p, cell, technique, reasons.clone()))
+            })
+            .collect())
+    }
+
+    fn watches_parallel_lines(&self) -> bool {
+        self.unique
+    }
+}
+
+/// Fills each empty cell of the line where only one symbol passes `valid`,
+/// which is how [`Puzzle::presolve_with`] works on any puzzle.
+pub struct Trial<P: Puzzle> {
+    pub valid: fn(&P) -> bool,
+}
+
+impl<P: Puzzle> Propagator<P, ((u8, u8), P::Cell)> for Trial<P> {
+    fn propagate(&self, board: &P, line: Line) -> Result<Vec<((u8, u8), P::Cell)>, Contradiction> {
+        let mut board = board.clone();
+        let mut result = Vec::new();
+        for position in 0..board.size() {
+            let (row, col) = line.cell(position);
+            if board.cell(row, col) != P::EMPTY {
+                continue;
+            }
+            let [first, second] = P::SYMBOLS;
+            board.put(row, col, first);
+            let first_valid = (self.valid)(&board);
+            board.put(row, col, second);
+            let second_valid = (self.valid)(&board);
+            board.put(row, col, P::EMPTY);
+            match (first_valid, second_valid) {
+                (true, false) => result.push(((row, col), first)),
+                (false, true) => result.push(((row, col), second)),
+                (false, false) => return Err(Contradiction),
+                (true, true) => (),
+            }
+        }
+        Ok(result)
+    }
+
+    /// The rules spanning several lines, such as no two lines being the
+    /// same, can compare a line with a finished one parallel to it.
+    fn watches_parallel_lines(&self) -> bool {
+        true
+    }
+}
+
+/// Every line technique, for searching and for trying out a guess.
+pub const LOGIC: [&dyn Propagator; 3] = [&NoTriple, &Quota, &Completions { unique: true }];
+
+/// The propagator that makes deductions with a technique. Trial is not a
+/// line technique and has none.
+pub fn propagator(technique: Technique) -> Option<&'static dyn Propagator> {
+    match technique {
+        Technique::Pair | Technique::Gap => Some(&NoTriple),
+        Technique::Count => Some(&Quota),
+        Technique::LineAnalysis => Some(&Completions { unique: false }),
+        Technique::Uniqueness => Some(&Completions { unique: true }),
+        Technique::Trial => None,
+    }
+}
+
+/// What running the propagators to a fixpoint found.
+pub struct Propagation<D = Deduction> {
+    /// The cells filled, in the order they were deduced.
+    pub deductions: Vec<D>,
+    /// Whether the propagation stopped because the board cannot be completed.
+    pub contradiction: bool,
+}
+
+/// The lines waiting to be looked at, each at most once.
+struct Worklist {
+    queue: VecDeque<Line>,
+    /// Whether each column, then each row, is in the queue.
+    queued: [Vec<bool>; 2],
+}
+
+impl Worklist {
+    fn new(size: u8) -> Self {
+        Worklist {
+            queue: VecDeque::new(),
+            queued: [vec![false; size as usize], vec![false; size as usize]],
+        }
+    }
+
+    fn push(&mut self, line: Line) {
+        let waiting = &mut self.queued[line.is_row as usize][line.index as usize];
+        if !*waiting {
+            *waiting = true;
+            self.queue.push_back(line);
+        }
+    }
+
+    fn pop(&mut self) -> Option<Line> {
+        let line = self.queue.pop_front()?;
+        self.queued[line.is_row as usize][line.index as usize] = false;
+        Some(line)
+    }
+}
+
+/// Fills the cells the propagators deduce until none of them finds anything
+/// more. Every line is looked at once, and afterwards only the lines that
+/// changed, or that a finished line can affect, are looked at again.
+pub fn propagate<P: Puzzle, D: Fill<P>>(
+    board: &mut P,
+    propagators: &[&dyn Propagator<P, D>],
+) -> Propagation<D> {
+    let size = board.size();
+    let parallel = propagators.iter().any(|p| p.watches_parallel_lines());
+    let mut worklist = Worklist::new(size);
+    for index in 0..size {
+        worklist.push(Line {
+            is_row: true,
+            index,
+        });
+        worklist.push(Line {
+            is_row: false,
+            index,
+        });
+    }
+    let mut deductions = Vec::new();
+    while let Some(line) = worklist.pop() {
+        for propagator in propagators {
+            let Ok(found) = propagator.propagate(board, line) else {
+                return Propagation {
+                    deductions,
+                    contradiction: true,
+                };
+            };
+            for deduction in found {
+                let ((row, col), symbol) = deduction.fill();
+                match board.cell(row, col) {
+                    cell if cell == P::EMPTY => (),
+                    cell if cell == symbol => continue,
+                    _ => {
+                        return Propagation {
+                            deductions,
+                            contradiction: true,
+                        }
+                    }
+                }
+                board.put(row, col, symbol);
+                for changed in [
+                    Line {
+                        is_row: true,
+                        index: row,
+                    },
+                    Line {
+                        is_row: false,
+                        index: col,
+                    },
+                ] {
+                    worklist.push(changed);
+                    let full = (0..size).all(|p| {
+                        let (row, col) = changed.cell(p);
+                        board.cell(row, col) != P::EMPTY
+                    });
+                    if parallel && full {
+                        for index in 0..size {
+                            worklist.push(Line {
+                                is_row: changed.is_row,
+                                index,
+                            });
+                        }
+                    }
+                }
+                deductions.push(deduction);
+            }
+        }
+    }
+    Propagation {
+        deductions,
+        contradiction: false,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn fixpoint() {
+        let mut b = Binox::new_from_string("X......O...X..XX....X..O....X..XX...".into());
+        let propagation = propagate(&mut b, &LOGIC);
+        assert!(!propagation.contradiction);
+        assert_eq!(propagation.deductions.len(), 36 - 10);
+        assert!(b.is_solved());
+
+        let mut b = Binox::new_from_string("xx..............".into());
+        let propagation = propagate(&mut b, &[&NoTriple]);
+        assert_eq!(propagation.deductions.len(), 1);
+        assert_eq!(b.get_cell(0, 2), Ok(BinoxCell::O));
+
+        let mut b = Binox::new_from_string(format!("xx.oo.{}", ".".repeat(30)));
+        assert!(propagate(&mut b, &[&NoTriple]).contradiction);
+        let mut b = Binox::new_from_string("xoxoxoxo........".into());
+        assert!(propagate(&mut b, &LOGIC).contradiction);
+    }
+}
@@ -1,14 +1,117 @@
-#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
-pub struct BinRow {
-    pub data: u16,
+use alloc::vec::Vec;
+use core::fmt::Debug;
+use core::hash::Hash;
+use core::ops::{BitAnd, BitOr, BitXor, Not, Shl, Shr};
+
+/// An unsigned integer that stores the cells of a line, one bit per cell.
+/// Its width is the longest line it can hold.
+pub trait RowBits:
+    Copy
+    + Debug
+    + Eq
+    + Ord
+    + Hash
+    + BitAnd<Output = Self>
+    + BitOr<Output = Self>
+    + BitXor<Output = Self>
+    + Not<Output = Self>
+    + Shl<u8, Output = Self>
+    + Shr<u8, Output = Self>
+{
+    const BITS: u8;
+    const ZERO: Self;
+    const ONE: Self;
+    const MAX: Self;
+    /// The error for a line longer than the integer has bits.
+    const TOO_LONG: &'static str;
+}
+
+macro_rules! row_bits {
+    ($($bits:ty => $too_long:literal),*) => {
+        $(
+            impl RowBits for $bits {
+                const BITS: u8 = <$bits>::BITS as u8;
+                const ZERO: Self = 0;
+                const ONE: Self = 1;
+                const MAX: Self = <$bits>::MAX;
+                const TOO_LONG: &'static str = $too_long;
+            }
+        )*
+    };
+}
+
+row_bits!(
+    u16 => "size must be at most 16",
+    u32 => "size must be at most 32",
+    u64 => "size must be at most 64",
+    u128 => "size must be at most 128"
+);
+
+/// One row or column as a bit set of the cells holding one symbol. The
+/// backing integer sets the longest line: `u16` for the boards of 4 to 16
+/// cells a side, or a wider one for bigger boards. Rows of different widths
+/// are different types, so they cannot be mixed up.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct BinRow<T: RowBits = u16> {
+    pub data: T,
     size: u8,
     pub count: u8,
 }
 
 impl BinRow {
+    /// A row of up to 16 cells, as on every board up to 16x16.
     pub fn new(size: u8) -> Result<Self, &'static str> {
-        if size > 16 {
-            return Err("size must be at most 16");
+        BinRow::with_size(size)
+    }
+
+    /// Whether every line passes [`BinRow::is_valid`].
+    pub fn all_valid(lines: &[BinRow]) -> bool {
+        Self::check_all(lines, false)
+    }
+
+    /// Whether every line passes [`BinRow::is_valid_simple`].
+    pub fn all_valid_simple(lines: &[BinRow]) -> bool {
+        Self::check_all(lines, true)
+    }
+
+    /// On 64-bit targets, checks four lines at a time, side by side in the
+    /// 16-bit lanes of one `u64`, so that a few shifts find the triples of
+    /// all four at once. Elsewhere, checks one line at a time.
+    fn check_all(lines: &[BinRow], simple: bool) -> bool {
+        if cfg!(not(target_pointer_width = "64")) {
+            return if simple {
+                lines.iter().all(BinRow::is_valid_simple)
+            } else {
+                lines.iter().all(BinRow::is_valid)
+            };
+        }
+        lines.chunks(4).all(|chunk| {
+            let (mut data, mut starts, mut others) = (0u64, 0u64, 0u64);
+            for (lane, line) in chunk.iter().enumerate() {
+                if line.count > line.size / 2 {
+                    return false;
+                }
+                let shift = 16 * lane as u32;
+                let full = line.full() as u64;
+                data |= (line.data as u64) << shift;
+                // A triple can only start where two more cells of the line
+                // follow, which also keeps lanes from running into each other.
+                starts |= (full >> 2) << shift;
+                if !simple && line.count == line.size / 2 {
+                    others |= ((line.data as u64) ^ full) << shift;
+                }
+            }
+            let triples = |bits: u64| bits & bits >> 1 & bits >> 2 & starts;
+            triples(data) == 0 && triples(others) == 0
+        })
+    }
+}
+
+impl<T: RowBits> BinRow<T> {
+    /// A row of any length that the backing integer has bits for.
+    pub fn with_size(size: u8) -> Result<Self, &'static str> {
+        if size > T::BITS {
+            return Err(T::TOO_LONG);
         }
         if size < 4 {
             return Err("size must be at least 4");
@@ -18,7 +121,7 @@ impl BinRow {
         }
         Ok(BinRow {
             size,
-            data: 0,
+            data: T::ZERO,
             count: 0,
         })
     }
@@ -27,10 +130,10 @@ impl BinRow {
         if position >= self.size {
             return Err("attempted to set one out of range");
         }
-        if self.data & (1 << position) == 0 {
+        if self.data & (T::ONE << position) == T::ZERO {
             self.count += 1
         }
-        self.data |= 1 << position;
+        self.data = self.data | T::ONE << position;
         Ok(())
     }
 
@@ -38,10 +141,10 @@ impl BinRow {
         if position >= self.size {
             return Err("attempted to set zero out of range");
         }
-        if self.data & (1 << position) != 0 {
+        if self.data & (T::ONE << position) != T::ZERO {
             self.count -= 1
         }
-        self.data &= !(1 << position);
+        self.data = self.data & !(T::ONE << position);
         Ok(())
     }
 
@@ -57,21 +160,98 @@ impl BinRow {
         if position >= self.size {
             return Err("attempted to get out of range");
         }
-        Ok((self.data & 1 << position) > 0)
+        Ok(self.data & T::ONE << position != T::ZERO)
+    }
+
+    /// The bits of every cell of the row.
+    fn full(&self) -> T {
+        T::MAX >> (T::BITS - self.size)
+    }
+
+    /// The positions of the set cells, from 0 up.
+    pub fn iter_ones(&self) -> impl Iterator<Item = u8> + '_ {
+        (0..self.size).filter(|&position| self.data & T::ONE << position != T::ZERO)
+    }
+
+    /// The length of the longest stretch of set cells next to each other.
+    pub fn longest_run(&self) -> u8 {
+        // Each step keeps the cells that begin a stretch one longer.
+        let mut bits = self.data;
+        let mut run = 0;
+        while bits != T::ZERO {
+            bits = bits & bits >> 1;
+            run += 1;
+        }
+        run
+    }
+
+    /// The row with every cell flipped, within its size.
+    pub fn complement(&self) -> Self {
+        BinRow {
+            data: !self.data & self.full(),
+            size: self.size,
+            count: self.size - self.count,
+        }
+    }
+
+    /// The cells set in neither this row nor its pair, such as the empty
+    /// cells of a line given the rows of its X's and its O's.
+    pub fn free_mask(&self, pair: &Self) -> T {
+        !(self.data | pair.data) & self.full()
+    }
+
+    /// Every full line that keeps to the rules and agrees with a partly
+    /// filled one, given as the row of its X's and the row of its O's. Each
+    /// line is yielded as the bits of its X's, from the smallest up.
+    pub fn valid_completions(x_row: &Self, o_row: &Self) -> impl Iterator<Item = T> {
+        let mut lines = Vec::new();
+        if x_row.data & o_row.data == T::ZERO {
+            x_row.complete(o_row, 0, T::ZERO, [0, 0], &mut lines);
+        }
+        lines.sort();
+        lines.into_iter()
+    }
+
+    /// Fills the line from `position` on, given the bits of the X's before
+    /// it and how many O's and X's those are, and collects each full line.
+    fn complete(&self, o_row: &Self, position: u8, bits: T, counts: [u8; 2], lines: &mut Vec<T>) {
+        if position == self.size {
+            lines.push(bits);
+            return;
+        }
+        let mask = T::ONE << position;
+        for (symbol, set) in [(1, true), (0, false)] {
+            let taken = if set { o_row.data } else { self.data };
+            if taken & mask != T::ZERO || counts[symbol] == self.size / 2 {
+                continue;
+            }
+            let same = |back: u8| {
+                position >= back && (bits & T::ONE << (position - back) != T::ZERO) == set
+            };
+            if same(1) && same(2) {
+                continue;
+            }
+            let mut counts = counts;
+            counts[symbol] += 1;
+            let bits = if set { bits | mask } else { bits };
+            self.complete(o_row, position + 1, bits, counts, lines);
+        }
+    }
+
+    /// True if three cells in a row are set.
+    fn has_triple(bits: T) -> bool {
+        bits & bits << 1 & bits >> 1 != T::ZERO
     }
 
     pub fn is_valid_simple(&self) -> bool {
-        self.data & self.data << 1 & self.data >> 1 == 0 && self.count <= self.size / 2
+        !Self::has_triple(self.data) && self.count <= self.size / 2
     }
 
     pub fn is_valid(&self) -> bool {
-        self.data & self.data << 1 & self.data >> 1 == 0
+        let others = self.data ^ self.full();
+        !Self::has_triple(self.data)
             && self.count <= self.size / 2
-            && !(self.count == self.size / 2
-                && (self.data ^ ((1 << self.size) - 1))
-                    & ((self.data ^ ((1 << self.size) - 1)) << 1)
-                    & ((self.data ^ ((1 << self.size) - 1)) >> 1)
-                    != 0)
+            && !(self.count == self.size / 2 && Self::has_triple(others))
     }
 }
 
@@ -106,5 +286,119 @@ mod test {
         assert!(row.is_valid());
         row.set_one(7).unwrap();
         assert!(!row.is_valid());
+
+        let mut row = BinRow::new(16).unwrap();
+        for position in [0, 3, 4, 7, 8, 11, 12, 15] {
+            row.set_one(position).unwrap();
+        }
+        assert!(row.is_valid());
+        row.set_zero(15).unwrap();
+        row.set_one(14).unwrap();
+        assert!(row.is_valid());
+        row.set_zero(0).unwrap();
+        row.set_one(1).unwrap();
+        assert!(row.is_valid());
+        let mut row = BinRow::new(16).unwrap();
+        for position in [0, 1, 3, 4, 6, 7, 9, 10] {
+            row.set_one(position).unwrap();
+        }
+        assert!(!row.is_valid());
+    }
+
+    #[test]
+    fn bit_utilities() {
+        let mut x = BinRow::new(8).unwrap();
+        for position in [0, 2, 3, 6] {
+            x.set_one(position).unwrap();
+        }
+        assert_eq!(x.iter_ones().collect::<Vec<_>>(), [0, 2, 3, 6]);
+        assert_eq!(x.longest_run(), 2);
+        assert_eq!(BinRow::new(8).unwrap().longest_run(), 0);
+        let o = x.complement();
+        assert_eq!(o.data, 0b10110010);
+        assert_eq!(o.count, 4);
+        assert_eq!(o.complement(), x);
+        assert_eq!(x.free_mask(&o), 0);
+        let mut o = BinRow::new(8).unwrap();
+        o.set_one(1).unwrap();
+        assert_eq!(x.free_mask(&o), 0b10110000);
+    }
+
+    #[test]
+    fn completions() {
+        let blank = BinRow::new(4).unwrap();
+        assert_eq!(BinRow::valid_completions(&blank, &blank).count(), 6);
+        let blank = BinRow::new(6).unwrap();
+        assert_eq!(BinRow::valid_completions(&blank, &blank).count(), 14);
+        let mut x = BinRow::new(4).unwrap();
+        let mut o = BinRow::new(4).unwrap();
+        x.set_one(0).unwrap();
+        o.set_one(1).unwrap();
+        let lines: Vec<u16> = BinRow::valid_completions(&x, &o).collect();
+        assert_eq!(lines, [0b0101, 0b1001]);
+        o.set_one(0).unwrap();
+        assert_eq!(BinRow::valid_completions(&x, &o).count(), 0);
+        let x = BinRow::<u32>::with_size(20).unwrap();
+        assert!(BinRow::valid_completions(&x, &x).all(|line| {
+            let mut row = BinRow::<u32>::with_size(20).unwrap();
+            row.data = line;
+            row.count = line.count_ones() as u8;
+            row.is_valid()
+        }));
+    }
+
+    #[test]
+    fn batch_validation() {
+        let rows: Vec<BinRow> = [0b0110, 0b1001, 0b011001, 0b0011, 0b10110010]
+            .into_iter()
+            .zip([4, 4, 6, 4, 8])
+            .map(|(bits, size)| {
+                let mut row = BinRow::new(size).unwrap();
+                for position in 0..size {
+                    row.set_bool(position, bits >> position & 1 == 1).unwrap();
+                }
+                row
+            })
+            .collect();
+        assert!(BinRow::all_valid(&rows));
+        let mut full = BinRow::new(16).unwrap();
+        for position in [0, 3, 4, 7, 8, 11, 12, 15] {
+            full.set_one(position).unwrap();
+        }
+        assert!(BinRow::all_valid(&[rows[0], full]));
+        for (position, valid) in [(13, false), (14, true)] {
+            let mut broken = full;
+            broken.set_zero(15).unwrap();
+            broken.set_one(position).unwrap();
+            assert_eq!(BinRow::all_valid(&[full, full, full, full, broken]), valid);
+            assert_eq!(broken.is_valid(), valid);
+        }
+        let mut sparse = BinRow::new(8).unwrap();
+        for position in [0, 1, 3, 4] {
+            sparse.set_one(position).unwrap();
+        }
+        assert!(!BinRow::all_valid(&[sparse]));
+        assert!(BinRow::all_valid_simple(&[sparse]));
+    }
+
+    #[test]
+    fn wider_storage() {
+        assert!(BinRow::<u16>::with_size(18).is_err());
+        assert_eq!(BinRow::<u32>::with_size(34), Err("size must be at most 32"));
+        let mut row = BinRow::<u32>::with_size(32).unwrap();
+        for position in (0..32).filter(|p| p % 4 == 0 || p % 4 == 3) {
+            row.set_one(position).unwrap();
+        }
+        assert_eq!(row.count, 16);
+        assert!(row.is_valid());
+        assert!(row.get(31).unwrap());
+        row.set_zero(31).unwrap();
+        row.set_one(30).unwrap();
+        assert!(row.is_valid());
+        let mut row = BinRow::<u64>::with_size(40).unwrap();
+        for position in 37..40 {
+            row.set_one(position).unwrap();
+        }
+        assert!(!row.is_valid_simple());
     }
 }
@@ -0,0 +1,119 @@
+use alloc::format;
+use alloc::string::String;
+use core::fmt;
+
+use crate::binox::{column_name, Binox, BinoxCell};
+use crate::theme::current_theme;
+
+/// The rules a single move can break.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Rule {
+    /// More than half of the line would hold the symbol.
+    Count,
+    /// Three identical symbols would be next to each other.
+    Triple,
+    /// The finished line would be the same as the line with this index.
+    Duplicate(u8),
+}
+
+/// A rule broken by placing `cell` in a row or column.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Violation {
+    pub rule: Rule,
+    pub is_row: bool,
+    pub index: u8,
+    pub cell: BinoxCell,
+}
+
+fn line_name(is_row: bool, index: u8) -> String {
+    if is_row {
+        format!("row {index}")
+    } else {
+        format!("column {}", column_name(index))
+    }
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let line = line_name(self.is_row, self.index);
+        let symbol = current_theme().symbol(self.cell);
+        match self.rule {
+            Rule::Count => write!(f, "{line} would have more {symbol}'s than allowed"),
+            Rule::Triple => write!(f, "{line} would have three {symbol}'s next to each other"),
+            Rule::Duplicate(other) => write!(
+                f,
+                "{line} would be the same as {}",
+                line_name(self.is_row, other)
+            ),
+        }
+    }
+}
+
+impl Binox {
+    /// Checks whether placing `cell` breaks a rule in its row or column right
+    /// away, without changing the board.
+    pub fn check_move(&self, row: u8, col: u8, cell: BinoxCell) -> Result<(), Violation> {
+        if cell == BinoxCell::EMPTY {
+            return Ok(());
+        }
+        let mut after = self.clone();
+        if after.set_cell(row, col, cell).is_err() {
+            return Ok(());
+        }
+        for (is_row, index) in [(true, row), (false, col)] {
+            let (xs, os) = if is_row {
+                (&after.x_rows, &after.o_rows)
+            } else {
+                (&after.x_cols, &after.o_cols)
+            };
+            let (x, o) = (xs[index as usize], os[index as usize]);
+            let line = if cell == BinoxCell::X { x } else { o };
+            let violation = |rule| Violation {
+                rule,
+                is_row,
+                index,
+                cell,
+            };
+            if line.longest_run() >= 3 {
+                return Err(violation(Rule::Triple));
+            }
+            if line.count > self.size / 2 {
+                return Err(violation(Rule::Count));
+            }
+            if x.free_mask(&o) == 0 {
+                let duplicate = (0..self.size).find(|&other| {
+                    other != index
+                        && xs[other as usize].data == x.data
+                        && os[other as usize].data == o.data
+                });
+                if let Some(other) = duplicate {
+                    return Err(violation(Rule::Duplicate(other)));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn move_violations() {
+        let b = Binox::new_from_string("XX..X...........".into());
+        assert_eq!(
+            b.check_move(0, 2, BinoxCell::X).unwrap_err().rule,
+            Rule::Triple
+        );
+        assert!(b.check_move(0, 2, BinoxCell::O).is_ok());
+        let b = Binox::new_from_string("X.X.............".into());
+        let violation = b.check_move(0, 3, BinoxCell::X).unwrap_err();
+        assert_eq!((violation.rule, violation.is_row), (Rule::Count, true));
+        let b = Binox::new_from_string("XOXOXO.O........".into());
+        let violation = b.check_move(1, 2, BinoxCell::X).unwrap_err();
+        assert_eq!(violation.rule, Rule::Duplicate(0));
+        assert_eq!(violation.to_string(), "row 1 would be the same as row 0");
+        assert!(b.check_move(2, 2, BinoxCell::O).is_ok());
+    }
+}
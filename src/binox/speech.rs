@@ -0,0 +1,95 @@
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use crate::binox::{column_name, Binox, BinoxCell};
+use crate::theme::current_theme;
+
+/// Spoken-style descriptions of the board for screen readers.
+impl Binox {
+    fn describe(&self, row: u8, col: u8) -> String {
+        let theme = current_theme();
+        match self.get_cell(row, col).unwrap() {
+            BinoxCell::EMPTY => "blank".into(),
+            cell if self.is_default(row, col).unwrap() => format!("given {}", theme.symbol(cell)),
+            cell => theme.symbol(cell).to_string(),
+        }
+    }
+
+    fn check_index(&self, index: u8, name: &str) -> Result<(), String> {
+        if index >= self.size() {
+            return Err(format!("{name} must be less than {}", self.size()));
+        }
+        Ok(())
+    }
+
+    /// Describes a row from left to right, e.g. `row 3: X, blank, given O, O`.
+    pub fn describe_row(&self, row: u8) -> Result<String, String> {
+        self.check_index(row, "row")?;
+        let cells: Vec<String> = (0..self.size())
+            .map(|col| self.describe(row, col))
+            .collect();
+        Ok(format!("row {row}: {}", cells.join(", ")))
+    }
+
+    /// Describes a column from top to bottom.
+    pub fn describe_col(&self, col: u8) -> Result<String, String> {
+        self.check_index(col, "column")?;
+        let cells: Vec<String> = (0..self.size())
+            .map(|row| self.describe(row, col))
+            .collect();
+        Ok(format!("column {}: {}", column_name(col), cells.join(", ")))
+    }
+
+    pub fn describe_cell(&self, row: u8, col: u8) -> Result<String, String> {
+        self.check_index(row, "row")?;
+        self.check_index(col, "column")?;
+        Ok(format!(
+            "cell {}{row}: {}",
+            column_name(col),
+            self.describe(row, col)
+        ))
+    }
+
+    /// A short summary read in place of the board drawing.
+    pub fn describe_board(&self) -> String {
+        let size = self.size() as usize;
+        let filled = self.as_string().chars().filter(|&c| c != '.').count();
+        let state = if self.is_solved() {
+            "solved"
+        } else if !self.is_valid() {
+            "has a mistake"
+        } else {
+            "in progress"
+        };
+        format!(
+            "{size} by {size} board, {filled} of {} cells filled, {state}",
+            size * size
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn spoken_descriptions() {
+        let mut b = Binox::new_from_string("X..O............".into());
+        b.set_cell(0, 1, BinoxCell::O).unwrap();
+        assert_eq!(
+            b.describe_row(0).unwrap(),
+            "row 0: given X, O, blank, given O"
+        );
+        assert_eq!(
+            b.describe_col(1).unwrap(),
+            "column b: O, blank, blank, blank"
+        );
+        assert_eq!(b.describe_cell(0, 3).unwrap(), "cell d0: given O");
+        assert!(b.describe_row(4).is_err());
+        assert_eq!(
+            b.describe_board(),
+            "4 by 4 board, 3 of 16 cells filled, in progress"
+        );
+    }
+}
@@ -1,31 +1,108 @@
-use std::fs;
-use std::io;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, IsTerminal};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Arc, LazyLock};
+use std::thread;
+use std::time::Duration;
 
 use colored::Colorize;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use signal_hook::consts::SIGINT;
 
+use crate::autosave::{self, Autosave};
+use crate::binox::logic::{Deduction, Technique};
+use crate::binox::rules::Violation;
 use crate::binox::Binox;
-use crate::binox::BinoxCell;
 use crate::binox::BinoxSolution;
+use crate::binox::{column_name, seed_from_str, BinoxCell, BinoxError, Symmetry};
+use crate::campaign::{Campaign, Level};
+#[cfg(feature = "clipboard")]
+use crate::clipboard;
+use crate::completion::{canonical, Completed};
+use crate::config::Config;
+use crate::difficulty::Difficulty;
+use crate::history::History;
+use crate::locale::{self, fill, tr, tr_with};
+use crate::marks::Marks;
+use crate::output::{Output, OutputMode, Rendering};
+use crate::puzzle::{PresolveResult, Puzzle};
+use crate::puzzle_pack;
+use crate::puzzle_set::{GenerateOptions, MetaField, PuzzleEntry, PuzzleSet, SET_EXTENSION};
+use crate::stats::{Solve, Stats};
+use crate::theme::{current_theme, set_theme, BoardStyle, Theme};
+use crate::timer::{self, format_duration, reorder, shift_down, Timers};
+
+/// How many solutions `count` looks for when no limit is given.
+const COUNT_LIMIT: usize = 1000;
+
+/// Set by Ctrl-C while a command runs, which stops a long solve, count or
+/// generation. It stays set while the interpreter waits for input, so that
+/// Ctrl-C at the prompt quits as usual.
+static INTERRUPTED: LazyLock<Arc<AtomicBool>> = LazyLock::new(Arc::default);
+
+/// Seeds the puzzles `explain` looks through for an example, so that each
+/// technique always has the same one.
+const EXAMPLE_SEED: u64 = 1;
+
+const INTERRUPTED_TEXT: &str = "interrupted; the board was not changed";
 
 pub enum BIR {
     Normal(bool),
+    /// A generated or blank puzzle, which replaces the current one along with
+    /// its seed and options, and starts its clock again.
+    Generated(Box<PuzzleEntry>),
     Error(String),
     Exit,
     Next,
     Previous,
+    Goto(usize),
+    Add,
+    Remove(Option<usize>),
+    Save(Option<String>),
+    Edit(bool),
+    Quiet(bool),
+    Show,
+    Tallies(bool),
+    List,
+    Config,
+    Display(Rendering),
+    Mark(BinoxCell, Vec<(u8, u8)>),
+    Unmark(BinoxCell, Vec<(u8, u8)>),
+    ShowMarks(Option<bool>),
+    Auto(bool),
+    Assist(bool),
+    Mistakes,
+    CheckCell(u8, u8),
+    Peek(Option<(bool, u8)>),
+    Time,
+    Info,
+    Title(Option<Option<String>>),
+    Meta(Option<(MetaField, Option<String>)>),
+    Stars(u8),
+    /// Shuffles the collection, with the given seed or a random one.
+    Shuffle(Option<u64>),
+    Stats,
+    Campaign,
+    PlayLevel(Option<Level>),
+    Undo(usize),
+    Redo(usize),
     Import(String),
+    ImportPack(String, String),
+    Paste(Vec<String>),
 }
 
-pub fn interpret(mut binox: Binox, line: String) -> (Binox, BIR) {
-    let words: Vec<&str> = line.split(' ').collect();
-    if words.is_empty() {
-        return (binox, BIR::Error("you must enter text".into()));
-    }
-    match words[0].to_lowercase().as_str() {
-        "h" | "help" => {
-            println!(
-                "\n
-{}
+pub fn interpret(binox: Binox, line: String) -> (Binox, BIR) {
+    interpret_with(binox, line, &Output::default())
+}
+
+/// The list of commands. A translation replaces it as a whole, under the key
+/// `help`.
+const HELP: &str = "\n
+{title}
 
 Rules:
 Fill the board with {x}'s and {o}'s such that the following conditions are met:
@@ -35,114 +112,620 @@ Fill the board with {x}'s and {o}'s such that the following conditions are met:
 All cells must be filled. Each puzzle has exactly one solution.
 
 Commands:
-x (row) (column): sets an {x} in the specified cell.
-o (row) (column): sets an {o} in the specified cell.
-erase (row) (column): erases the specified cell.
+x (cells): sets an {x} in the specified cells.
+o (cells): sets an {o} in the specified cells.
+erase (cells): erases the specified cells.
+lock (cells): turns the filled cells into clues that cannot be changed.
+unlock (cells): turns clues back into ordinary cells.
+edit [on|off]: in edit mode, every symbol placed becomes a clue, for entering a puzzle from a book.
+play: leaves edit mode.
+export [clues|all]: prints the clues as a new puzzle string, or with 'all', every filled cell as a clue.
+copy [clues|all]: copies the puzzle string that 'export' would print to the clipboard.
+paste: loads the puzzles on the clipboard, one puzzle string per line, as a new collection.
+ - A cell is a column letter followed by a row number, such as 'a3' or 'a 3'.
+ - Two numbers are read as the column and then the row, so 'x 0 3' is the same as 'x a3'.
+ - Several cells of a row are given as the row and a list of columns, such as 'x 0 2-5' or 'o 3 b,e,g'.
+ - Several cells of a column are given with 'col', such as 'x col b 1,4,7'.
+ - If any of the cells cannot be changed, none of them are.
 clear: resets the puzzle to its original state.
+mark (symbol) (cells): notes that the symbol might go in the specified empty cells.
+unmark [symbol] (cells): removes the notes of one symbol, or all notes, from the specified cells.
+marks [on|off]: shows or hides the notes on the board.
+assist (on|off): refuses moves that would break a rule right away, and explains which rule.
+auto (on|off): after each move, fills the cells forced by a pair, a gap or a full count. Filled cells are set apart on the board and can be undone on their own.
+undo [count]: takes back the last change, or the given number of changes.
+redo [count]: restores changes that were taken back.
 verify: tells you whether any rules have been broken so far.
+check (cell): tells you whether the symbol in one cell is correct, or if the solution is unknown, whether it breaks a rule.
+candidates: shows the symbols that could still go in each empty cell, with '*' where both could.
+time: shows how long you have spent on the current puzzle.
+info: shows the current puzzle's number, size, difficulty and title, and for a generated puzzle, the seed and options to generate it again.
+meta [(field) (value)]: shows the current puzzle's author, source, creation date and notes, or sets one, as in 'meta author Ann Lee', 'meta created 2024-05-17' or 'meta created today'. 'meta notes none' removes the notes. They are kept by 'save' in '.binoxset' and JSON files.
+title [(name)]: names the current puzzle, as in 'title Sunday special', or shows its name. The title is shown above the board and in 'list', and is kept by 'save'. 'title none' removes it.
+stats: summarizes the puzzles you have solved, your times, hints and streak.
+campaign: shows the levels of the campaign, from 4x4 easy to 16x16 expert, and which are cleared. Each level unlocks the next.
+campaign play [(size) (difficulty)]: loads the next level of the campaign, or an unlocked level such as '6x6 hard'.
+shuffle [seed (seed)]: puts the puzzles of the collection in a random order for 'next' and 'previous', the same order for the same seed. The file only changes when you 'save'.
+stars (1-5): rates a puzzle you have finished from 1 to 5 stars. 'list' shows the average stars of each puzzle.
+rate: grades the current puzzle from its clues and lists the techniques it needs.
+count [limit]: counts the solutions of the board as it stands, stopping at the limit (1000 by default).
+peek [row (row)|col (column)]: shows the solution, or one line of it, without changing your board.
+mistakes: highlights the cells you have filled that differ from the solution, without showing the solution.
 solve: solves the puzzle.
+hint [level]: gives a hint found by logic. Level 1 points to a row or column, level 2 names a cell, and level 3 fills the cell and explains why. They cost 1, 2 and 3 hint points, from the puzzle's hint_budget if one is set.
+techniques: lists the solving techniques the hints use, from simplest to hardest.
+explain (technique): describes a technique and shows an example board where it finds a cell.
+step: fills the next cell the logical solver would, names the technique it used and why, and highlights the cells involved.
 new (size): creates a blank puzzle of the specified size.
-generate (size) [perfect] [extras]: generates a puzzle of the specified size with exactly one solution.
+generate (size) [perfect] [extras] [seed (number)]: generates a puzzle of the specified size with exactly one solution.
  - If perfect is specified, the puzzle will have no unnecessary clues but will take longer to generate.
  - If extras is specified, the puzzle will have extra clues equal to the specified number.
-import (file name): imports puzzles from the specified file.
+ - If a seed is specified, the same seed and options always generate the same puzzle. A seed is a number or a word such as 'october-challenge'.
+ - Ctrl-C stops a long solve, count or generation without changing the board.
+import (file name): imports puzzles from the specified puzzle file or .binoxset collection. Puzzles with problems are listed with their line and character, and you are offered to skip them.
+import (pack name) (category): imports one category of puzzles from the specified puzzle pack.
+pack (directory) (pack name): compresses every puzzle file in the directory into one puzzle pack.
+unpack (pack name) (directory): extracts every puzzle file of a puzzle pack into the directory.
 next: saves progress on the current puzzle and moves to the next puzzle.
 previous: saves progress on the current puzzle and moves to the previous puzzle.
+goto (number): saves progress on the current puzzle and moves to the puzzle with that number in 'list'.
+list: lists the puzzles in the current collection with their size, how much is filled in, and a mark on the solved ones.
+add: adds the board as it stands to the end of the collection.
+save [file]: writes the collection, with your progress, to the file (by default the one it was loaded from). Titles, grades and answer keys are kept in .binoxset files only.
+remove [number]: removes the puzzle with that number in 'list', or the current one, from the collection.
+quiet [on|off]: after a move, prints only the changed row or column instead of the whole board.
+verbose: prints the whole board after every move again.
+show: prints the whole board.
+tallies [on|off]: shows how many of each symbol every row and column has, out of the number it needs.
+config: displays the current settings and the file they were read from.
+display (ascii|color|spoken|auto): switches between plain ASCII boards for copying, colored boards, a short spoken summary for screen readers, and the drawing that suits the terminal, which is the default.
+read (row (row)|col (column)|cell (cell)|board): describes part of the board in words, e.g. 'row 3: X, blank, given O, O'.
+style (classic|boxed): switches between the classic board and a tighter board drawn with box characters.
 help: displays this list.
-exit: exits the program.",
-                "BINOX".bold().underline(),
-                x="X".red().bold(),
-                o="O".blue().bold(),
-            );
+exit: exits the program.";
+
+#[cfg(feature = "clipboard")]
+fn copy_puzzle(puzzle: &str) -> Result<(), String> {
+    clipboard::copy(puzzle)
+}
+
+#[cfg(not(feature = "clipboard"))]
+fn copy_puzzle(_: &str) -> Result<(), String> {
+    Err("this build of binox has no clipboard support".into())
+}
+
+#[cfg(feature = "clipboard")]
+fn paste_puzzles() -> Result<Vec<String>, String> {
+    clipboard::puzzles(&clipboard::paste()?)
+}
+
+#[cfg(not(feature = "clipboard"))]
+fn paste_puzzles() -> Result<Vec<String>, String> {
+    Err("this build of binox has no clipboard support".into())
+}
+
+/// The cell a hint is about, followed by the cells that explain it.
+fn hint_cells(deduction: &Deduction) -> Vec<(u8, u8)> {
+    let mut cells = vec![(deduction.row, deduction.col)];
+    cells.extend(&deduction.reasons);
+    cells
+}
+
+/// An error about one cell, such as "cell a3: only empty cells can be marked."
+fn cell_error(row: u8, col: u8, error: &str) -> String {
+    let cell = format!("{}{row}", column_name(col));
+    tr_with(
+        "cell {cell}: {error}",
+        &[("cell", &cell), ("error", &tr(error))],
+    )
+}
+
+/// A cell that could not be changed, such as "cell a3 is a given and cannot
+/// be modified", in the interpreter's language.
+fn binox_error(error: BinoxError) -> String {
+    let (row, col) = error.cell();
+    let cell = format!("{}{row}", column_name(col));
+    match error {
+        BinoxError::OutOfRange { size, .. } => tr_with(
+            "row {row}, column {col} is outside the {size}x{size} board",
+            &[
+                ("row", &row.to_string()),
+                ("col", &col.to_string()),
+                ("size", &size.to_string()),
+            ],
+        ),
+        BinoxError::Given { .. } => tr_with(
+            "cell {cell} is a given and cannot be modified",
+            &[("cell", &cell)],
+        ),
+        BinoxError::Empty { .. } => tr_with(
+            "cell {cell} is empty; only filled cells can be locked",
+            &[("cell", &cell)],
+        ),
+        BinoxError::NotLocked { .. } => tr_with("cell {cell} is not locked", &[("cell", &cell)]),
+    }
+}
+
+/// Locks the cells placed in edit mode, and the clues that were unlocked so
+/// they could be overwritten.
+fn lock_edits(binox: &mut Binox, before: &Binox) {
+    for row in 0..binox.size() {
+        for col in 0..binox.size() {
+            let cell = binox.get_cell(row, col).unwrap();
+            let changed = before.get_cell(row, col) != Ok(cell);
+            if cell != BinoxCell::EMPTY && (changed || before.is_default(row, col).unwrap()) {
+                binox.lock(row, col).unwrap();
+            }
+        }
+    }
+}
+
+/// Reads the level of a hint: 1 (nudge) if not given, 2 (cell) or 3 (fill).
+pub fn hint_level(word: Option<&str>) -> Result<u8, String> {
+    match word.map(str::to_lowercase).as_deref() {
+        None | Some("1") | Some("nudge") => Ok(1),
+        Some("2") | Some("cell") => Ok(2),
+        Some("3") | Some("fill") => Ok(3),
+        _ => Err("hint level must be 1 (nudge), 2 (cell) or 3 (fill)".into()),
+    }
+}
+
+/// The hint points a hint of the level takes from a puzzle's budget. The
+/// more a hint gives away, the more it costs.
+pub fn hint_cost(level: u8) -> usize {
+    level as usize
+}
+
+/// What the hint a line asks for costs, or `None` if the line does not ask
+/// for a hint.
+fn requested_hint_cost(line: &str) -> Option<usize> {
+    let mut words = line.split_whitespace();
+    let level = match words.next()?.to_lowercase().as_str() {
+        "step" => 3,
+        "hint" => hint_level(words.next()).ok()?,
+        _ => return None,
+    };
+    Some(hint_cost(level))
+}
+
+/// The hints taken on one puzzle, and the hint points they cost.
+#[derive(Clone, Copy, Debug, Default)]
+struct HintsTaken {
+    count: usize,
+    points: usize,
+}
+
+/// The lowercase name of a command, with the theme's symbols standing for
+/// `x` and `o`.
+fn command_name(word: &str) -> String {
+    match (word.to_lowercase(), current_theme().cell_for(word)) {
+        (command, _) if command == "x" || command == "o" => command,
+        (_, Some(BinoxCell::X)) => "x".into(),
+        (_, Some(BinoxCell::O)) => "o".into(),
+        (command, _) => command,
+    }
+}
+
+pub fn interpret_with(mut binox: Binox, line: String, out: &Output) -> (Binox, BIR) {
+    let words: Vec<&str> = line.split(' ').collect();
+    if words.is_empty() {
+        return (binox, BIR::Error("you must enter text".into()));
+    }
+    let command = command_name(words[0]);
+    match command.as_str() {
+        "h" | "help" => {
+            let help = locale::lookup("help").unwrap_or_else(|| HELP.into());
+            out.help(&fill(
+                &help,
+                &[
+                    ("title", &"BINOX".bold().underline().to_string()),
+                    ("x", &current_theme().paint(BinoxCell::X).bold().to_string()),
+                    ("o", &current_theme().paint(BinoxCell::O).bold().to_string()),
+                ],
+            ));
             (binox, BIR::Normal(false))
         }
-        "x" => {
-            if words.len() < 3 {
+        "lock" | "unlock" => {
+            if words.len() < 2 {
                 return (
                     binox,
-                    BIR::Error("command 'x' requires arguments for row and column".into()),
+                    BIR::Error(tr_with(
+                        "command '{command}' requires a cell, such as 'a3'",
+                        &[("command", &command)],
+                    )),
                 );
             };
-            let col: u8 = match words[1].parse() {
-                Ok(a) => a,
-                Err(_) => return (binox, BIR::Error("column must be an integer".into())),
-            };
-            let row: u8 = match words[2].parse() {
+            let cells = match parse_cells(&words[1..]) {
                 Ok(a) => a,
-                Err(_) => return (binox, BIR::Error("row must be an integer".into())),
+                Err(s) => return (binox, BIR::Error(s)),
             };
-            let result = binox.set_cell(row, col, BinoxCell::X);
-            let result_text = match result {
-                Ok(_) => BIR::Normal(true),
-                Err(s) => BIR::Error(s.into()),
+            let original = binox.clone();
+            for (row, col) in cells {
+                let result = match command.as_str() {
+                    "lock" => binox.lock(row, col),
+                    _ => binox.unlock(row, col),
+                };
+                if let Err(s) = result {
+                    return (original, BIR::Error(binox_error(s)));
+                }
+            }
+            (binox, BIR::Normal(true))
+        }
+        "edit" => match words.get(1).map(|w| w.to_lowercase()).as_deref() {
+            None | Some("on") => (binox, BIR::Edit(true)),
+            Some("off") => (binox, BIR::Edit(false)),
+            _ => (
+                binox,
+                BIR::Error("command 'edit' takes 'on' or 'off'".into()),
+            ),
+        },
+        "play" => (binox, BIR::Edit(false)),
+        "quiet" => match words.get(1).map(|w| w.to_lowercase()).as_deref() {
+            None | Some("on") => (binox, BIR::Quiet(true)),
+            Some("off") => (binox, BIR::Quiet(false)),
+            _ => (
+                binox,
+                BIR::Error("command 'quiet' takes 'on' or 'off'".into()),
+            ),
+        },
+        "verbose" => (binox, BIR::Quiet(false)),
+        "show" => (binox, BIR::Show),
+        "tallies" | "counts" => match words.get(1).map(|w| w.to_lowercase()).as_deref() {
+            None | Some("on") => (binox, BIR::Tallies(true)),
+            Some("off") => (binox, BIR::Tallies(false)),
+            _ => (
+                binox,
+                BIR::Error("command 'tallies' takes 'on' or 'off'".into()),
+            ),
+        },
+        "export" => match words.get(1).map(|w| w.to_lowercase()).as_deref() {
+            None | Some("clues") => {
+                out.puzzle(&binox.puzzle_string(false));
+                (binox, BIR::Normal(false))
+            }
+            Some("all") => {
+                out.puzzle(&binox.puzzle_string(true));
+                (binox, BIR::Normal(false))
+            }
+            _ => (
+                binox,
+                BIR::Error("command 'export' takes 'clues' or 'all'".into()),
+            ),
+        },
+        "copy" => {
+            let all = match words.get(1).map(|w| w.to_lowercase()).as_deref() {
+                None | Some("clues") => false,
+                Some("all") => true,
+                _ => {
+                    return (
+                        binox,
+                        BIR::Error("command 'copy' takes 'clues' or 'all'".into()),
+                    )
+                }
             };
-            (binox.clone(), result_text)
+            match copy_puzzle(&binox.puzzle_string(all)) {
+                Ok(()) => {
+                    out.success("copied the puzzle to the clipboard");
+                    (binox, BIR::Normal(false))
+                }
+                Err(text) => (binox, BIR::Error(text)),
+            }
         }
-        "o" => {
-            if words.len() < 3 {
+        "paste" => match paste_puzzles() {
+            Ok(puzzles) => (binox, BIR::Paste(puzzles)),
+            Err(text) => (binox, BIR::Error(text)),
+        },
+        "x" | "o" | "e" | "empty" | "erase" => {
+            let (cell, name) = match command.as_str() {
+                "x" => (BinoxCell::X, "x"),
+                "o" => (BinoxCell::O, "o"),
+                _ => (BinoxCell::EMPTY, "erase"),
+            };
+            if words.len() < 2 {
                 return (
                     binox,
-                    BIR::Error("command 'o' requires arguments for row and column".into()),
+                    BIR::Error(tr_with(
+                        "command '{command}' requires a cell, such as 'a3'",
+                        &[("command", name)],
+                    )),
                 );
             };
-            let col: u8 = match words[1].parse() {
+            let cells = match parse_cells(&words[1..]) {
                 Ok(a) => a,
-                Err(_) => return (binox, BIR::Error("column must be an integer".into())),
+                Err(s) => return (binox, BIR::Error(s)),
             };
-            let row: u8 = match words[2].parse() {
-                Ok(a) => a,
-                Err(_) => return (binox, BIR::Error("row must be an integer".into())),
+            let original = binox.clone();
+            for (row, col) in cells {
+                if let Err(s) = binox.set_cell(row, col, cell) {
+                    return (original, BIR::Error(binox_error(s)));
+                }
+            }
+            (binox, BIR::Normal(true))
+        }
+        "c" | "clear" | "reset" => {
+            binox.reset();
+            (binox, BIR::Normal(true))
+        }
+        "check" if words.len() > 1 => match parse_cell(&words[1..]) {
+            Ok((row, col)) => (binox, BIR::CheckCell(row, col)),
+            Err(s) => (binox, BIR::Error(s)),
+        },
+        "v" | "check" | "verify" => {
+            out.verify(&binox);
+            (binox, BIR::Normal(true))
+        }
+        "techniques" => {
+            out.techniques();
+            (binox, BIR::Normal(false))
+        }
+        "explain" if words.len() > 1 => match words[1..].join(" ").parse::<Technique>() {
+            Ok(technique) => {
+                let example = technique.example(&mut StdRng::seed_from_u64(EXAMPLE_SEED));
+                out.technique(technique, example.as_ref());
+                (binox, BIR::Normal(false))
+            }
+            Err(e) => (binox, BIR::Error(e.into())),
+        },
+        "hint" | "step" => {
+            let level = match hint_level(words.get(1).copied()) {
+                _ if command == "step" => 3,
+                Ok(level) => level,
+                Err(text) => return (binox, BIR::Error(text)),
             };
-            let result = binox.set_cell(row, col, BinoxCell::O);
-            let result_text = match result {
-                Ok(_) => BIR::Normal(true),
-                Err(s) => BIR::Error(s.into()),
+            if !binox.is_valid() {
+                return (binox, BIR::Error("a mistake has been made".into()));
+            }
+            let Some(deduction) = binox.next_deduction() else {
+                return (binox, BIR::Error("no cell can be deduced".into()));
             };
-            (binox.clone(), result_text)
+            let name = format!("{}{}", column_name(deduction.col), deduction.row);
+            match level {
+                1 => {
+                    out.info(&match deduction.line() {
+                        (true, row) => tr_with("look at row {row}", &[("row", &row.to_string())]),
+                        (false, col) => tr_with(
+                            "look at column {column}",
+                            &[("column", &column_name(col).to_string())],
+                        ),
+                    });
+                    (binox, BIR::Normal(false))
+                }
+                2 => {
+                    out.info(&tr_with(
+                        "cell {cell} can be deduced using {technique}",
+                        &[
+                            ("cell", &name),
+                            ("technique", &tr(deduction.technique.name())),
+                        ],
+                    ));
+                    out.set_hinted(hint_cells(&deduction));
+                    (binox, BIR::Normal(true))
+                }
+                _ => {
+                    binox
+                        .set_cell(deduction.row, deduction.col, deduction.cell)
+                        .unwrap();
+                    out.set_hinted(hint_cells(&deduction));
+                    let text = if command == "step" {
+                        "{technique}: cell {cell} is {symbol}: {reason}"
+                    } else {
+                        "cell {cell} is {symbol}: {reason}"
+                    };
+                    out.info(&tr_with(
+                        text,
+                        &[
+                            ("technique", &tr(deduction.technique.name())),
+                            ("cell", &name),
+                            (
+                                "symbol",
+                                &current_theme().symbol(deduction.cell).to_string(),
+                            ),
+                            ("reason", &deduction.explanation()),
+                        ],
+                    ));
+                    (binox, BIR::Normal(true))
+                }
+            }
         }
-        "e" | "empty" | "erase" => {
-            if words.len() < 3 {
+        "mark" | "unmark" => {
+            let symbol = words.get(1).and_then(|w| current_theme().cell_for(w));
+            let cell_words = match symbol {
+                Some(_) => &words[2.min(words.len())..],
+                None => &words[1..],
+            };
+            if (command == "mark" && symbol.is_none()) || cell_words.is_empty() {
                 return (
                     binox,
-                    BIR::Error("command 'erase' requires arguments for row and column".into()),
+                    BIR::Error(tr_with(
+                        "command '{command}' requires a symbol and cells, such as 'mark x a3'",
+                        &[("command", &command)],
+                    )),
                 );
-            };
-            let col: u8 = match words[1].parse() {
+            }
+            let cells = match parse_cells(cell_words) {
                 Ok(a) => a,
-                Err(_) => return (binox, BIR::Error("column must be an integer".into())),
+                Err(s) => return (binox, BIR::Error(s)),
             };
-            let row: u8 = match words[2].parse() {
-                Ok(a) => a,
-                Err(_) => return (binox, BIR::Error("row must be an integer".into())),
+            let symbol = symbol.unwrap_or(BinoxCell::EMPTY);
+            if command == "mark" {
+                (binox, BIR::Mark(symbol, cells))
+            } else {
+                (binox, BIR::Unmark(symbol, cells))
+            }
+        }
+        "marks" => match words.get(1).map(|w| w.to_lowercase()).as_deref() {
+            None => (binox, BIR::ShowMarks(None)),
+            Some("on") => (binox, BIR::ShowMarks(Some(true))),
+            Some("off") => (binox, BIR::ShowMarks(Some(false))),
+            _ => (
+                binox,
+                BIR::Error("command 'marks' takes 'on', 'off' or nothing".into()),
+            ),
+        },
+        "auto" => match words.get(1).map(|w| w.to_lowercase()).as_deref() {
+            Some("on") => (binox, BIR::Auto(true)),
+            Some("off") => (binox, BIR::Auto(false)),
+            _ => (
+                binox,
+                BIR::Error("command 'auto' requires argument 'on' or 'off'".into()),
+            ),
+        },
+        "assist" => match words.get(1).map(|w| w.to_lowercase()).as_deref() {
+            Some("on") => (binox, BIR::Assist(true)),
+            Some("off") => (binox, BIR::Assist(false)),
+            _ => (
+                binox,
+                BIR::Error("command 'assist' requires argument 'on' or 'off'".into()),
+            ),
+        },
+        "u" | "undo" | "redo" => {
+            let count = match words.get(1).map(|w| w.parse::<usize>()) {
+                None => 1,
+                Some(Ok(count)) if count > 0 => count,
+                _ => return (binox, BIR::Error("count must be a positive integer".into())),
+            };
+            if command == "redo" {
+                (binox, BIR::Redo(count))
+            } else {
+                (binox, BIR::Undo(count))
+            }
+        }
+        "mistakes" => (binox, BIR::Mistakes),
+        "time" => (binox, BIR::Time),
+        "info" => (binox, BIR::Info),
+        "shuffle" => match (words.get(1), words.get(2)) {
+            (None, _) => (binox, BIR::Shuffle(None)),
+            (Some(word), Some(seed)) if word.eq_ignore_ascii_case("seed") => {
+                (binox, BIR::Shuffle(Some(seed_from_str(seed))))
+            }
+            _ => (
+                binox,
+                BIR::Error("command 'shuffle' takes nothing or 'seed (seed)'".into()),
+            ),
+        },
+        "stars" => match words.get(1).and_then(|w| w.parse().ok()) {
+            Some(stars) if (1..=5).contains(&stars) => (binox, BIR::Stars(stars)),
+            _ => (
+                binox,
+                BIR::Error("command 'stars' takes a number from 1 to 5".into()),
+            ),
+        },
+        "meta" => {
+            let Some(field) = words.get(1).filter(|w| !w.is_empty()) else {
+                return (binox, BIR::Meta(None));
             };
-            let result = binox.set_cell(row, col, BinoxCell::EMPTY);
-            let result_text = match result {
-                Ok(_) => BIR::Normal(true),
-                Err(s) => BIR::Error(s.into()),
+            let field = match field.parse::<MetaField>() {
+                Ok(field) => field,
+                Err(text) => return (binox, BIR::Error(text)),
             };
-            (binox.clone(), result_text)
+            match words[2..].join(" ").trim() {
+                "" => (
+                    binox,
+                    BIR::Error("give a value, or 'none' to remove it".into()),
+                ),
+                "none" => (binox, BIR::Meta(Some((field, None)))),
+                "today" if field == MetaField::Created => {
+                    let date = timer::format_day(timer::today());
+                    (binox, BIR::Meta(Some((field, Some(date)))))
+                }
+                value => (binox, BIR::Meta(Some((field, Some(value.into()))))),
+            }
         }
-        "c" | "clear" | "reset" => {
-            binox.reset();
-            (binox, BIR::Normal(true))
+        "title" => match words[1..].join(" ").trim() {
+            "" => (binox, BIR::Title(None)),
+            "none" => (binox, BIR::Title(Some(None))),
+            title => (binox, BIR::Title(Some(Some(title.into())))),
+        },
+        "stats" => (binox, BIR::Stats),
+        "campaign" => match (
+            words.get(1).map(|w| w.to_lowercase()).as_deref(),
+            &words[1..],
+        ) {
+            (None, _) => (binox, BIR::Campaign),
+            (Some("play"), [_]) => (binox, BIR::PlayLevel(None)),
+            (Some("play"), [_, size, difficulty]) => {
+                let size = size.split('x').next().and_then(|size| size.parse().ok());
+                let difficulty = difficulty.parse::<Difficulty>().ok();
+                let level = Level::all()
+                    .find(|level| Some(level.size) == size && Some(level.difficulty) == difficulty);
+                match level {
+                    Some(level) => (binox, BIR::PlayLevel(Some(level))),
+                    None => (binox, BIR::Error("no such level in the campaign".into())),
+                }
+            }
+            _ => (
+                binox,
+                BIR::Error(
+                    "command 'campaign' takes nothing, 'play' or 'play (size) (difficulty)'".into(),
+                ),
+            ),
+        },
+        "rate" => {
+            let mut clues = binox.clone();
+            clues.reset();
+            out.rating(&clues.rate());
+            (binox, BIR::Normal(false))
         }
-        "v" | "check" | "verify" => {
-            match (binox.is_full(), binox.is_valid()) {
-                (true, true) => println!("{}", "the puzzle has been solved".green().bold()),
-                (false, true) => println!("{}", "no mistakes so far".yellow().bold()),
-                (_, false) => println!("{}", "a mistake has been made".red().bold()),
+        "count" => {
+            let limit = match words.get(1).map(|w| w.parse::<usize>()) {
+                None => COUNT_LIMIT,
+                Some(Ok(limit)) if limit > 0 => limit,
+                _ => {
+                    return (
+                        binox,
+                        BIR::Error("the limit must be a positive number".into()),
+                    )
+                }
             };
-            (binox, BIR::Normal(true))
+            match binox.count_with_stop(limit, &INTERRUPTED) {
+                Some(count) => {
+                    out.solution_count(count, limit);
+                    (binox, BIR::Normal(false))
+                }
+                None => (binox, BIR::Error(INTERRUPTED_TEXT.into())),
+            }
         }
-        "p" | "presolve" => {
-            binox.presolve();
-            (binox, BIR::Normal(true))
+        "peek" | "show-solution" => match (words.get(1).copied(), words.get(2)) {
+            (None, _) => (binox, BIR::Peek(None)),
+            (Some("row"), Some(row)) => match row.parse() {
+                Ok(row) if row < binox.size() => (binox, BIR::Peek(Some((true, row)))),
+                _ => (binox, BIR::Error("row must be a row number".into())),
+            },
+            (Some("col") | Some("column"), Some(col)) => match parse_column(col) {
+                Some(col) if col < binox.size() => (binox, BIR::Peek(Some((false, col)))),
+                _ => (binox, BIR::Error("column must be a column letter".into())),
+            },
+            _ => (
+                binox,
+                BIR::Error("command 'peek' takes nothing, 'row (row)' or 'col (column)'".into()),
+            ),
+        },
+        "candidates" => {
+            out.board_with_marks(&binox, Some(&Marks::from_candidates(&binox)));
+            let dead: Vec<String> = binox
+                .compute_candidates()
+                .into_iter()
+                .filter(|(_, _, symbols)| symbols.is_empty())
+                .map(|(row, col, _)| format!("{}{row}", column_name(col)))
+                .collect();
+            if !dead.is_empty() {
+                out.warning(&tr_with(
+                    "no symbol fits in {cells}",
+                    &[("cells", &dead.join(", "))],
+                ));
+            }
+            (binox, BIR::Normal(false))
         }
-        "s" | "solve" => match binox.solve(true) {
-            BinoxSolution::Zero => (binox, BIR::Error("puzzle has no solution".into())),
-            BinoxSolution::One(a) => (a, BIR::Normal(true)),
-            BinoxSolution::Multiple(a, _) => {
-                println!("{}", "multiple solutions found".yellow().bold());
+        "p" | "presolve" => match binox.presolve() {
+            PresolveResult::Good => (binox, BIR::Normal(true)),
+            PresolveResult::Bad => (binox, BIR::Error("a mistake has been made".into())),
+        },
+        "s" | "solve" => match binox.search(true, &INTERRUPTED) {
+            None => (binox, BIR::Error(INTERRUPTED_TEXT.into())),
+            Some(BinoxSolution::Zero) => (binox, BIR::Error("puzzle has no solution".into())),
+            Some(BinoxSolution::One(a)) => (a, BIR::Normal(true)),
+            Some(BinoxSolution::Multiple(a, _)) => {
+                out.warning("multiple solutions found");
                 (a, BIR::Normal(true))
             }
         },
@@ -158,11 +741,26 @@ exit: exits the program.",
                 Err(_) => return (binox, BIR::Error("size must be an integer".into())),
             };
             match Binox::new(size) {
-                Ok(binox) => (binox, BIR::Normal(true)),
+                Ok(binox) => {
+                    let entry = PuzzleEntry::new(binox.as_string());
+                    (binox, BIR::Generated(Box::new(entry)))
+                }
                 Err(s) => (binox, BIR::Error(s.into())),
             }
         }
         "g" | "generate" => {
+            let mut words = words;
+            let seed = match words.iter().position(|w| w.eq_ignore_ascii_case("seed")) {
+                Some(i) => {
+                    let seed = match words.get(i + 1) {
+                        Some(seed) => seed_from_str(seed),
+                        None => return (binox, BIR::Error("seed requires a value".into())),
+                    };
+                    words.drain(i..i + 2);
+                    seed
+                }
+                None => rand::random(),
+            };
             if words.len() < 2 {
                 return (
                     binox,
@@ -183,10 +781,21 @@ exit: exits the program.",
                 || (words.len() > 2
                     && (words[2].to_lowercase() == "perfect" || words[2].to_lowercase() == "p"));
             if perfect {
-                println!("generating perfect")
+                out.info("generating perfect")
             }
-            match Binox::generate(size, perfect, extras) {
-                Ok(binox) => (binox, BIR::Normal(true)),
+            let options = GenerateOptions {
+                perfect,
+                extras,
+                symmetry: Symmetry::None,
+            };
+            match PuzzleEntry::generate_with_stop(size, seed, options, &INTERRUPTED) {
+                Ok(entry) => (
+                    Binox::new_from_string(entry.puzzle.clone()),
+                    BIR::Generated(Box::new(entry)),
+                ),
+                Err(_) if INTERRUPTED.load(Ordering::Relaxed) => {
+                    (binox, BIR::Error(INTERRUPTED_TEXT.into()))
+                }
                 Err(s) => (binox, BIR::Error(s.into())),
             }
         }
@@ -197,78 +806,1219 @@ exit: exits the program.",
                     BIR::Error("command 'import' requires argument for file name".into()),
                 );
             };
+            if words.len() > 2 {
+                return (binox, BIR::ImportPack(words[1].into(), words[2].into()));
+            }
             (binox, BIR::Import(words[1].into()))
         }
+        "pack" => {
+            if words.len() < 3 {
+                return (
+                    binox,
+                    BIR::Error(
+                        "command 'pack' requires arguments for directory and pack name".into(),
+                    ),
+                );
+            };
+            let filename = pack_file_name(words[2]);
+            match puzzle_pack::pack(words[1], &filename) {
+                Ok(entries) => {
+                    for entry in entries {
+                        out.info(&tr_with(
+                            "{category}: {count} puzzles",
+                            &[
+                                ("category", &entry.category),
+                                ("count", &entry.count.to_string()),
+                            ],
+                        ));
+                    }
+                    out.success(&tr_with(
+                        "created puzzle pack {file}",
+                        &[("file", &filename)],
+                    ));
+                    (binox, BIR::Normal(false))
+                }
+                Err(s) => (binox, BIR::Error(s)),
+            }
+        }
+        "unpack" => {
+            if words.len() < 3 {
+                return (
+                    binox,
+                    BIR::Error(
+                        "command 'unpack' requires arguments for pack name and directory".into(),
+                    ),
+                );
+            };
+            match puzzle_pack::unpack(&pack_file_name(words[1]), words[2]) {
+                Ok(entries) => {
+                    for entry in entries {
+                        out.info(&entry.file_name());
+                    }
+                    (binox, BIR::Normal(false))
+                }
+                Err(s) => (binox, BIR::Error(s)),
+            }
+        }
         "ne" | "next" => (binox, BIR::Next),
         "pr" | "prev" | "previous" => (binox, BIR::Previous),
+        "goto" | "go" => match words.get(1).map(|w| w.parse::<usize>()) {
+            Some(Ok(index)) => (binox, BIR::Goto(index)),
+            _ => (
+                binox,
+                BIR::Error("command 'goto' must be followed by a puzzle number from 'list'".into()),
+            ),
+        },
+        "list" | "ls" => (binox, BIR::List),
+        "add" => (binox, BIR::Add),
+        "save" => (binox, BIR::Save(words.get(1).map(|w| w.to_string()))),
+        "remove" | "rm" => match words.get(1).map(|w| w.parse::<usize>()) {
+            None => (binox, BIR::Remove(None)),
+            Some(Ok(index)) => (binox, BIR::Remove(Some(index))),
+            Some(Err(_)) => (
+                binox,
+                BIR::Error("puzzle number must be a number from 'list'".into()),
+            ),
+        },
+        "config" | "settings" => (binox, BIR::Config),
+        "display" => match words.get(1).map(|w| w.to_lowercase()).as_deref() {
+            Some("ascii") | Some("plain") => (binox, BIR::Display(Rendering::Plain)),
+            Some("color") | Some("colour") => (binox, BIR::Display(Rendering::Color)),
+            Some("spoken") | Some("speech") => (binox, BIR::Display(Rendering::Spoken)),
+            Some("auto") => (binox, BIR::Display(Rendering::Auto)),
+            _ => (
+                binox,
+                BIR::Error(
+                    "command 'display' requires argument 'ascii', 'color', 'spoken' or 'auto'"
+                        .into(),
+                ),
+            ),
+        },
+        "read" => {
+            let result = match (words.get(1).copied(), words.get(2)) {
+                (Some("row"), Some(row)) => match row.parse() {
+                    Ok(row) => binox.describe_row(row),
+                    Err(_) => Err("row must be an integer".into()),
+                },
+                (Some("col") | Some("column"), Some(col)) => match parse_column(col) {
+                    Some(col) => binox.describe_col(col),
+                    None => Err("column must be a letter or an integer".into()),
+                },
+                (Some("cell"), Some(_)) => {
+                    parse_cell(&words[2..]).and_then(|(row, col)| binox.describe_cell(row, col))
+                }
+                (Some("board"), _) => Ok(binox.describe_board()),
+                _ => Err(
+                    "command 'read' requires 'row (row)', 'col (column)', 'cell (cell)' or 'board'"
+                        .into(),
+                ),
+            };
+            match result {
+                Ok(text) => {
+                    out.info(&text);
+                    (binox, BIR::Normal(false))
+                }
+                Err(s) => (binox, BIR::Error(s)),
+            }
+        }
+        "style" => {
+            if words.len() < 2 {
+                return (
+                    binox,
+                    BIR::Error("command 'style' requires argument 'classic' or 'boxed'".into()),
+                );
+            }
+            match words[1].parse::<BoardStyle>() {
+                Ok(style) => {
+                    set_theme(Theme {
+                        style,
+                        ..current_theme()
+                    });
+                    (binox, BIR::Normal(true))
+                }
+                Err(s) => (binox, BIR::Error(s.into())),
+            }
+        }
         "exit" => (binox, BIR::Exit),
         _ => (binox, BIR::Error("invalid command".into())),
     }
 }
 
+/// Reads a column given as a letter (`a` is column 0) or as a number.
+pub fn parse_column(word: &str) -> Option<u8> {
+    match word.to_ascii_lowercase().as_bytes() {
+        [c @ b'a'..=b'z'] => Some(c - b'a'),
+        _ => word.parse().ok(),
+    }
+}
+
+/// Reads lines from stdin on another thread, so that something else, such as
+/// a connection or a clock, can be watched while waiting for the player.
+pub fn stdin_lines() -> Receiver<String> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        for line in io::stdin().lock().lines().map_while(Result::ok) {
+            if sender.send(line).is_err() {
+                break;
+            }
+        }
+    });
+    receiver
+}
+
+/// Reads a cell as `(row, column)` from `a3`, `a 3` or `3 a`, where the letter
+/// is the column, or from two numbers giving the column and then the row.
+pub fn parse_cell(words: &[&str]) -> Result<(u8, u8), String> {
+    let is_letter = |w: &str| w.len() == 1 && w.as_bytes()[0].is_ascii_alphabetic();
+    let (col, row) = match words {
+        [] => return Err("a cell is required, such as 'a3'".into()),
+        [word] => {
+            let split = word.find(|c: char| !c.is_ascii_alphabetic()).unwrap_or(0);
+            if split == 0 {
+                return Err(tr_with(
+                    "'{word}' is not a cell, such as 'a3'",
+                    &[("word", word)],
+                ));
+            }
+            word.split_at(split)
+        }
+        [row, col, ..] if is_letter(col) && !is_letter(row) => (*col, *row),
+        [col, row, ..] => (*col, *row),
+    };
+    let col = parse_column(col).ok_or("column must be a letter or an integer")?;
+    let row = row.parse().map_err(|_| "row must be an integer")?;
+    Ok((row, col))
+}
+
+/// Reads a comma-separated list of indices and inclusive ranges, like `1,4-6`.
+fn parse_list(word: &str, parse: impl Fn(&str) -> Option<u8>) -> Option<Vec<u8>> {
+    let mut result = Vec::new();
+    for item in word.split(',') {
+        match item.split_once('-') {
+            Some((start, end)) => {
+                let (start, end) = (parse(start)?, parse(end)?);
+                if start > end {
+                    return None;
+                }
+                result.extend(start..=end);
+            }
+            None => result.push(parse(item)?),
+        }
+    }
+    Some(result)
+}
+
+/// Reads one cell like `parse_cell`, or several cells of one line: a row and a
+/// list of columns (`0 2-5`, `row 3 a,c`), or `col` with a column and a list
+/// of rows (`col b 1,4,7`).
+pub fn parse_cells(words: &[&str]) -> Result<Vec<(u8, u8)>, String> {
+    let parse_row = |w: &str| w.parse().ok();
+    let row_cells = |row: &str, cols: &str| -> Result<Vec<(u8, u8)>, String> {
+        let row = row.parse().map_err(|_| "row must be an integer")?;
+        let cols = parse_list(cols, parse_column).ok_or("columns must be a list such as a-c,f")?;
+        Ok(cols.into_iter().map(|col| (row, col)).collect())
+    };
+    match words {
+        ["col" | "column", col, rows, ..] => {
+            let col = parse_column(col).ok_or("column must be a letter or an integer")?;
+            let rows = parse_list(rows, parse_row).ok_or("rows must be a list such as 1,4-6")?;
+            Ok(rows.into_iter().map(|row| (row, col)).collect())
+        }
+        ["row", row, cols, ..] => row_cells(row, cols),
+        [row, cols, ..] if cols.contains(['-', ',']) => row_cells(row, cols),
+        _ => Ok(vec![parse_cell(words)?]),
+    }
+}
+
+/// The solution of the board: the answer key of the puzzle entry if it fits
+/// the board's clues, or else the board's unique solution, which the board
+/// keeps for the next time.
+fn solution_for(binox: &mut Binox, entry: &PuzzleEntry) -> Option<Binox> {
+    entry
+        .solution
+        .as_ref()
+        .and_then(|solution| Binox::parse_strict(solution).ok())
+        .filter(|solution| binox.fits(solution))
+        .or_else(|| binox.solution())
+}
+
+/// Keeps the board, its pencil marks and the time spent on it in the current
+/// entry, so that they are saved with the collection.
+fn store_progress(puzzles: &mut PuzzleSet, binox: &Binox, marks: &Marks, timers: &Timers) {
+    puzzles.update_current(binox.as_string());
+    puzzles.update_progress(marks.encode(), timers.elapsed().as_secs());
+}
+
+/// Starts the clocks of a newly loaded collection at the times saved with it.
+fn restore_times(timers: &mut Timers, puzzles: &PuzzleSet) {
+    timers.reset(puzzles.selected());
+    for (index, entry) in puzzles.entries.iter().enumerate() {
+        if let Some(seconds) = entry.elapsed {
+            timers.restore(index, Duration::from_secs(seconds));
+        }
+    }
+}
+
+/// Shuffles a collection, keeping each puzzle's progress, time and hints with
+/// it.
+fn shuffle_set(
+    puzzles: &mut PuzzleSet,
+    binox: &Binox,
+    marks: &Marks,
+    timers: &mut Timers,
+    hints: &mut HashMap<usize, HintsTaken>,
+    seed: u64,
+) {
+    store_progress(puzzles, binox, marks, timers);
+    let order = puzzles.shuffle(seed);
+    timers.reorder(&order, puzzles.selected());
+    *hints = reorder(std::mem::take(hints), &order);
+}
+
+/// Whether `after` only has symbols added to empty cells of `before`.
+fn only_added(before: &Binox, after: &Binox) -> bool {
+    before.size() == after.size()
+        && before.as_string() != after.as_string()
+        && before
+            .as_string()
+            .chars()
+            .zip(after.as_string().chars())
+            .all(|(b, a)| b == a || b == '.')
+}
+
+/// Checks the symbols added between `before` and `after` one at a time.
+fn check_moves(before: &Binox, after: &Binox) -> Result<(), Violation> {
+    let mut board = before.clone();
+    for row in 0..board.size() {
+        for col in 0..board.size() {
+            let cell = after.get_cell(row, col).unwrap();
+            if board.get_cell(row, col).unwrap() != cell {
+                board.check_move(row, col, cell)?;
+                board.set_cell(row, col, cell).unwrap();
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Fills the cells forced on the board as one automatic step.
+fn auto_fill(binox: &mut Binox, history: &mut History, marks: &mut Marks) {
+    let forced = binox.forced_cells();
+    if forced.is_empty() {
+        return;
+    }
+    history.record_automatic(binox.clone());
+    for deduction in forced {
+        binox
+            .set_cell(deduction.row, deduction.col, deduction.cell)
+            .unwrap();
+        marks.set_auto(deduction.row, deduction.col).unwrap();
+    }
+    marks.clear_filled(binox);
+}
+
+/// Adds or removes marks on several cells, changing none of them if any fails.
+fn change_marks(
+    marks: &mut Marks,
+    binox: &Binox,
+    cell: BinoxCell,
+    cells: &[(u8, u8)],
+    add: bool,
+) -> Result<(), String> {
+    let mut changed = marks.clone();
+    for &(row, col) in cells {
+        let result = if add {
+            changed.add(binox, row, col, cell)
+        } else {
+            changed.remove(row, col, cell)
+        };
+        result.map_err(|s| cell_error(row, col, s))?;
+    }
+    changed.shown = true;
+    *marks = changed;
+    Ok(())
+}
+
+fn pack_file_name(name: &str) -> String {
+    if name.contains('.') {
+        name.into()
+    } else {
+        format!("{name}.{}", puzzle_pack::PACK_EXTENSION)
+    }
+}
+
+pub struct InterpreterOptions {
+    /// Settings such as the size of the puzzle generated at startup.
+    pub config: Config,
+    /// File the settings were read from, if any.
+    pub config_path: Option<PathBuf>,
+    /// Puzzle file to import at startup instead of generating a puzzle.
+    pub file: Option<String>,
+    /// Whether to offer restoring the previous session.
+    pub recover: bool,
+    /// File to read commands from instead of the terminal. The interpreter
+    /// exits at the end of the file.
+    pub script: Option<String>,
+    /// Whether responses are written as text or JSON lines.
+    pub output: OutputMode,
+    /// Puzzles to play instead of importing a file or generating a puzzle.
+    pub puzzles: Option<PuzzleSet>,
+    /// Called with the board and the time of each puzzle solved, such as to
+    /// send the time to a server. The message it returns, if any, is shown.
+    pub on_solve: Option<SolveHook>,
+}
+
+pub type SolveHook = Box<dyn FnMut(&str, Duration) -> Result<Option<String>, String>>;
+
+impl Default for InterpreterOptions {
+    fn default() -> Self {
+        InterpreterOptions {
+            config: Config::default(),
+            config_path: None,
+            file: None,
+            recover: true,
+            script: None,
+            output: OutputMode::Text,
+            puzzles: None,
+            on_solve: None,
+        }
+    }
+}
+
 pub fn run_interpreter() {
-    let mut binox = Binox::generate(8, true, 0).unwrap();
-    let mut puzzles: Vec<String> = vec![binox.as_string(), "            ".into()];
-    let mut selected_puzzle = 0;
-    println!("{}", binox);
+    run_interpreter_with(InterpreterOptions::default())
+}
+
+pub fn run_interpreter_with(mut options: InterpreterOptions) {
+    INTERRUPTED.store(true, Ordering::Relaxed);
+    // Ctrl-C kills the process if the flag is already set, and sets it
+    // otherwise, so a second Ctrl-C still quits a command that ignores it.
+    let _ = signal_hook::flag::register_conditional_default(SIGINT, Arc::clone(&INTERRUPTED));
+    let _ = signal_hook::flag::register(SIGINT, Arc::clone(&INTERRUPTED));
+    let mut out = Output::new(options.output);
+    let mut on_solve = options.on_solve.take();
+    let given = options.puzzles.take();
+    let config = &options.config;
+    out.set_rendering(if config.accessible {
+        Rendering::Spoken
+    } else {
+        Rendering::Auto
+    });
+    out.set_tallies(config.tallies);
+    let (mut binox, mut puzzles) = match (given, &options.file) {
+        (Some(set), _) if !set.is_empty() => {
+            (Binox::new_from_string(set.current().puzzle.clone()), set)
+        }
+        (_, Some(filename)) => match PuzzleSet::load_checked(&config.find_puzzle_file(filename))
+            .map(|(mut set, problems)| {
+                for problem in &problems {
+                    out.warning(&tr_with(
+                        "skipped the puzzle at {problem}",
+                        &[("problem", &problem.to_string())],
+                    ));
+                }
+                set.skip_problems(&problems);
+                set
+            }) {
+            Ok(set) if !set.is_empty() => {
+                (Binox::new_from_string(set.current().puzzle.clone()), set)
+            }
+            Ok(_) => {
+                out.error("file contains no puzzles");
+                return;
+            }
+            Err(text) => {
+                out.error(&text);
+                return;
+            }
+        },
+        (_, None) => {
+            let options = GenerateOptions {
+                perfect: true,
+                extras: config.difficulty.map_or(0, |d| d.extras(config.size)),
+                symmetry: Symmetry::None,
+            };
+            let entry = match PuzzleEntry::generate(config.size, rand::random(), options) {
+                Ok(entry) => PuzzleEntry {
+                    difficulty: config.difficulty,
+                    ..entry
+                },
+                Err(text) => {
+                    out.error(text);
+                    return;
+                }
+            };
+            let binox = Binox::new_from_string(entry.puzzle.clone());
+            let puzzles = PuzzleSet::new(
+                "session".into(),
+                vec![entry, PuzzleEntry::new(Binox::new(4).unwrap().as_string())],
+            );
+            (binox, puzzles)
+        }
+    };
+    let mut input: Box<dyn BufRead> = match &options.script {
+        Some(filename) => match File::open(filename) {
+            Ok(file) => Box::new(BufReader::new(file)),
+            Err(_) => {
+                out.error(&tr_with("file not found: {file}", &[("file", filename)]));
+                return;
+            }
+        },
+        None => Box::new(io::stdin().lock()),
+    };
+    let interactive = options.script.is_none() && io::stdin().is_terminal();
+    let autosaving = interactive && config.autosave.enabled;
+    let recovered = if autosaving && options.recover && options.file.is_none() {
+        autosave::load_recovery()
+    } else {
+        None
+    };
+    if let Some(recovered) = recovered {
+        out.warning("a previous session was found. restore it? (y/n)");
+        let mut answer = String::new();
+        input.read_line(&mut answer).expect("Failed to read input");
+        if answer.trim().to_lowercase().starts_with('y') {
+            puzzles = recovered;
+            binox = Binox::new_from_string(puzzles.current().puzzle.clone());
+        }
+    }
+    let mut autosave = Autosave::with_interval(config.autosave.interval());
+    let mut history = History::new();
+    let mut marks = Marks::new(binox.size());
+    marks.restore(binox.size(), puzzles.current().marks.as_deref());
+    out.set_title(puzzles.current().title.as_deref());
+    let mut auto = config.auto_fill;
+    let mut assisted = config.assisted;
+    let mut editing = false;
+    let mut quiet = config.quiet;
+    let mut timers = Timers::new(puzzles.selected());
+    restore_times(&mut timers, &puzzles);
+    let mut completed = Completed::load();
+    let mut stats = Stats::load();
+    let mut campaign = Campaign::load();
+    // The level being played, with its clues.
+    let mut level: Option<(Level, String)> = None;
+    let mut hints: HashMap<usize, HintsTaken> = HashMap::new();
+    let mut source = options.file.as_ref().map(|f| config.find_puzzle_file(f));
+    if binox.is_solved() {
+        timers.finish();
+    }
+    out.board_with_marks(&binox, Some(&marks));
     loop {
-        let mut input = String::new();
-        io::stdin()
-            .read_line(&mut input)
-            .expect("Failed to read input");
-        let input: String = input.trim().into();
-        let (new_binox, result) = interpret(binox, input);
+        let mut line = String::new();
+        if input.read_line(&mut line).expect("Failed to read input") == 0 {
+            break;
+        }
+        let line: String = line.trim().into();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = config.expand_alias(&line);
+        let before = binox.clone();
+        let hint_cost = requested_hint_cost(&line);
+        let taken = hints.get(&puzzles.selected()).copied().unwrap_or_default();
+        let over_budget = match (hint_cost, config.hint_budget) {
+            (Some(cost), Some(budget)) => taken.points + cost > budget,
+            _ => false,
+        };
+        let placing = editing
+            && line.split(' ').next().is_some_and(|word| {
+                matches!(
+                    command_name(word).as_str(),
+                    "x" | "o" | "e" | "empty" | "erase"
+                )
+            });
+        if placing {
+            binox.unlock_all();
+        }
+        let (new_binox, result) = if over_budget {
+            let left = config.hint_budget.unwrap_or_default() - taken.points;
+            let text = tr_with(
+                "this hint costs {cost}, but only {left} hint points are left on this puzzle",
+                &[
+                    ("cost", &hint_cost.unwrap_or_default().to_string()),
+                    ("left", &left.to_string()),
+                ],
+            );
+            (binox, BIR::Error(text))
+        } else {
+            INTERRUPTED.store(false, Ordering::Relaxed);
+            let interpreted = interpret_with(binox, line, &out);
+            INTERRUPTED.store(true, Ordering::Relaxed);
+            interpreted
+        };
         binox = new_binox;
+        if placing {
+            lock_edits(&mut binox, &before);
+        }
+        let result = match result {
+            BIR::Normal(_) if assisted && !editing && only_added(&before, &binox) => {
+                match check_moves(&before, &binox) {
+                    Ok(()) => result,
+                    Err(violation) => {
+                        binox = before.clone();
+                        BIR::Error(tr_with(
+                            "move refused: {reason}",
+                            &[("reason", &violation.to_string())],
+                        ))
+                    }
+                }
+            }
+            result => result,
+        };
+        if let (BIR::Normal(_), Some(cost)) = (&result, hint_cost) {
+            let taken = hints.entry(puzzles.selected()).or_default();
+            taken.count += 1;
+            taken.points += cost;
+            if let Some(budget) = config.hint_budget {
+                out.info(&tr_with(
+                    "{left} hint points left on this puzzle",
+                    &[("left", &(budget - taken.points).to_string())],
+                ));
+            }
+        }
+        let switching = matches!(
+            result,
+            BIR::Next
+                | BIR::Previous
+                | BIR::Goto(_)
+                | BIR::Remove(_)
+                | BIR::Edit(_)
+                | BIR::Import(_)
+                | BIR::ImportPack(..)
+                | BIR::Paste(_)
+        );
+        if switching {
+            out.clear_recent();
+        }
         match result {
             BIR::Normal(print) => {
-                if print {
-                    println!("{}", binox)
+                history.record_change(&before, &binox);
+                marks.clear_filled(&binox);
+                if auto && !editing && only_added(&before, &binox) {
+                    auto_fill(&mut binox, &mut history, &mut marks);
+                }
+                out.set_recent(&before, &binox);
+                if print && quiet {
+                    out.changes(&before, &binox, Some(&marks));
+                } else if print {
+                    out.board_with_marks(&binox, Some(&marks));
+                }
+            }
+            BIR::Generated(entry) => {
+                puzzles.replace_current(*entry);
+                timers.restart();
+                hints.remove(&puzzles.selected());
+                history.record_change(&before, &binox);
+                marks.clear(binox.size());
+                out.set_title(puzzles.current().title.as_deref());
+                out.board_with_marks(&binox, Some(&marks));
+            }
+            BIR::Mark(cell, cells) => match change_marks(&mut marks, &binox, cell, &cells, true) {
+                Ok(()) => out.board_with_marks(&binox, Some(&marks)),
+                Err(text) => out.error(&text),
+            },
+            BIR::Unmark(cell, cells) => {
+                match change_marks(&mut marks, &binox, cell, &cells, false) {
+                    Ok(()) => out.board_with_marks(&binox, Some(&marks)),
+                    Err(text) => out.error(&text),
+                }
+            }
+            BIR::Mistakes => match solution_for(&mut binox, puzzles.current()) {
+                Some(solution) => out.mistakes(&binox, &binox.differences(&solution)),
+                None => out.error("the puzzle does not have exactly one solution"),
+            },
+            BIR::Assist(on) => {
+                assisted = on;
+                out.info(if assisted {
+                    "moves that break a rule will be refused"
+                } else {
+                    "moves will no longer be checked"
+                });
+            }
+            BIR::Stats => out.stats(&stats, timer::today()),
+            BIR::Campaign => out.campaign(&campaign),
+            BIR::PlayLevel(chosen) => match chosen.or_else(|| campaign.next()) {
+                None => out.success("every level of the campaign is cleared!"),
+                Some(chosen) if !campaign.is_unlocked(chosen) => out.error(&tr_with(
+                    "level {level} is locked",
+                    &[("level", &chosen.to_string())],
+                )),
+                Some(chosen) => match chosen.puzzle(config) {
+                    Ok(puzzle) => {
+                        puzzles = PuzzleSet::from_lines(
+                            format!("campaign {chosen}"),
+                            &puzzle,
+                            Some(chosen.difficulty),
+                        );
+                        source = None;
+                        level = Some((chosen, canonical(&puzzle)));
+                        binox = Binox::new_from_string(puzzle);
+                        timers.reset(puzzles.selected());
+                        hints.clear();
+                        history.clear();
+                        marks.clear(binox.size());
+                        out.set_title(puzzles.current().title.as_deref());
+                        out.info(&tr_with("level {level}", &[("level", &chosen.to_string())]));
+                        out.board_with_marks(&binox, Some(&marks));
+                    }
+                    Err(text) => out.error(&text),
+                },
+            },
+            BIR::Info => out.puzzle_info(&puzzles, binox.size()),
+            BIR::Title(None) => match &puzzles.current().title {
+                Some(title) => out.info(title),
+                None => out.info("this puzzle has no title"),
+            },
+            BIR::Title(Some(title)) => {
+                puzzles.set_title(title.as_deref());
+                out.set_title(puzzles.current().title.as_deref());
+                out.board_with_marks(&binox, Some(&marks));
+            }
+            BIR::Stars(stars) => {
+                if !binox.is_solved() {
+                    out.error("finish the puzzle before rating it");
+                } else if let Err(text) = stats.rate(&binox.as_string(), stars, timer::today()) {
+                    out.error(text);
+                } else {
+                    out.success(&tr_with(
+                        "rated {stars} out of 5 stars",
+                        &[("stars", &stars.to_string())],
+                    ));
+                    if interactive {
+                        if let Err(text) = stats.save() {
+                            out.error(&text);
+                        }
+                    }
+                }
+            }
+            BIR::Meta(None) => out.meta(puzzles.current()),
+            BIR::Meta(Some((field, value))) => match puzzles.set_meta(field, value.as_deref()) {
+                Ok(()) => out.meta(puzzles.current()),
+                Err(text) => out.error(&text),
+            },
+            BIR::Time => {
+                let time = format_duration(timers.elapsed());
+                out.info(&if timers.is_finished() {
+                    tr_with("solved in {time}", &[("time", &time)])
+                } else {
+                    tr_with("time on this puzzle: {time}", &[("time", &time)])
+                });
+            }
+            BIR::Peek(line) => match solution_for(&mut binox, puzzles.current()) {
+                Some(solution) => out.solution(&binox, &solution, line),
+                None => out.error("the puzzle does not have exactly one solution"),
+            },
+            BIR::CheckCell(row, col) => {
+                let name = format!("{}{row}", column_name(col));
+                let message = |text| tr_with(text, &[("cell", &name)]);
+                match (binox.get_cell(row, col), binox.is_default(row, col)) {
+                    (Err(s), _) => out.error(s),
+                    (Ok(BinoxCell::EMPTY), _) => out.warning(&message("cell {cell} is empty")),
+                    (_, Ok(true)) => out.info(&message("cell {cell} is a clue")),
+                    (Ok(cell), _) => match solution_for(&mut binox, puzzles.current()) {
+                        Some(solution) if solution.get_cell(row, col) == Ok(cell) => {
+                            out.success(&message("cell {cell} is correct"))
+                        }
+                        Some(_) => out.error(&message("cell {cell} is wrong")),
+                        None => {
+                            let mut without = binox.clone();
+                            without.set_cell(row, col, BinoxCell::EMPTY).unwrap();
+                            match without.check_move(row, col, cell) {
+                                Ok(()) => out.warning(&message(
+                                    "cell {cell} breaks no rules, but the puzzle does not have exactly one solution to compare with"
+                                )),
+                                Err(violation) => out.error(&tr_with(
+                                    "cell {cell} is wrong: {reason}",
+                                    &[("cell", &name), ("reason", &violation.to_string())],
+                                )),
+                            }
+                        }
+                    },
+                }
+            }
+            BIR::Quiet(on) => {
+                quiet = on;
+                out.info(if quiet {
+                    "only the changed row or column will be printed after a move. type 'show' to see the board"
+                } else {
+                    "the board will be printed after every move"
+                });
+            }
+            BIR::Show => out.board_with_marks(&binox, Some(&marks)),
+            BIR::Tallies(on) => {
+                out.set_tallies(on);
+                out.board_with_marks(&binox, Some(&marks));
+            }
+            BIR::Edit(on) => {
+                editing = on;
+                out.info(if editing {
+                    "edit mode: symbols you place become clues. type 'play' when the puzzle is entered"
+                } else {
+                    "play mode: symbols you place can be changed"
+                });
+            }
+            BIR::Auto(on) => {
+                auto = on;
+                out.info(if auto {
+                    "cells forced by the simple rules will be filled automatically"
+                } else {
+                    "cells will no longer be filled automatically"
+                });
+            }
+            BIR::ShowMarks(shown) => {
+                marks.shown = shown.unwrap_or(!marks.shown);
+                out.board_with_marks(&binox, Some(&marks));
+            }
+            BIR::Undo(count) => {
+                if history.next_undo_is_automatic() {
+                    out.info("undoing cells filled automatically");
+                }
+                let (new_binox, steps) = history.undo(binox, count);
+                binox = new_binox;
+                marks.clear_filled(&binox);
+                out.set_recent(&before, &binox);
+                if steps == 0 {
+                    out.error("nothing to undo");
+                } else if quiet {
+                    out.changes(&before, &binox, Some(&marks));
+                } else {
+                    out.board_with_marks(&binox, Some(&marks));
+                }
+            }
+            BIR::Redo(count) => {
+                let (new_binox, steps) = history.redo(binox, count);
+                binox = new_binox;
+                marks.clear_filled(&binox);
+                out.set_recent(&before, &binox);
+                if steps == 0 {
+                    out.error("nothing to redo");
+                } else if quiet {
+                    out.changes(&before, &binox, Some(&marks));
+                } else {
+                    out.board_with_marks(&binox, Some(&marks));
                 }
             }
             BIR::Exit => {
-                println!("{}", "Exiting the program".yellow().bold());
+                store_progress(&mut puzzles, &binox, &marks, &timers);
+                if autosaving {
+                    if let Err(text) = autosave.save(&puzzles) {
+                        out.error(&text);
+                    }
+                }
+                out.warning("Exiting the program");
                 break;
             }
             BIR::Next => {
-                puzzles[selected_puzzle] = binox.as_string();
-                selected_puzzle = if selected_puzzle >= puzzles.len() - 1 {
-                    0
-                } else {
-                    selected_puzzle + 1
-                };
-                binox = Binox::new_from_string(puzzles[selected_puzzle].clone());
-                println!("{}", binox);
+                store_progress(&mut puzzles, &binox, &marks, &timers);
+                binox = Binox::new_from_string(puzzles.next_puzzle().puzzle.clone());
+                timers.select(puzzles.selected());
+                history.clear();
+                marks.restore(binox.size(), puzzles.current().marks.as_deref());
+                out.set_title(puzzles.current().title.as_deref());
+                out.board_with_marks(&binox, Some(&marks));
             }
             BIR::Previous => {
-                puzzles[selected_puzzle] = binox.as_string();
-                selected_puzzle = if selected_puzzle == 0 {
-                    puzzles.len() - 1
-                } else {
-                    selected_puzzle - 1
+                store_progress(&mut puzzles, &binox, &marks, &timers);
+                binox = Binox::new_from_string(puzzles.previous_puzzle().puzzle.clone());
+                timers.select(puzzles.selected());
+                history.clear();
+                marks.restore(binox.size(), puzzles.current().marks.as_deref());
+                out.set_title(puzzles.current().title.as_deref());
+                out.board_with_marks(&binox, Some(&marks));
+            }
+            BIR::Goto(index) => {
+                store_progress(&mut puzzles, &binox, &marks, &timers);
+                match puzzles.select(index) {
+                    Ok(entry) => {
+                        binox = Binox::new_from_string(entry.puzzle.clone());
+                        timers.select(puzzles.selected());
+                        history.clear();
+                        marks.restore(binox.size(), puzzles.current().marks.as_deref());
+                        out.set_title(puzzles.current().title.as_deref());
+                        out.board_with_marks(&binox, Some(&marks));
+                    }
+                    Err(text) => out.error(text),
+                }
+            }
+            BIR::Save(filename) => {
+                store_progress(&mut puzzles, &binox, &marks, &timers);
+                let filename = match filename.or_else(|| source.clone()) {
+                    Some(filename) if filename.contains('.') => filename,
+                    Some(filename) => format!("{filename}.{SET_EXTENSION}"),
+                    None => format!("{}.{SET_EXTENSION}", puzzles.name),
                 };
-                binox = Binox::new_from_string(puzzles[selected_puzzle].clone());
-                println!("{}", binox);
+                match puzzles.write(&filename) {
+                    Ok(()) => {
+                        out.success(&tr_with(
+                            "saved {count} puzzles to {file}",
+                            &[("count", &puzzles.len().to_string()), ("file", &filename)],
+                        ));
+                        source = Some(filename);
+                    }
+                    Err(text) => out.error(&text),
+                }
+            }
+            BIR::Add => {
+                store_progress(&mut puzzles, &binox, &marks, &timers);
+                let index = puzzles.add(PuzzleEntry::new(binox.as_string()));
+                out.success(&tr_with(
+                    "added the board as puzzle {number}",
+                    &[("number", &index.to_string())],
+                ));
+            }
+            BIR::Remove(index) => {
+                store_progress(&mut puzzles, &binox, &marks, &timers);
+                let index = index.unwrap_or(puzzles.selected());
+                let was_current = index == puzzles.selected();
+                match puzzles.remove(index) {
+                    Ok(_) => {
+                        timers.remove(index, puzzles.selected());
+                        hints = shift_down(hints, index);
+                        out.info(&tr_with(
+                            "removed puzzle {number}",
+                            &[("number", &index.to_string())],
+                        ));
+                        if was_current {
+                            binox = Binox::new_from_string(puzzles.current().puzzle.clone());
+                            history.clear();
+                            marks.restore(binox.size(), puzzles.current().marks.as_deref());
+                            out.set_title(puzzles.current().title.as_deref());
+                            out.board_with_marks(&binox, Some(&marks));
+                        }
+                    }
+                    Err(text) => out.error(text),
+                }
+            }
+            BIR::Shuffle(seed) => {
+                let seed = seed.unwrap_or_else(rand::random);
+                shuffle_set(&mut puzzles, &binox, &marks, &mut timers, &mut hints, seed);
+                out.info(&tr_with(
+                    "shuffled the collection with seed {seed}; 'save' keeps the new order",
+                    &[("seed", &seed.to_string())],
+                ));
+            }
+            BIR::Config => out.config(config, options.config_path.as_deref()),
+            BIR::Display(rendering) => {
+                out.set_rendering(rendering);
+                out.board_with_marks(&binox, Some(&marks));
+            }
+            BIR::List => {
+                store_progress(&mut puzzles, &binox, &marks, &timers);
+                out.puzzle_list(&puzzles, &completed, &stats);
             }
             BIR::Import(mut filename) => {
                 if !filename.contains('.') {
                     filename.push_str(".binox")
                 }
-                if let Ok(contents) = fs::read_to_string(filename.clone()) {
-                    let lines: Vec<&str> = contents.lines().collect::<Vec<&str>>();
-                    let lines: Vec<String> = lines.iter().map(|str| str.to_string()).collect();
-                    if lines.is_empty() {
-                        println!("file contains no puzzles");
-                    } else {
-                        puzzles = lines;
-                        selected_puzzle = 0;
-                        binox = Binox::new_from_string(puzzles[0].clone());
-                        println!("{}", binox);
+                let filename = config.find_puzzle_file(&filename);
+                let loaded = PuzzleSet::load_checked(&filename).map(|(mut set, problems)| {
+                    if !problems.is_empty() {
+                        for problem in &problems {
+                            out.warning(&problem.to_string());
+                        }
+                        // Without a player to ask, the puzzles are skipped.
+                        let mut answer = String::from("y");
+                        if interactive {
+                            out.warning("skip the puzzles with problems? (y/n)");
+                            answer.clear();
+                            input.read_line(&mut answer).expect("Failed to read input");
+                        }
+                        if answer.trim().to_lowercase().starts_with('y') {
+                            set.skip_problems(&problems);
+                        }
                     }
-                } else {
-                    println!("{} {}", "file not found:".red().bold(), filename);
-                };
+                    set
+                });
+                match loaded {
+                    Ok(set) if set.is_empty() => out.error("file contains no puzzles"),
+                    Ok(set) => {
+                        puzzles = set;
+                        source = Some(filename);
+                        binox = Binox::new_from_string(puzzles.current().puzzle.clone());
+                        restore_times(&mut timers, &puzzles);
+                        hints.clear();
+                        history.clear();
+                        marks.restore(binox.size(), puzzles.current().marks.as_deref());
+                        out.set_title(puzzles.current().title.as_deref());
+                        out.board_with_marks(&binox, Some(&marks));
+                    }
+                    Err(text) => out.error(&text),
+                }
+            }
+            BIR::ImportPack(filename, category) => {
+                let filename = config.find_puzzle_file(&pack_file_name(&filename));
+                match puzzle_pack::read_category(&filename, &category) {
+                    Ok(lines) if lines.is_empty() => out.error("category contains no puzzles"),
+                    Ok(lines) => {
+                        puzzles = PuzzleSet::from_lines(
+                            category.clone(),
+                            &lines.join("\n"),
+                            Difficulty::from_file_name(&category),
+                        );
+                        source = None;
+                        binox = Binox::new_from_string(puzzles.current().puzzle.clone());
+                        timers.reset(puzzles.selected());
+                        hints.clear();
+                        history.clear();
+                        marks.clear(binox.size());
+                        out.set_title(puzzles.current().title.as_deref());
+                        out.board_with_marks(&binox, Some(&marks));
+                    }
+                    Err(text) => out.error(&text),
+                }
+            }
+            BIR::Paste(lines) => {
+                puzzles = PuzzleSet::from_lines("clipboard".into(), &lines.join("\n"), None);
+                source = None;
+                binox = Binox::new_from_string(puzzles.current().puzzle.clone());
+                timers.reset(puzzles.selected());
+                hints.clear();
+                history.clear();
+                marks.clear(binox.size());
+                out.set_title(puzzles.current().title.as_deref());
+                out.board_with_marks(&binox, Some(&marks));
+            }
+            BIR::Error(text) => out.error(&text),
+        }
+        if !editing && binox.is_solved() && !timers.is_finished() {
+            let time = timers.finish().unwrap_or_default();
+            if !switching {
+                out.success(&tr_with(
+                    "congratulations! you solved the puzzle in {time}",
+                    &[("time", &format_duration(time))],
+                ));
+                out.info("rate the puzzle from 1 to 5 stars with 'stars 4', if you like");
+                let difficulty = puzzles.current().difficulty.unwrap_or_else(|| {
+                    let mut clues = binox.clone();
+                    clues.reset();
+                    clues.rate().difficulty
+                });
+                let taken = hints.get(&puzzles.selected()).copied().unwrap_or_default();
+                stats.record(Solve {
+                    size: binox.size(),
+                    difficulty: Some(difficulty),
+                    seconds: time.as_secs(),
+                    hints: taken.count,
+                    hint_points: taken.points,
+                    day: timer::today(),
+                });
+                if interactive {
+                    if let Err(text) = stats.save() {
+                        out.error(&text);
+                    }
+                }
+                if let Some(hook) = on_solve.as_mut() {
+                    match hook(&binox.as_string(), time) {
+                        Ok(Some(text)) => out.info(&text),
+                        Ok(None) => (),
+                        Err(text) => out.error(&text),
+                    }
+                }
+            }
+            if let Some((cleared, clues)) = &level {
+                if canonical(&binox.as_string()) == *clues && campaign.clear(*cleared) {
+                    out.success(&tr_with(
+                        "level {level} cleared!",
+                        &[("level", &cleared.to_string())],
+                    ));
+                    if let Some(next) = campaign.next() {
+                        out.info(&tr_with(
+                            "{level} is unlocked. type 'campaign play' to play it.",
+                            &[("level", &next.to_string())],
+                        ));
+                    }
+                    if interactive {
+                        if let Err(text) = campaign.save() {
+                            out.error(&text);
+                        }
+                    }
+                }
+            }
+            if completed.insert(&binox.as_string()) && interactive {
+                if let Err(text) = completed.save() {
+                    out.error(&text);
+                }
+            }
+        }
+        if autosaving && autosave.is_due() {
+            store_progress(&mut puzzles, &binox, &marks, &timers);
+            if let Err(text) = autosave.save(&puzzles) {
+                out.error(&text);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn cell_coordinates() {
+        assert_eq!(parse_cell(&["a3"]), Ok((3, 0)));
+        assert_eq!(parse_cell(&["C10"]), Ok((10, 2)));
+        assert_eq!(parse_cell(&["b", "3"]), Ok((3, 1)));
+        assert_eq!(parse_cell(&["3", "b"]), Ok((3, 1)));
+        assert_eq!(parse_cell(&["1", "3"]), Ok((3, 1)));
+        assert!(parse_cell(&["3"]).is_err());
+        assert!(parse_cell(&["ab3"]).is_err());
+        assert!(parse_cell(&["a", "b"]).is_err());
+        let binox = Binox::new(4).unwrap();
+        let (binox, _) = interpret(binox, "x b2".into());
+        let (binox, _) = interpret(binox, "o 2 b".into());
+        assert_eq!(binox.get_cell(2, 1).unwrap(), BinoxCell::O);
+        let (binox, _) = interpret(binox, "x 3 0".into());
+        assert_eq!(binox.get_cell(0, 3).unwrap(), BinoxCell::X);
+    }
+
+    #[test]
+    fn multiple_cells() {
+        assert_eq!(
+            parse_cells(&["0", "2-3,5"]),
+            Ok(vec![(0, 2), (0, 3), (0, 5)])
+        );
+        assert_eq!(parse_cells(&["row", "1", "a,c"]), Ok(vec![(1, 0), (1, 2)]));
+        assert_eq!(parse_cells(&["col", "b", "1-2"]), Ok(vec![(1, 1), (2, 1)]));
+        assert_eq!(parse_cells(&["a3"]), Ok(vec![(3, 0)]));
+        assert!(parse_cells(&["0", "3-1"]).is_err());
+        let binox = Binox::new_from_string("...X............".into());
+        let (binox, result) = interpret(binox, "o 0 0-2".into());
+        assert!(matches!(result, BIR::Normal(true)));
+        assert_eq!(binox.as_string(), "oooX............");
+        let (binox, result) = interpret(binox, "x 0 0-3".into());
+        assert!(matches!(result, BIR::Error(_)));
+        assert_eq!(binox.as_string(), "oooX............");
+    }
+
+    #[test]
+    fn hints() {
+        let binox = Binox::new_from_string("XX..............".into());
+        let (binox, result) = interpret(binox, "hint".into());
+        assert!(matches!(result, BIR::Normal(false)));
+        let (binox, result) = interpret(binox, "hint 3".into());
+        assert!(matches!(result, BIR::Normal(true)));
+        assert_eq!(binox.as_string(), "XXo.............");
+        let (binox, result) = interpret(binox, "hint 4".into());
+        assert!(matches!(result, BIR::Error(_)));
+        let (binox, result) = interpret(binox, "step".into());
+        assert!(matches!(result, BIR::Normal(true)));
+        assert_eq!(binox.as_string(), "XXoo............");
+        assert_eq!(requested_hint_cost("hint"), Some(1));
+        assert_eq!(requested_hint_cost("HINT cell"), Some(2));
+        assert_eq!(requested_hint_cost("step"), Some(3));
+        assert_eq!(requested_hint_cost("hint 4"), None);
+        assert_eq!(requested_hint_cost("x a1"), None);
+    }
+
+    /// What each command parses to, without running it.
+    #[test]
+    fn command_parsing() {
+        type Expected = fn(&BIR) -> bool;
+        let cases: &[(&str, Expected)] = &[
+            ("generate 6 seed", |r| matches!(r, BIR::Error(_))),
+            (
+                "campaign play 6x6 hard",
+                |r| matches!(r, BIR::PlayLevel(Some(l)) if l.size == 6 && l.difficulty == Difficulty::Hard),
+            ),
+            (
+                "campaign play 8 e",
+                |r| matches!(r, BIR::PlayLevel(Some(l)) if l.size == 8),
+            ),
+            ("campaign play", |r| matches!(r, BIR::PlayLevel(None))),
+            ("campaign play 5 easy", |r| matches!(r, BIR::Error(_))),
+            ("campaign", |r| matches!(r, BIR::Campaign)),
+            (
+                "meta source Puzzle Monthly 12",
+                |r| matches!(r, BIR::Meta(Some((MetaField::Source, Some(v)))) if v == "Puzzle Monthly 12"),
+            ),
+            ("meta notes none", |r| {
+                matches!(r, BIR::Meta(Some((MetaField::Notes, None))))
+            }),
+            (
+                "meta created today",
+                |r| matches!(r, BIR::Meta(Some((MetaField::Created, Some(d)))) if d.len() == 10),
+            ),
+            ("meta colour red", |r| matches!(r, BIR::Error(_))),
+            ("meta", |r| matches!(r, BIR::Meta(None))),
+            ("shuffle", |r| matches!(r, BIR::Shuffle(None))),
+            ("shuffle seed 7", |r| matches!(r, BIR::Shuffle(Some(7)))),
+            (
+                "shuffle seed october",
+                |r| matches!(r, BIR::Shuffle(Some(s)) if *s == seed_from_str("october")),
+            ),
+            ("shuffle seed", |r| matches!(r, BIR::Error(_))),
+            ("stars 4", |r| matches!(r, BIR::Stars(4))),
+            ("stars 6", |r| matches!(r, BIR::Error(_))),
+            ("stars", |r| matches!(r, BIR::Error(_))),
+            (
+                "title Sunday  Special",
+                |r| matches!(r, BIR::Title(Some(Some(t))) if t == "Sunday  Special"),
+            ),
+            ("title none", |r| matches!(r, BIR::Title(Some(None)))),
+            ("title", |r| matches!(r, BIR::Title(None))),
+        ];
+        for &(command, expected) in cases {
+            let (_, result) = interpret(Binox::new(4).unwrap(), command.into());
+            assert!(expected(&result), "{command}");
+        }
+
+        let (binox, result) = interpret(Binox::new(4).unwrap(), "generate 6 seed 42 2".into());
+        let BIR::Generated(entry) = result else {
+            panic!("expected a generated puzzle");
+        };
+        assert_eq!(entry.seed, Some(42));
+        assert_eq!(entry.options.map(|o| o.extras), Some(2));
+        assert_eq!(binox.as_string(), entry.puzzle);
+        let (again, _) = interpret(binox, "generate 6 2 seed 42".into());
+        assert_eq!(again.as_string(), entry.puzzle);
+        let (_, result) = interpret(again, "generate 6 seed october".into());
+        let BIR::Generated(entry) = result else {
+            panic!("expected a generated puzzle");
+        };
+        assert_eq!(entry.seed, Some(seed_from_str("october")));
+
+        let mut puzzles = PuzzleSet::from_lines("set".into(), "X...............\n", None);
+        puzzles.set_title(Some(" Sunday\tSpecial "));
+        assert_eq!(puzzles.current().title.as_deref(), Some("Sunday Special"));
+    }
+
+    #[test]
+    fn shuffle_keeps_progress_with_puzzles() {
+        let lines = "X...............\n.X..............\n..X.............\n...X............\n";
+        let mut puzzles = PuzzleSet::from_lines("set".into(), lines, None);
+        let before: Vec<String> = puzzles.entries.iter().map(|e| e.puzzle.clone()).collect();
+        puzzles.select(2).unwrap();
+        let binox = Binox::new_from_string("..XO............".into());
+        let marks = Marks::new(4);
+        let mut timers = Timers::new(2);
+        let mut hints = HashMap::new();
+        for index in 0..4 {
+            if index != 2 {
+                timers.restore(index, Duration::from_secs(100 * index as u64));
+            }
+            let taken = HintsTaken {
+                count: index,
+                points: 10 * index,
+            };
+            hints.insert(index, taken);
+        }
+        shuffle_set(&mut puzzles, &binox, &marks, &mut timers, &mut hints, 7);
+
+        let selected = puzzles.selected();
+        assert_eq!(puzzles.current().puzzle, "..XO............");
+        assert_eq!(timers.elapsed().as_secs(), 0);
+        for index in 0..4 {
+            let old = (0..4)
+                .find(|&old| before[old] == puzzles.entries[index].puzzle)
+                .unwrap_or(2);
+            assert_eq!(hints[&index].count, old);
+            assert_eq!(hints[&index].points, 10 * old);
+            if index != selected {
+                timers.select(index);
+                assert_eq!(timers.elapsed().as_secs(), 100 * old as u64);
             }
-            BIR::Error(text) => println!("{}", text.red().bold()),
         }
+        assert_ne!(selected, 2, "seed 7 should move the selected puzzle");
     }
 }
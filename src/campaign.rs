@@ -0,0 +1,192 @@
+use std::collections::BTreeSet;
+use std::env;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use crate::binox::{Binox, Symmetry};
+use crate::config::Config;
+use crate::difficulty::Difficulty;
+use crate::make_files::DEFAULT_SIZES;
+use crate::puzzle_set::PuzzleSet;
+
+const CAMPAIGN_FILE_NAME: &str = ".binox_campaign";
+
+/// The campaign file lives in the home directory, or the working directory
+/// if no home directory is known.
+pub fn campaign_path() -> PathBuf {
+    match env::var_os("HOME").or_else(|| env::var_os("USERPROFILE")) {
+        Some(home) => PathBuf::from(home).join(CAMPAIGN_FILE_NAME),
+        None => PathBuf::from(CAMPAIGN_FILE_NAME),
+    }
+}
+
+/// One puzzle of the campaign.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Level {
+    pub size: u8,
+    pub difficulty: Difficulty,
+}
+
+impl Level {
+    /// Every level in the order they are played: each difficulty of 4x4,
+    /// then of 6x6, and so on up to 16x16 expert.
+    pub fn all() -> impl Iterator<Item = Level> {
+        DEFAULT_SIZES.into_iter().flat_map(|size| {
+            Difficulty::ALL
+                .into_iter()
+                .map(move |difficulty| Level { size, difficulty })
+        })
+    }
+
+    pub fn index(&self) -> usize {
+        Level::all()
+            .position(|level| level == *self)
+            .unwrap_or_default()
+    }
+
+    /// The name of the default file with puzzles for the level, as written
+    /// by `binox make-files`.
+    pub fn file_name(&self) -> String {
+        format!("{0}x{0}_{1}.binox", self.size, self.difficulty)
+    }
+
+    /// The level's puzzle: the first one of its default file if that can be
+    /// found, or else one generated from a seed that is the same for everyone.
+    pub fn puzzle(&self, config: &Config) -> Result<String, String> {
+        let path = config.find_puzzle_file(&self.file_name());
+        if Path::new(&path).exists() {
+            if let Some(entry) = PuzzleSet::load(&path)?.entries.first() {
+                return Ok(entry.puzzle.clone());
+            }
+        }
+        let mut rng = StdRng::seed_from_u64(self.index() as u64);
+        let (puzzle, _) = Binox::generate_with_rng(
+            self.size,
+            true,
+            self.difficulty.extras(self.size),
+            Symmetry::None,
+            &mut rng,
+        )?;
+        Ok(puzzle.as_string())
+    }
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{0}x{0} {1}", self.size, self.difficulty)
+    }
+}
+
+/// The levels that have been cleared, one per line as in `6x6_hard`. Each
+/// level is unlocked by clearing the one before it.
+#[derive(Clone, Debug, Default)]
+pub struct Campaign {
+    cleared: BTreeSet<String>,
+}
+
+fn key(level: Level) -> String {
+    format!("{0}x{0}_{1}", level.size, level.difficulty)
+}
+
+impl Campaign {
+    pub fn parse(contents: &str) -> Self {
+        Campaign {
+            cleared: contents
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .map(String::from)
+                .collect(),
+        }
+    }
+
+    /// Reads the campaign file. A missing or unreadable file means no level
+    /// has been cleared yet.
+    pub fn load() -> Self {
+        fs::read_to_string(campaign_path())
+            .map(|contents| Campaign::parse(&contents))
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let path = campaign_path();
+        fs::write(&path, self.to_string())
+            .map_err(|_| format!("could not write campaign file {}", path.display()))
+    }
+
+    pub fn is_cleared(&self, level: Level) -> bool {
+        self.cleared.contains(&key(level))
+    }
+
+    pub fn is_unlocked(&self, level: Level) -> bool {
+        match level.index().checked_sub(1) {
+            None => true,
+            Some(before) => Level::all()
+                .nth(before)
+                .is_some_and(|before| self.is_cleared(before)),
+        }
+    }
+
+    /// Records a level as cleared. Returns false if it already was.
+    pub fn clear(&mut self, level: Level) -> bool {
+        self.cleared.insert(key(level))
+    }
+
+    /// The first level that has not been cleared, or `None` once every level
+    /// has been.
+    pub fn next(&self) -> Option<Level> {
+        Level::all().find(|&level| !self.is_cleared(level))
+    }
+
+    pub fn cleared(&self) -> usize {
+        Level::all().filter(|&level| self.is_cleared(level)).count()
+    }
+}
+
+impl fmt::Display for Campaign {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for level in &self.cleared {
+            writeln!(f, "{level}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn progression() {
+        let levels: Vec<Level> = Level::all().collect();
+        assert_eq!(levels.len(), 28);
+        assert_eq!(levels[0].to_string(), "4x4 easy");
+        assert_eq!(levels[27].to_string(), "16x16 expert");
+        assert_eq!(levels[5].index(), 5);
+        assert_eq!(levels[5].file_name(), "6x6_medium.binox");
+
+        let mut campaign = Campaign::parse("4x4_easy\n\n4x4_medium\n");
+        assert_eq!(campaign.cleared(), 2);
+        assert!(campaign.is_unlocked(levels[2]));
+        assert!(!campaign.is_unlocked(levels[3]));
+        assert_eq!(campaign.next(), Some(levels[2]));
+        assert!(campaign.clear(levels[2]));
+        assert!(!campaign.clear(levels[2]));
+        assert!(campaign.is_unlocked(levels[3]));
+        let reloaded = Campaign::parse(&campaign.to_string());
+        assert_eq!(reloaded.cleared(), 3);
+
+        let config = Config::default();
+        let level = Level {
+            size: 6,
+            difficulty: Difficulty::Hard,
+        };
+        let puzzle = level.puzzle(&config).unwrap();
+        assert_eq!(puzzle, level.puzzle(&config).unwrap());
+        assert!(Binox::new_from_string(puzzle).unique_solution().is_some());
+    }
+}
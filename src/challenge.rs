@@ -0,0 +1,411 @@
+use std::io::{self, Write};
+use std::sync::mpsc::RecvTimeoutError;
+use std::time::{Duration, Instant};
+
+use rand::rngs::StdRng;
+
+use crate::binox::{column_name, Binox, BinoxCell, Symmetry};
+use crate::binox_interpreter::{hint_cost, hint_level, parse_cell, stdin_lines};
+use crate::difficulty::Difficulty;
+use crate::puzzle::Puzzle;
+use crate::stats::{ChallengeRun, Solve, Stats};
+use crate::theme::current_theme;
+use crate::timer::{self, format_duration};
+
+/// Points lost for each hint point spent.
+const HINT_POINT_PENALTY: u64 = 10;
+const MISTAKE_PENALTY: u64 = 50;
+
+/// The points for solving a puzzle: ten a cell, times 1 for easy up to 4 for
+/// expert, and one more for every second left on the clock, less 10 for each
+/// hint point spent and 50 for each mistake. Never less than 0.
+pub fn points(
+    size: u8,
+    difficulty: Difficulty,
+    remaining: Duration,
+    hint_points: usize,
+    mistakes: usize,
+) -> u64 {
+    let level = Difficulty::ALL
+        .iter()
+        .position(|&d| d == difficulty)
+        .unwrap_or_default() as u64
+        + 1;
+    let earned = 10 * (size as u64).pow(2) * level + remaining.as_secs();
+    earned
+        .saturating_sub(HINT_POINT_PENALTY * hint_points as u64 + MISTAKE_PENALTY * mistakes as u64)
+}
+
+/// What a move in a challenge led to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Move {
+    Placed,
+    /// A hint showed where to look, and nothing was placed.
+    Hinted,
+    /// The symbol was not the solution's, and was not placed.
+    Mistake,
+    /// The move finished the puzzle, and the next one has begun.
+    Solved {
+        points: u64,
+        hints: usize,
+        hint_points: usize,
+    },
+}
+
+/// A run of generated puzzles of one size and difficulty, solved one after
+/// another for points. The clock is kept by the caller, which passes the
+/// time left to each move.
+pub struct Challenge {
+    size: u8,
+    difficulty: Difficulty,
+    rng: StdRng,
+    /// Hint points available on each puzzle, or `None` for no limit.
+    hint_budget: Option<usize>,
+    board: Binox,
+    solution: Binox,
+    /// Hints, their cost and mistakes on the current puzzle.
+    hints: usize,
+    hint_points: usize,
+    mistakes: usize,
+    total_hints: usize,
+    total_hint_points: usize,
+    total_mistakes: usize,
+    solved: usize,
+    score: u64,
+}
+
+impl Challenge {
+    pub fn new(
+        size: u8,
+        difficulty: Difficulty,
+        hint_budget: Option<usize>,
+        mut rng: StdRng,
+    ) -> Result<Self, &'static str> {
+        let (board, solution) = Challenge::puzzle(size, difficulty, &mut rng)?;
+        Ok(Challenge {
+            size,
+            difficulty,
+            rng,
+            hint_budget,
+            board,
+            solution,
+            hints: 0,
+            hint_points: 0,
+            mistakes: 0,
+            total_hints: 0,
+            total_hint_points: 0,
+            total_mistakes: 0,
+            solved: 0,
+            score: 0,
+        })
+    }
+
+    fn puzzle(
+        size: u8,
+        difficulty: Difficulty,
+        rng: &mut StdRng,
+    ) -> Result<(Binox, Binox), &'static str> {
+        Binox::generate_with_rng(size, true, difficulty.extras(size), Symmetry::None, rng)
+    }
+
+    pub fn board(&self) -> &Binox {
+        &self.board
+    }
+
+    pub fn score(&self) -> u64 {
+        self.score
+    }
+
+    pub fn solved(&self) -> usize {
+        self.solved
+    }
+
+    /// Places a symbol if it is the solution's, and counts a mistake if not.
+    /// Moves that cannot be made at all, such as on a filled cell, are errors
+    /// and cost nothing.
+    pub fn place(
+        &mut self,
+        row: u8,
+        col: u8,
+        cell: BinoxCell,
+        remaining: Duration,
+    ) -> Result<Move, String> {
+        if cell == BinoxCell::EMPTY {
+            return Err("only x and o can be placed".into());
+        }
+        if self.board.get_cell(row, col)? != BinoxCell::EMPTY {
+            return Err(format!("{}{row} is already filled", column_name(col)));
+        }
+        if self.solution.get_cell(row, col)? != cell {
+            self.mistakes += 1;
+            self.total_mistakes += 1;
+            return Ok(Move::Mistake);
+        }
+        self.board.set_cell(row, col, cell)?;
+        self.finish_move(remaining).map_err(String::from)
+    }
+
+    /// Finds the next cell that can be deduced, or any empty cell if none
+    /// can, and returns where it is. Only a hint of level 3 fills it.
+    pub fn hint(&mut self, level: u8, remaining: Duration) -> Result<((u8, u8), Move), String> {
+        let cost = hint_cost(level);
+        if let Some(budget) = self.hint_budget {
+            if self.hint_points + cost > budget {
+                return Err(format!(
+                    "this hint costs {cost}, but only {} hint points are left on this puzzle",
+                    budget - self.hint_points
+                ));
+            }
+        }
+        let (row, col) = match self.board.next_deduction() {
+            Some(deduction) => (deduction.row, deduction.col),
+            None => *self
+                .board
+                .empty_cells()
+                .first()
+                .ok_or("the puzzle is already solved")?,
+        };
+        self.hints += 1;
+        self.total_hints += 1;
+        self.hint_points += cost;
+        self.total_hint_points += cost;
+        if level < 3 {
+            return Ok(((row, col), Move::Hinted));
+        }
+        self.board
+            .set_cell(row, col, self.solution.get_cell(row, col)?)?;
+        Ok(((row, col), self.finish_move(remaining)?))
+    }
+
+    /// Replaces the puzzle with a new one, for no points.
+    pub fn skip(&mut self) -> Result<(), &'static str> {
+        (self.board, self.solution) = Challenge::puzzle(self.size, self.difficulty, &mut self.rng)?;
+        self.hints = 0;
+        self.hint_points = 0;
+        self.mistakes = 0;
+        Ok(())
+    }
+
+    fn finish_move(&mut self, remaining: Duration) -> Result<Move, &'static str> {
+        if !self.board.is_full() {
+            return Ok(Move::Placed);
+        }
+        let points = points(
+            self.size,
+            self.difficulty,
+            remaining,
+            self.hint_points,
+            self.mistakes,
+        );
+        let (hints, hint_points) = (self.hints, self.hint_points);
+        self.score += points;
+        self.solved += 1;
+        self.skip()?;
+        Ok(Move::Solved {
+            points,
+            hints,
+            hint_points,
+        })
+    }
+
+    /// The run as kept in the stats file.
+    pub fn run(&self, limit: Duration, day: u64) -> ChallengeRun {
+        ChallengeRun {
+            size: self.size,
+            difficulty: self.difficulty,
+            seconds: limit.as_secs(),
+            solved: self.solved,
+            hints: self.total_hints,
+            hint_points: self.total_hint_points,
+            mistakes: self.total_mistakes,
+            score: self.score,
+            day,
+        }
+    }
+}
+
+/// Plays a challenge on this terminal until the clock runs out or the player
+/// quits, and records it in the stats file.
+pub fn run_challenge(
+    size: u8,
+    difficulty: Difficulty,
+    limit: Duration,
+    hint_budget: Option<usize>,
+    rng: StdRng,
+) -> Result<(), String> {
+    let mut challenge = Challenge::new(size, difficulty, hint_budget, rng)?;
+    let mut stats = Stats::load();
+    println!(
+        "solve as many puzzles as you can in {}. place symbols with `x a3`, or type hint, skip or quit.",
+        format_duration(limit)
+    );
+    println!("{}", challenge.board());
+    let lines = stdin_lines();
+    let deadline = Instant::now() + limit;
+    let mut puzzle_started = Instant::now();
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            println!("time is up!");
+            break;
+        }
+        print!(
+            "{} left, {} points> ",
+            format_duration(remaining),
+            challenge.score()
+        );
+        io::stdout().flush().map_err(|e| e.to_string())?;
+        let line = match lines.recv_timeout(remaining) {
+            Ok(line) => line,
+            Err(RecvTimeoutError::Timeout) => {
+                println!();
+                continue;
+            }
+            Err(RecvTimeoutError::Disconnected) => break,
+        };
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let result = match words.first().map(|w| w.to_lowercase()).as_deref() {
+            None => continue,
+            Some("quit" | "exit") => break,
+            Some("board") => {
+                println!("{}", challenge.board());
+                continue;
+            }
+            Some("skip") => {
+                challenge.skip()?;
+                puzzle_started = Instant::now();
+                println!("{}", challenge.board());
+                continue;
+            }
+            Some("hint") => hint_level(words.get(1).copied()).and_then(|level| {
+                let ((row, col), result) = challenge.hint(level, remaining)?;
+                let cell = format!("{}{row}", column_name(col));
+                let penalty = HINT_POINT_PENALTY * hint_cost(level) as u64;
+                match level {
+                    1 => println!("look at row {row} (-{penalty} points)"),
+                    2 => println!("look at cell {cell} (-{penalty} points)"),
+                    _ => println!("{cell} filled (-{penalty} points)"),
+                }
+                Ok(result)
+            }),
+            Some(word) => match current_theme().cell_for(word) {
+                Some(cell) => parse_cell(&words[1..])
+                    .and_then(|(row, col)| challenge.place(row, col, cell, remaining)),
+                None => Err("place a symbol with `x a3`, or type hint, skip or quit".into()),
+            },
+        };
+        match result {
+            Err(text) => println!("{text}"),
+            Ok(Move::Mistake) => println!("that is a mistake (-{MISTAKE_PENALTY} points)"),
+            Ok(Move::Hinted) => (),
+            Ok(Move::Placed) => println!("{}", challenge.board()),
+            Ok(Move::Solved {
+                points,
+                hints,
+                hint_points,
+            }) => {
+                println!("solved! +{points} points");
+                stats.record(Solve {
+                    size,
+                    difficulty: Some(difficulty),
+                    seconds: puzzle_started.elapsed().as_secs(),
+                    hints,
+                    hint_points,
+                    day: timer::today(),
+                });
+                puzzle_started = Instant::now();
+                println!("{}", challenge.board());
+            }
+        }
+    }
+    println!(
+        "{} solved for {} points",
+        challenge.solved(),
+        challenge.score()
+    );
+    let run = challenge.run(limit, timer::today());
+    if stats
+        .best_challenge()
+        .is_none_or(|best| run.score > best.score)
+        && run.score > 0
+    {
+        println!("that is your best challenge yet!");
+    }
+    stats.record_challenge(run);
+    stats.save()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::SeedableRng;
+
+    #[test]
+    fn scoring() {
+        assert_eq!(
+            points(6, Difficulty::Easy, Duration::from_secs(100), 0, 0),
+            460
+        );
+        assert_eq!(
+            points(8, Difficulty::Expert, Duration::ZERO, 2, 1),
+            2560 - 70
+        );
+        assert_eq!(points(4, Difficulty::Easy, Duration::ZERO, 9, 9), 0);
+
+        let mut challenge =
+            Challenge::new(6, Difficulty::Easy, Some(4), StdRng::seed_from_u64(1)).unwrap();
+        let first = challenge.board().clone();
+        let solution = challenge.solution.clone();
+        let empty = challenge.board().empty_cells();
+        let (row, col) = empty[0];
+        let wrong = match solution.get_cell(row, col).unwrap() {
+            BinoxCell::X => BinoxCell::O,
+            _ => BinoxCell::X,
+        };
+        let time = Duration::from_secs(10);
+        assert_eq!(challenge.place(row, col, wrong, time), Ok(Move::Mistake));
+        assert!(challenge.place(row, col, BinoxCell::EMPTY, time).is_err());
+        let (nudged, result) = challenge.hint(1, time).unwrap();
+        assert_eq!(result, Move::Hinted);
+        assert_eq!(
+            challenge.board().get_cell(nudged.0, nudged.1),
+            Ok(BinoxCell::EMPTY)
+        );
+        let ((row, col), result) = challenge.hint(3, time).unwrap();
+        assert_eq!((row, col), nudged);
+        assert_eq!(result, Move::Placed);
+        assert!(challenge.hint(1, time).is_err());
+        assert_eq!(
+            challenge.board().get_cell(row, col),
+            solution.get_cell(row, col)
+        );
+        assert!(challenge.place(row, col, wrong, time).is_err());
+
+        let mut last = None;
+        for (row, col) in challenge.board().empty_cells() {
+            last = Some(
+                challenge
+                    .place(row, col, solution.get_cell(row, col).unwrap(), time)
+                    .unwrap(),
+            );
+        }
+        let points = points(6, Difficulty::Easy, time, 4, 1);
+        assert_eq!(
+            last,
+            Some(Move::Solved {
+                points,
+                hints: 2,
+                hint_points: 4
+            })
+        );
+        assert_eq!(challenge.score(), points);
+        assert_eq!(challenge.solved(), 1);
+        assert_ne!(challenge.board().as_string(), first.as_string());
+
+        let run = challenge.run(Duration::from_secs(300), 7);
+        assert_eq!((run.seconds, run.hints, run.mistakes), (300, 2, 1));
+        assert_eq!(run.hint_points, 4);
+        assert_eq!(run.score, points);
+    }
+}
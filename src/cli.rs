@@ -0,0 +1,707 @@
+use std::ffi::OsStr;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::Duration;
+
+use clap::builder::{PossibleValue, StringValueParser, TypedValueParser};
+use clap::error::ErrorKind;
+use clap::{Arg, CommandFactory, Parser, Subcommand};
+use clap_complete::Shell;
+
+use binox::autosave;
+use binox::batch::{self, SolveOutcome};
+use binox::bench::{self, BenchOptions};
+use binox::binox::{seed_from_str, Binox, Symmetry};
+use binox::binox_interpreter::{run_interpreter_with, InterpreterOptions, SolveHook};
+use binox::config::Config;
+use binox::daily::{self, DailyPuzzle};
+use binox::difficulty::Difficulty;
+use binox::formats::{self, Format};
+use binox::make_files::{create_default_files, create_default_set_files};
+use binox::output::OutputMode;
+use binox::puzzle_set::{GenerateOptions, PuzzleEntry, PuzzleSet, SET_EXTENSION};
+use binox::{challenge, duel, locale, metrics, race, rpc, server, theme, timer, tui};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+#[derive(Parser)]
+#[command(
+    name = "binox",
+    version,
+    about = "Play, solve and generate Binox puzzles"
+)]
+pub struct Cli {
+    /// Configuration file to use instead of `~/.config/binox/config.toml`.
+    #[arg(long, global = true)]
+    pub config: Option<String>,
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Starts the interactive interpreter (the default).
+    Play {
+        /// Puzzle file to open instead of generating a puzzle.
+        file: Option<String>,
+        /// Size of the puzzle generated at startup. Overrides the configuration.
+        #[arg(short, long)]
+        size: Option<u8>,
+        /// Do not offer to restore the previous session.
+        #[arg(long)]
+        no_recover: bool,
+        /// Read commands from a file instead of the terminal and exit at its end.
+        #[arg(long)]
+        script: Option<String>,
+        /// Write every response as a JSON object on its own line.
+        #[arg(long)]
+        json: bool,
+    },
+    /// Starts the full-screen interface with cursor navigation.
+    Tui {
+        /// Puzzle file to open instead of generating a puzzle.
+        file: Option<String>,
+        /// Size of the puzzle generated at startup. Overrides the configuration.
+        #[arg(short, long)]
+        size: Option<u8>,
+    },
+    /// Writes the default sample puzzle files to the working directory.
+    MakeFiles {
+        /// Write v2 `.binoxset` files that include answer keys.
+        #[arg(long)]
+        answers: bool,
+    },
+    /// Solves every puzzle in a file and prints the solutions, or "no solution"
+    /// and "ambiguous" for puzzles without exactly one solution. With `-` the
+    /// puzzles are read from stdin, one per line, as in
+    /// `echo "<puzzle>" | binox solve -`.
+    Solve {
+        /// Puzzle file to solve, or `-` for stdin.
+        file: String,
+        /// File to write the solutions to instead of stdout.
+        #[arg(short, long)]
+        output: Option<String>,
+    },
+    /// Writes an answer key for a puzzle file: the solved grids in the same
+    /// order, one per line. Puzzles without exactly one solution are flagged
+    /// with "ambiguous" or "no solution" in their place.
+    Answers {
+        /// Puzzle file to write the answer key for.
+        file: String,
+        /// File to write the answer key to. Defaults to `pack_answers.binox`
+        /// next to `pack.binox`.
+        #[arg(short, long)]
+        output: Option<String>,
+        /// Write nothing if any puzzle does not have exactly one solution.
+        #[arg(long)]
+        strict: bool,
+    },
+    /// Checks every puzzle in a file for well-formedness, rule consistency and a
+    /// unique solution. Exits with a failure code if any puzzle is invalid.
+    Validate {
+        /// Puzzle file to check.
+        file: String,
+    },
+    /// Converts puzzle files between the legacy, v2, JSON, CSV and binary formats.
+    /// If the input is a directory, every puzzle file in it is converted.
+    Convert {
+        /// Input file or directory.
+        input: String,
+        /// Output file or directory.
+        output: String,
+        /// Format of the input. Guessed from the file extension if not given.
+        #[arg(long)]
+        from: Option<Format>,
+        /// Format of the output. Guessed from the file extension if not given.
+        #[arg(long)]
+        to: Option<Format>,
+    },
+    /// Grades the difficulty of every puzzle in a file.
+    Rate {
+        /// Puzzle file to rate.
+        file: String,
+        /// Store the grades in the file. Legacy puzzle files are saved as a
+        /// `.binoxset` file next to the original.
+        #[arg(short, long)]
+        annotate: bool,
+    },
+    /// Grades every puzzle in the files and rewrites each file ordered from
+    /// easiest to hardest.
+    Sort {
+        /// Puzzle files to sort.
+        #[arg(required = true)]
+        files: Vec<String>,
+        /// Write one file per difficulty next to each file, such as
+        /// `pack_easy.binox` for `pack.binox`, instead of rewriting it.
+        #[arg(long)]
+        split: bool,
+    },
+    /// Writes a CSV or JSON report with the clues, grade, techniques, solver
+    /// nodes and generation seed of every puzzle in a file.
+    Metrics {
+        /// Puzzle file to measure.
+        file: String,
+        /// Write JSON instead of CSV.
+        #[arg(long)]
+        json: bool,
+        /// File to write the report to instead of stdout.
+        #[arg(short, long)]
+        output: Option<String>,
+    },
+    /// Generates puzzles and prints them or writes them to a file.
+    Generate {
+        /// Size of the puzzles.
+        size: u8,
+        /// Number of puzzles to generate.
+        #[arg(short = 'n', long, default_value_t = 1)]
+        count: u32,
+        /// Difficulty preset. Implies --perfect.
+        #[arg(short, long, value_parser = DifficultyParser)]
+        difficulty: Option<Difficulty>,
+        /// Remove every unnecessary clue.
+        #[arg(short, long)]
+        perfect: bool,
+        /// Number of extra clues to add. Overrides the difficulty preset.
+        #[arg(short, long)]
+        extras: Option<usize>,
+        /// Placement of the clues (none, rotational, mirror).
+        #[arg(long, default_value = "none")]
+        symmetry: Symmetry,
+        /// Seed of the first puzzle, a number or any text such as
+        /// `october-challenge`; the ones after it take the following seeds.
+        /// Every puzzle's seed is kept in a `.binoxset` file and shown by
+        /// `info` in the interpreter.
+        #[arg(long, value_parser = parse_seed)]
+        seed: Option<u64>,
+        /// File to write the puzzles to. A `.binoxset` file also keeps the answer keys and seeds.
+        #[arg(short, long)]
+        output: Option<String>,
+    },
+    /// Serves puzzles over HTTP: `GET /puzzle?size=&difficulty=&seed=`,
+    /// `GET /daily?size=&difficulty=`, `POST /verify` and `POST /hint` with a
+    /// `{"grid": "..."}` body, and `POST /daily/time` for solved daily puzzles.
+    Serve {
+        /// Address and port to listen on.
+        #[arg(long, default_value = "127.0.0.1:8080")]
+        address: String,
+    },
+    /// Speaks JSON-RPC 2.0 on stdin and stdout, one message per line, so a
+    /// graphical frontend can drive the engine as a child process.
+    Rpc,
+    /// Races other players on the same puzzle over WebSockets.
+    Race {
+        #[command(subcommand)]
+        action: RaceCommand,
+    },
+    /// Plays the day's puzzle, which is the same for everyone. With a server,
+    /// the puzzle comes from it and the time is sent back once it is solved.
+    Daily {
+        /// Server to download the puzzle from, such as
+        /// `http://127.0.0.1:8080`. Defaults to the configured `daily_server`.
+        #[arg(long)]
+        remote: Option<String>,
+        /// Size of the puzzle. Defaults to the configured size.
+        #[arg(short, long)]
+        size: Option<u8>,
+        /// Difficulty preset. Defaults to the configured difficulty.
+        #[arg(short, long, value_parser = DifficultyParser)]
+        difficulty: Option<Difficulty>,
+    },
+    /// Solves as many generated puzzles as possible before the clock runs
+    /// out, for points, and records the run in the stats file.
+    Challenge {
+        /// Size of the puzzles. Defaults to the configured size.
+        #[arg(short, long)]
+        size: Option<u8>,
+        /// Difficulty preset. Defaults to the configured difficulty, or medium.
+        #[arg(short, long, value_parser = DifficultyParser)]
+        difficulty: Option<Difficulty>,
+        /// Length of the clock in minutes.
+        #[arg(short, long, default_value_t = 5)]
+        minutes: u64,
+        /// Seed for the random number generator, a number or any text.
+        #[arg(long, value_parser = parse_seed)]
+        seed: Option<u64>,
+    },
+    /// Two players on this terminal take turns placing symbols on one
+    /// puzzle. Whoever breaks a rule, or has no move left, loses.
+    Duel {
+        /// Size of the puzzle. Defaults to the configured size.
+        #[arg(short, long)]
+        size: Option<u8>,
+        /// Difficulty preset.
+        #[arg(short, long, value_parser = DifficultyParser)]
+        difficulty: Option<Difficulty>,
+        /// Names of the two players, separated by a comma.
+        #[arg(long, value_delimiter = ',', default_value = "player 1,player 2")]
+        players: Vec<String>,
+    },
+    /// Times generating, presolving and solving puzzles of each size and
+    /// difficulty, and prints a summary table.
+    Bench {
+        /// Sizes to time, separated by commas.
+        #[arg(short, long, value_delimiter = ',', default_value = "6,8,10")]
+        sizes: Vec<u8>,
+        /// Difficulty presets to time, separated by commas. All of them if not given.
+        #[arg(short, long, value_delimiter = ',', value_parser = DifficultyParser)]
+        difficulty: Vec<Difficulty>,
+        /// Rounds to run before timing starts.
+        #[arg(long, default_value_t = 1)]
+        warmup: usize,
+        /// Timed rounds for each size and difficulty.
+        #[arg(short, long, default_value_t = 5)]
+        repetitions: usize,
+        /// Seed for the puzzles, so that runs time the same work.
+        #[arg(long, default_value_t = 0)]
+        seed: u64,
+    },
+    /// Prints a completion script for a shell, such as
+    /// `binox completions bash > /etc/bash_completion.d/binox`.
+    Completions {
+        /// Shell to complete in.
+        shell: Shell,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum RaceCommand {
+    /// Hosts a race. The puzzle is sent to every player at once when the last
+    /// one joins.
+    Host {
+        /// Address and port to listen on.
+        #[arg(long, default_value = "127.0.0.1:8081")]
+        address: String,
+        /// Number of players to wait for.
+        #[arg(short, long, default_value_t = 2)]
+        players: usize,
+        /// Size of the puzzle. Defaults to the configured size.
+        #[arg(short, long)]
+        size: Option<u8>,
+        /// Difficulty preset.
+        #[arg(short, long, value_parser = DifficultyParser)]
+        difficulty: Option<Difficulty>,
+        /// Seed for the random number generator, a number or any text.
+        #[arg(long, value_parser = parse_seed)]
+        seed: Option<u64>,
+    },
+    /// Joins a race, such as `binox race join ws://127.0.0.1:8081 --name ann`.
+    Join {
+        /// Address of the host.
+        url: String,
+        /// Name shown to the other players.
+        #[arg(short, long)]
+        name: String,
+    },
+}
+
+/// Reads a seed given as a number or as any other text.
+fn parse_seed(text: &str) -> Result<u64, String> {
+    Ok(seed_from_str(text))
+}
+
+/// Parses difficulty names and their one-letter abbreviations, and offers the
+/// full names to `--help` and shell completions.
+#[derive(Clone)]
+struct DifficultyParser;
+
+impl TypedValueParser for DifficultyParser {
+    type Value = Difficulty;
+
+    fn parse_ref(
+        &self,
+        cmd: &clap::Command,
+        arg: Option<&Arg>,
+        value: &OsStr,
+    ) -> Result<Difficulty, clap::Error> {
+        let value = StringValueParser::new().parse_ref(cmd, arg, value)?;
+        value.parse().map_err(|text: &str| {
+            clap::Error::raw(ErrorKind::InvalidValue, format!("{text}\n")).with_cmd(cmd)
+        })
+    }
+
+    fn possible_values(&self) -> Option<Box<dyn Iterator<Item = PossibleValue> + '_>> {
+        Some(Box::new(
+            Difficulty::ALL.iter().map(|d| PossibleValue::new(d.name())),
+        ))
+    }
+}
+
+pub fn run(cli: Cli) -> Result<(), String> {
+    let (config, config_path) = Config::load(cli.config.as_deref())?;
+    theme::init_color();
+    theme::set_theme(config.theme.clone());
+    if let Err(text) = locale::init(config.language.as_deref()) {
+        eprintln!("{text}");
+    }
+    match cli.command {
+        None => run_interpreter_with(InterpreterOptions {
+            config,
+            config_path,
+            ..InterpreterOptions::default()
+        }),
+        Some(Command::Play {
+            file,
+            size,
+            no_recover,
+            script,
+            json,
+        }) => run_interpreter_with(InterpreterOptions {
+            config: Config {
+                size: size.unwrap_or(config.size),
+                ..config
+            },
+            config_path,
+            file,
+            recover: !no_recover,
+            script,
+            output: if json {
+                OutputMode::Json
+            } else {
+                OutputMode::Text
+            },
+            ..InterpreterOptions::default()
+        }),
+        Some(Command::Tui { file, size }) => {
+            let puzzles = match file {
+                Some(file) => PuzzleSet::load(&config.find_puzzle_file(&file))?,
+                None => {
+                    let size = size.unwrap_or(config.size);
+                    let extras = config.difficulty.map_or(0, |d| d.extras(size));
+                    PuzzleSet::new(
+                        "session".into(),
+                        vec![PuzzleEntry::new(
+                            Binox::generate(size, true, extras)?.as_string(),
+                        )],
+                    )
+                }
+            };
+            autosave::save_recovery(&tui::run_tui(puzzles, config.keys)?)?;
+        }
+        Some(Command::MakeFiles { answers }) => {
+            if answers {
+                create_default_set_files()
+            } else {
+                create_default_files()
+            }
+        }
+        Some(Command::Solve { file, output }) => {
+            let outcomes = if file == "-" {
+                let input = io::read_to_string(io::stdin())
+                    .map_err(|_| "could not read puzzles from stdin")?;
+                batch::solve_text(&input)
+            } else {
+                let puzzles = PuzzleSet::load(&file)?;
+                batch::solve_set(&puzzles).into_iter().map(Ok).collect()
+            };
+            if outcomes.is_empty() {
+                return Err("no puzzles to solve".into());
+            }
+            let mut text = String::new();
+            for outcome in &outcomes {
+                match outcome {
+                    Ok(outcome) => text.push_str(&outcome.line()),
+                    Err(error) => text.push_str(&format!("invalid: {error}")),
+                }
+                text.push('\n');
+            }
+            match output {
+                Some(output) => {
+                    fs::write(&output, text).map_err(|_| format!("could not write {output}"))?
+                }
+                None => print!("{text}"),
+            }
+            let solved = outcomes
+                .iter()
+                .filter(|o| matches!(o, Ok(SolveOutcome::Solved(_))))
+                .count();
+            if solved < outcomes.len() {
+                return Err(format!(
+                    "{} of {} puzzles do not have exactly one solution",
+                    outcomes.len() - solved,
+                    outcomes.len()
+                ));
+            }
+        }
+        Some(Command::Answers {
+            file,
+            output,
+            strict,
+        }) => {
+            let puzzles = PuzzleSet::load(&file)?;
+            if puzzles.is_empty() {
+                return Err(format!("{file} has no puzzles"));
+            }
+            let (text, flagged) = batch::answer_key(&puzzles);
+            for i in &flagged {
+                eprintln!("puzzle {i}: {}", text.lines().nth(*i).unwrap_or_default());
+            }
+            if strict && !flagged.is_empty() {
+                return Err(format!(
+                    "{} of {} puzzles do not have exactly one solution; no answer key written",
+                    flagged.len(),
+                    puzzles.len()
+                ));
+            }
+            let output = output.unwrap_or_else(|| batch::answer_key_path(&file));
+            fs::write(&output, text).map_err(|_| format!("could not write {output}"))?;
+            println!("wrote {} answers to {output}", puzzles.len());
+            if !flagged.is_empty() {
+                return Err(format!(
+                    "{} of {} puzzles do not have exactly one solution",
+                    flagged.len(),
+                    puzzles.len()
+                ));
+            }
+        }
+        Some(Command::Validate { file }) => {
+            let puzzles = batch::numbered_puzzles(&file)?;
+            let mut failures = 0;
+            for (line, puzzle) in &puzzles {
+                if let Err(text) = batch::validate_puzzle(puzzle) {
+                    println!("{file}:{line}: {text}");
+                    failures += 1;
+                }
+            }
+            if failures > 0 {
+                return Err(format!(
+                    "{failures} of {} puzzles are invalid",
+                    puzzles.len()
+                ));
+            }
+            println!("all {} puzzles are valid", puzzles.len());
+        }
+        Some(Command::Convert {
+            input,
+            output,
+            from,
+            to,
+        }) => {
+            if Path::new(&input).is_dir() {
+                let to = to.ok_or("--to is required when converting a directory")?;
+                for file in formats::convert_directory(&input, &output, from, to)? {
+                    println!("{file}");
+                }
+            } else {
+                let count = formats::convert_file(&input, &output, from, to)?;
+                println!("converted {count} puzzles");
+            }
+        }
+        Some(Command::Rate { file, annotate }) => {
+            let mut puzzles = PuzzleSet::load(&file)?;
+            for (i, entry) in puzzles.entries.iter_mut().enumerate() {
+                let binox = entry.clues();
+                let rating = binox.rate();
+                let techniques: Vec<String> = rating
+                    .techniques
+                    .iter()
+                    .map(|(technique, count)| format!("{technique} x{count}"))
+                    .collect();
+                println!(
+                    "{i:>4}  {size}x{size}  {} clues  {:<6}  score {:.2}{}  {}",
+                    rating.clues,
+                    rating.difficulty,
+                    rating.score,
+                    if rating.solved_logically {
+                        ""
+                    } else {
+                        " (needs guessing)"
+                    },
+                    techniques.join(", "),
+                    size = binox.size(),
+                );
+                entry.difficulty = Some(rating.difficulty);
+            }
+            if annotate {
+                let output = match file.strip_suffix(".binox") {
+                    Some(stem) => format!("{stem}.{SET_EXTENSION}"),
+                    None => file,
+                };
+                puzzles.save(&output)?;
+                eprintln!("saved grades to {output}");
+            }
+        }
+        Some(Command::Sort { files, split }) => {
+            for file in files {
+                for (output, count) in batch::sort_file(&file, split)? {
+                    println!("{output}: {count} puzzles");
+                }
+            }
+        }
+        Some(Command::Metrics { file, json, output }) => {
+            let metrics = metrics::measure_set(&PuzzleSet::load(&file)?);
+            let text = if json {
+                metrics::to_json(&metrics)
+            } else {
+                metrics::to_csv(&metrics)
+            };
+            match output {
+                Some(output) => {
+                    fs::write(&output, text).map_err(|_| format!("could not write {output}"))?
+                }
+                None => print!("{text}"),
+            }
+        }
+        Some(Command::Generate {
+            size,
+            count,
+            difficulty,
+            perfect,
+            extras,
+            symmetry,
+            seed,
+            output,
+        }) => {
+            let options = GenerateOptions {
+                perfect: perfect || difficulty.is_some(),
+                extras: extras.unwrap_or(difficulty.map_or(0, |d| d.extras(size))),
+                symmetry,
+            };
+            // Each puzzle has a seed of its own, so that any one of them can
+            // be generated again on its own.
+            let first = seed.unwrap_or_else(rand::random);
+            let mut entries = Vec::new();
+            for i in 0..count {
+                let entry = PuzzleEntry::generate(size, first.wrapping_add(i.into()), options)?;
+                entries.push(PuzzleEntry {
+                    difficulty,
+                    ..entry
+                });
+                if output.is_some() {
+                    eprintln!("generated {} of {count}", i + 1);
+                }
+            }
+            let name = format!("{size}x{size}");
+            let puzzles = PuzzleSet::new(name, entries);
+            match output {
+                Some(output) => puzzles.write(&output)?,
+                None => print!("{}", puzzles.lines_string()),
+            }
+        }
+        Some(Command::Serve { address }) => server::serve(&address)?,
+        Some(Command::Rpc) => rpc::run_rpc()?,
+        Some(Command::Bench {
+            sizes,
+            difficulty,
+            warmup,
+            repetitions,
+            seed,
+        }) => {
+            if repetitions == 0 {
+                return Err("--repetitions must be at least 1".into());
+            }
+            let options = BenchOptions {
+                sizes,
+                difficulties: if difficulty.is_empty() {
+                    Difficulty::ALL.to_vec()
+                } else {
+                    difficulty
+                },
+                warmup,
+                repetitions,
+                seed,
+            };
+            let measurements = bench::run(&options, |size, difficulty| {
+                eprintln!("timing {size}x{size} {difficulty}")
+            })?;
+            print!("{}", bench::table(&measurements));
+        }
+        Some(Command::Daily {
+            remote,
+            size,
+            difficulty,
+        }) => {
+            let size = size.unwrap_or(config.size);
+            let difficulty = difficulty.or(config.difficulty);
+            let today = timer::today();
+            let server = remote.or_else(|| config.daily_server.clone());
+            let puzzle = match &server {
+                Some(server) => daily::fetch(server, size, difficulty, today)?,
+                None => DailyPuzzle::generate(size, difficulty, today)?,
+            };
+            let entry = PuzzleEntry {
+                difficulty,
+                ..PuzzleEntry::new(puzzle.puzzle.clone())
+            };
+            let on_solve = server.map(|server| -> SolveHook {
+                Box::new(move |grid, time| daily::submit(&server, &puzzle, grid, time))
+            });
+            run_interpreter_with(InterpreterOptions {
+                config,
+                config_path,
+                recover: false,
+                puzzles: Some(PuzzleSet::new(format!("daily {today}"), vec![entry])),
+                on_solve,
+                ..InterpreterOptions::default()
+            })
+        }
+        Some(Command::Challenge {
+            size,
+            difficulty,
+            minutes,
+            seed,
+        }) => {
+            if minutes == 0 {
+                return Err("the clock must run for at least a minute".into());
+            }
+            let rng = match seed {
+                Some(seed) => StdRng::seed_from_u64(seed),
+                None => StdRng::from_entropy(),
+            };
+            challenge::run_challenge(
+                size.unwrap_or(config.size),
+                difficulty
+                    .or(config.difficulty)
+                    .unwrap_or(Difficulty::Medium),
+                Duration::from_secs(60 * minutes),
+                config.hint_budget,
+                rng,
+            )?
+        }
+        Some(Command::Duel {
+            size,
+            difficulty,
+            players,
+        }) => {
+            let players: [String; 2] = players
+                .try_into()
+                .map_err(|_| "a duel needs exactly two players")?;
+            let size = size.unwrap_or(config.size);
+            let extras = difficulty.map_or(0, |d| d.extras(size));
+            let puzzle = Binox::generate(size, true, extras)?;
+            duel::run_duel(puzzle, players)?
+        }
+        Some(Command::Completions { shell }) => {
+            clap_complete::generate(shell, &mut Cli::command(), "binox", &mut io::stdout())
+        }
+        Some(Command::Race {
+            action:
+                RaceCommand::Host {
+                    address,
+                    players,
+                    size,
+                    difficulty,
+                    seed,
+                },
+        }) => {
+            let size = size.unwrap_or(config.size);
+            let extras = difficulty.map_or(0, |d| d.extras(size));
+            let mut rng = match seed {
+                Some(seed) => StdRng::seed_from_u64(seed),
+                None => StdRng::from_entropy(),
+            };
+            let (puzzle, _) = Binox::generate_with_rng(
+                size,
+                difficulty.is_some(),
+                extras,
+                Symmetry::None,
+                &mut rng,
+            )?;
+            race::host(&address, puzzle, players)?
+        }
+        Some(Command::Race {
+            action: RaceCommand::Join { url, name },
+        }) => race::join(&url, &name)?,
+    }
+    Ok(())
+}
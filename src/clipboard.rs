@@ -0,0 +1,53 @@
+use arboard::Clipboard;
+
+use crate::binox::Binox;
+
+fn open() -> Result<Clipboard, String> {
+    Clipboard::new().map_err(|e| format!("could not open the clipboard: {e}"))
+}
+
+/// Puts the text on the system clipboard.
+pub fn copy(text: &str) -> Result<(), String> {
+    open()?
+        .set_text(text)
+        .map_err(|e| format!("could not copy to the clipboard: {e}"))
+}
+
+/// The text on the system clipboard.
+pub fn paste() -> Result<String, String> {
+    open()?
+        .get_text()
+        .map_err(|e| format!("could not read the clipboard: {e}"))
+}
+
+/// The puzzles in pasted text, one per non-blank line. Fails unless every
+/// line is a puzzle, so that pasting a sentence by mistake changes nothing.
+pub fn puzzles(text: &str) -> Result<Vec<String>, String> {
+    let puzzles: Vec<String> = text
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| Binox::parse_strict(line).map(|_| line.to_string()))
+        .collect::<Result<_, _>>()
+        .map_err(|e| format!("the clipboard does not hold a puzzle: {e}"))?;
+    if puzzles.is_empty() {
+        return Err("the clipboard is empty".into());
+    }
+    Ok(puzzles)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn pasted_puzzles() {
+        let text = "XXO.O..X.O..X..O\r\n\n  ....OX.X........  \n";
+        assert_eq!(
+            puzzles(text),
+            Ok(vec!["XXO.O..X.O..X..O".into(), "....OX.X........".into()])
+        );
+        assert!(puzzles("hello there").is_err());
+        assert!(puzzles(" \n").is_err());
+    }
+}
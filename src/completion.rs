@@ -0,0 +1,102 @@
+use std::collections::BTreeSet;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::binox::Binox;
+
+const COMPLETION_FILE_NAME: &str = ".binox_completed";
+
+/// The completion file lives in the home directory, or the working directory
+/// if no home directory is known.
+pub fn completion_path() -> PathBuf {
+    match env::var_os("HOME").or_else(|| env::var_os("USERPROFILE")) {
+        Some(home) => PathBuf::from(home).join(COMPLETION_FILE_NAME),
+        None => PathBuf::from(COMPLETION_FILE_NAME),
+    }
+}
+
+/// The clues of a puzzle with any progress removed, so the same puzzle is
+/// recognized in every file and at every stage of solving.
+pub fn canonical(puzzle: &str) -> String {
+    let mut binox = Binox::new_from_string(puzzle.into());
+    binox.reset();
+    binox.as_string()
+}
+
+/// The puzzles that have been solved, one canonical form per line.
+#[derive(Clone, Debug, Default)]
+pub struct Completed {
+    puzzles: BTreeSet<String>,
+}
+
+impl Completed {
+    pub fn parse(contents: &str) -> Self {
+        Completed {
+            puzzles: contents
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .map(String::from)
+                .collect(),
+        }
+    }
+
+    /// Reads the completion file. A missing or unreadable file means nothing
+    /// has been solved yet.
+    pub fn load() -> Self {
+        fs::read_to_string(completion_path())
+            .map(|contents| Completed::parse(&contents))
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let path = completion_path();
+        fs::write(&path, self.to_string())
+            .map_err(|_| format!("could not write completion file {}", path.display()))
+    }
+
+    pub fn contains(&self, puzzle: &str) -> bool {
+        self.puzzles.contains(&canonical(puzzle))
+    }
+
+    /// Records a puzzle as solved. Returns false if it already was.
+    pub fn insert(&mut self, puzzle: &str) -> bool {
+        self.puzzles.insert(canonical(puzzle))
+    }
+
+    pub fn len(&self) -> usize {
+        self.puzzles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.puzzles.is_empty()
+    }
+}
+
+impl std::fmt::Display for Completed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for puzzle in &self.puzzles {
+            writeln!(f, "{puzzle}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn progress_is_ignored() {
+        let mut completed = Completed::default();
+        assert!(completed.insert("XXo.O..X.O..X..O"));
+        assert!(completed.contains("XX..O..X.O..X..O"));
+        assert!(completed.contains("XXoxOxoXoOxoXxoO"));
+        assert!(!completed.insert("XX..O..X.O..X..O"));
+        assert!(!completed.contains("X...O..X.O..X..O"));
+        let reloaded = Completed::parse(&completed.to_string());
+        assert_eq!(reloaded.len(), 1);
+        assert!(reloaded.contains("XX..O..X.O..X..O"));
+    }
+}
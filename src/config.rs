@@ -0,0 +1,256 @@
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::difficulty::Difficulty;
+use crate::theme::Theme;
+
+/// When and how often the session is written to the recovery file.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AutosaveConfig {
+    pub enabled: bool,
+    pub interval_secs: u64,
+}
+
+impl Default for AutosaveConfig {
+    fn default() -> Self {
+        AutosaveConfig {
+            enabled: true,
+            interval_secs: 30,
+        }
+    }
+}
+
+impl AutosaveConfig {
+    pub fn interval(&self) -> Duration {
+        Duration::from_secs(self.interval_secs)
+    }
+}
+
+/// Keys used by the full-screen interface. The arrow keys always move the cursor.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KeyBindings {
+    pub up: char,
+    pub down: char,
+    pub left: char,
+    pub right: char,
+    pub x: char,
+    pub o: char,
+    pub erase: char,
+    pub verify: char,
+    pub reset: char,
+    pub solve: char,
+    pub next: char,
+    pub previous: char,
+    pub quit: char,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        KeyBindings {
+            up: 'k',
+            down: 'j',
+            left: 'h',
+            right: 'l',
+            x: 'x',
+            o: 'o',
+            erase: ' ',
+            verify: 'v',
+            reset: 'r',
+            solve: 's',
+            next: 'n',
+            previous: 'p',
+            quit: 'q',
+        }
+    }
+}
+
+/// User settings read from `config.toml`. Missing settings take their defaults.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Size of the puzzle generated at startup.
+    pub size: u8,
+    /// Difficulty of the puzzle generated at startup. Without it the puzzle has
+    /// no extra clues.
+    pub difficulty: Option<Difficulty>,
+    pub theme: Theme,
+    pub autosave: AutosaveConfig,
+    pub keys: KeyBindings,
+    /// Replace board drawings with short descriptions for screen readers.
+    pub accessible: bool,
+    /// Show the X and O counts of each row and column next to the board.
+    pub tallies: bool,
+    /// Print only the changed row or column after a move, not the whole board.
+    pub quiet: bool,
+    /// Fill cells forced by the simple rules after every move.
+    pub auto_fill: bool,
+    /// Refuse moves that break a rule right away.
+    pub assisted: bool,
+    /// Directories searched by `import` when a file is not found.
+    pub puzzle_dirs: Vec<String>,
+    /// Interpreter commands that stand for longer ones. `$1`, `$2` and so on
+    /// are replaced by the words typed after the alias, and `$*` by all of
+    /// them; without placeholders the words are added at the end.
+    pub aliases: BTreeMap<String, String>,
+    /// Server that `binox daily` downloads the day's puzzle from and sends
+    /// times to, such as `"http://127.0.0.1:8080"`.
+    pub daily_server: Option<String>,
+    /// Hint points available on each puzzle. A nudge costs 1, naming a cell 2
+    /// and filling one 3. Without it hints are unlimited.
+    pub hint_budget: Option<usize>,
+    /// Language of the interpreter's messages, such as `"de"`. Without it the
+    /// language is taken from `LANG`.
+    pub language: Option<String>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            size: 8,
+            difficulty: None,
+            theme: Theme::default(),
+            autosave: AutosaveConfig::default(),
+            keys: KeyBindings::default(),
+            accessible: false,
+            tallies: false,
+            quiet: false,
+            auto_fill: false,
+            assisted: false,
+            puzzle_dirs: Vec::new(),
+            aliases: BTreeMap::new(),
+            daily_server: None,
+            hint_budget: None,
+            language: None,
+        }
+    }
+}
+
+/// `$XDG_CONFIG_HOME/binox/config.toml`, falling back to `~/.config`.
+pub fn default_config_path() -> Option<PathBuf> {
+    let base = match env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(env::var_os("HOME").or_else(|| env::var_os("USERPROFILE"))?)
+            .join(".config"),
+    };
+    Some(base.join("binox").join("config.toml"))
+}
+
+impl Config {
+    pub fn parse(contents: &str) -> Result<Self, String> {
+        let config: Config = toml::from_str(contents).map_err(|e| e.to_string())?;
+        config.theme.validate()?;
+        if let Some(name) = config
+            .aliases
+            .keys()
+            .find(|name| name.is_empty() || name.contains(char::is_whitespace))
+        {
+            return Err(format!("alias '{name}' must be a single word"));
+        }
+        Ok(config)
+    }
+
+    /// Replaces an alias at the start of an interpreter line with the command
+    /// it stands for. Other lines are returned unchanged.
+    pub fn expand_alias(&self, line: &str) -> String {
+        let mut words = line.split_whitespace();
+        let Some(template) = words.next().and_then(|name| {
+            self.aliases
+                .iter()
+                .find(|(alias, _)| alias.eq_ignore_ascii_case(name))
+                .map(|(_, template)| template)
+        }) else {
+            return line.into();
+        };
+        let arguments: Vec<&str> = words.collect();
+        if !template.contains('$') {
+            return [template.as_str()]
+                .into_iter()
+                .chain(arguments)
+                .collect::<Vec<_>>()
+                .join(" ");
+        }
+        let mut result = template.replace("$*", &arguments.join(" "));
+        for (i, argument) in arguments.iter().enumerate().rev() {
+            result = result.replace(&format!("${}", i + 1), argument);
+        }
+        result
+    }
+
+    /// Loads the configuration from `path`, or from the default location if no
+    /// path is given. A missing default file yields the default configuration,
+    /// but a missing explicit file is an error.
+    pub fn load(path: Option<&str>) -> Result<(Self, Option<PathBuf>), String> {
+        let path = match path {
+            Some(path) => PathBuf::from(path),
+            None => match default_config_path() {
+                Some(path) if path.exists() => path,
+                _ => return Ok((Config::default(), None)),
+            },
+        };
+        let contents = fs::read_to_string(&path)
+            .map_err(|_| format!("config file not found: {}", path.display()))?;
+        let config = Config::parse(&contents).map_err(|e| format!("{}: {}", path.display(), e))?;
+        Ok((config, Some(path)))
+    }
+
+    pub fn to_toml(&self) -> String {
+        toml::to_string(self).unwrap()
+    }
+
+    /// Finds a puzzle file in the working directory or the puzzle directories.
+    pub fn find_puzzle_file(&self, filename: &str) -> String {
+        if Path::new(filename).exists() {
+            return filename.into();
+        }
+        self.puzzle_dirs
+            .iter()
+            .map(|dir| Path::new(dir).join(filename))
+            .find(|path| path.exists())
+            .map_or(filename.into(), |path| path.to_string_lossy().to_string())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn partial_config() {
+        let config = Config::parse(
+            "size = 10\ndifficulty = \"hard\"\n[theme]\nx_color = \"green\"\n[keys]\nquit = \"z\"\n",
+        )
+        .unwrap();
+        assert_eq!(config.size, 10);
+        assert_eq!(config.difficulty, Some(Difficulty::Hard));
+        assert_eq!(config.theme.x_color, "green");
+        assert_eq!(config.theme.o_color, "blue");
+        assert_eq!(config.keys.quit, 'z');
+        assert_eq!(config.keys.x, 'x');
+        assert!(config.autosave.enabled);
+        assert_eq!(Config::parse(&config.to_toml()).unwrap(), config);
+        assert!(Config::parse("[theme]\nx_color = \"plaid\"\n").is_err());
+        assert!(Config::parse("size = \"big\"\n").is_err());
+    }
+
+    #[test]
+    fn aliases() {
+        let config = Config::parse(
+            "[aliases]\ng8 = \"generate 8 perfect\"\nxx = \"x $1 $2\"\nsay = \"read $*\"\n",
+        )
+        .unwrap();
+        assert_eq!(config.expand_alias("g8"), "generate 8 perfect");
+        assert_eq!(config.expand_alias("G8 now"), "generate 8 perfect now");
+        assert_eq!(config.expand_alias("xx 0 2-4"), "x 0 2-4");
+        assert_eq!(config.expand_alias("say row 3"), "read row 3");
+        assert_eq!(config.expand_alias("x a3"), "x a3");
+        assert_eq!(Config::parse(&config.to_toml()).unwrap(), config);
+        assert!(Config::parse("[aliases]\n\"g 8\" = \"generate 8\"\n").is_err());
+    }
+}
@@ -0,0 +1,286 @@
+use std::env;
+use std::fs;
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::binox::{Binox, Symmetry};
+use crate::completion::canonical;
+use crate::difficulty::Difficulty;
+
+const CACHE_FILE_NAME: &str = ".binox_daily.json";
+
+/// How long to wait for a daily puzzle server.
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The cache of downloaded daily puzzles lives in the home directory, or the
+/// working directory if no home directory is known.
+pub fn cache_path() -> PathBuf {
+    match env::var_os("HOME").or_else(|| env::var_os("USERPROFILE")) {
+        Some(home) => PathBuf::from(home).join(CACHE_FILE_NAME),
+        None => PathBuf::from(CACHE_FILE_NAME),
+    }
+}
+
+/// The puzzle of one day, as `binox serve` hands it out.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DailyPuzzle {
+    pub day: u64,
+    pub size: u8,
+    pub difficulty: Option<Difficulty>,
+    pub puzzle: String,
+}
+
+impl DailyPuzzle {
+    /// Generates the day's puzzle the way `binox serve` does, with the day as
+    /// the seed, so that it is the same as the server's.
+    pub fn generate(
+        size: u8,
+        difficulty: Option<Difficulty>,
+        day: u64,
+    ) -> Result<Self, &'static str> {
+        let extras = difficulty.map_or(0, |d| d.extras(size));
+        let mut rng = StdRng::seed_from_u64(day);
+        let (binox, _) =
+            Binox::generate_with_rng(size, difficulty.is_some(), extras, Symmetry::None, &mut rng)?;
+        Ok(DailyPuzzle {
+            day,
+            size,
+            difficulty,
+            puzzle: binox.as_string(),
+        })
+    }
+}
+
+/// A downloaded daily puzzle and the server it came from.
+#[derive(Serialize, Deserialize)]
+struct Cached {
+    server: String,
+    #[serde(flatten)]
+    daily: DailyPuzzle,
+}
+
+fn load_cache() -> Vec<Cached> {
+    fs::read_to_string(cache_path())
+        .ok()
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+/// Splits an `http://host:port/prefix` address into the host and port to
+/// connect to and the path prefix of every request.
+fn split_url(url: &str) -> Result<(String, String), String> {
+    let rest = url
+        .strip_prefix("http://")
+        .ok_or("only http:// servers are supported")?;
+    let (host, prefix) = match rest.find('/') {
+        Some(slash) => rest.split_at(slash),
+        None => (rest, ""),
+    };
+    if host.is_empty() {
+        return Err(format!("no host in {url}"));
+    }
+    let host = if host.contains(':') {
+        host.into()
+    } else {
+        format!("{host}:80")
+    };
+    Ok((host, prefix.trim_end_matches('/').into()))
+}
+
+/// Reassembles a body sent in chunks.
+fn unchunk(body: &str) -> String {
+    let mut result = String::new();
+    let mut rest = body;
+    while let Some((size, after)) = rest.split_once("\r\n") {
+        let size = usize::from_str_radix(size.trim(), 16).unwrap_or_default();
+        if size == 0 || after.len() < size {
+            break;
+        }
+        result.push_str(&after[..size]);
+        rest = after[size..].trim_start_matches("\r\n");
+    }
+    result
+}
+
+/// Sends one HTTP request to the server and reads its JSON answer. A server
+/// that answers with an error status has its `error` message returned.
+fn request(server: &str, method: &str, path: &str, body: Option<&Value>) -> Result<Value, String> {
+    let (host, prefix) = split_url(server)?;
+    let failed = |e: std::io::Error| format!("could not reach {server}: {e}");
+    let mut stream = TcpStream::connect(&host).map_err(failed)?;
+    stream.set_read_timeout(Some(TIMEOUT)).map_err(failed)?;
+    let body = body.map(Value::to_string).unwrap_or_default();
+    write!(
+        stream,
+        "{method} {prefix}{path} HTTP/1.1\r\nHost: {host}\r\nConnection: close\r\n\
+         Content-Type: application/json\r\nContent-Length: {}\r\n\r\n{body}",
+        body.len()
+    )
+    .map_err(failed)?;
+    let mut response = String::new();
+    stream.read_to_string(&mut response).map_err(failed)?;
+    let (head, body) = response
+        .split_once("\r\n\r\n")
+        .ok_or_else(|| format!("{server} sent an invalid response"))?;
+    let status: u16 = head
+        .split(' ')
+        .nth(1)
+        .and_then(|status| status.parse().ok())
+        .ok_or_else(|| format!("{server} sent an invalid response"))?;
+    let chunked = head
+        .lines()
+        .any(|line| line.eq_ignore_ascii_case("transfer-encoding: chunked"));
+    let body = if chunked { unchunk(body) } else { body.into() };
+    let value: Value =
+        serde_json::from_str(&body).map_err(|_| format!("{server} did not answer with JSON"))?;
+    if status != 200 {
+        let error = value["error"].as_str().unwrap_or("no reason given");
+        return Err(format!("{server} refused: {error}"));
+    }
+    Ok(value)
+}
+
+/// The day's puzzle from a server, downloaded once a day and then read from
+/// the cache.
+pub fn fetch(
+    server: &str,
+    size: u8,
+    difficulty: Option<Difficulty>,
+    today: u64,
+) -> Result<DailyPuzzle, String> {
+    let mut cache = load_cache();
+    let cached = cache.iter().find(|cached| {
+        cached.server == server
+            && cached.daily.day == today
+            && cached.daily.size == size
+            && cached.daily.difficulty == difficulty
+    });
+    if let Some(cached) = cached {
+        return Ok(cached.daily.clone());
+    }
+    let mut path = format!("/daily?size={size}");
+    if let Some(difficulty) = difficulty {
+        path.push_str(&format!("&difficulty={difficulty}"));
+    }
+    let value = request(server, "GET", &path, None)?;
+    let puzzle = value["puzzle"]
+        .as_str()
+        .ok_or_else(|| format!("{server} sent no puzzle"))?;
+    Binox::parse_strict(puzzle)?;
+    let daily = DailyPuzzle {
+        day: value["day"].as_u64().unwrap_or(today),
+        size,
+        difficulty,
+        puzzle: puzzle.into(),
+    };
+    cache.retain(|cached| cached.daily.day == daily.day);
+    cache.push(Cached {
+        server: server.into(),
+        daily: daily.clone(),
+    });
+    // Without the cache the puzzle is downloaded again next time, which is
+    // not worth stopping for.
+    let _ = serde_json::to_string(&cache).map(|contents| fs::write(cache_path(), contents));
+    Ok(daily)
+}
+
+/// Sends the time of a solved board to the server if it is the daily
+/// puzzle, and describes its rank among the day's times.
+pub fn submit(
+    server: &str,
+    daily: &DailyPuzzle,
+    grid: &str,
+    time: Duration,
+) -> Result<Option<String>, String> {
+    if canonical(grid) != canonical(&daily.puzzle) {
+        return Ok(None);
+    }
+    let body = json!({
+        "day": daily.day,
+        "size": daily.size,
+        "difficulty": daily.difficulty,
+        "grid": grid,
+        "seconds": time.as_secs(),
+    });
+    let value = request(server, "POST", "/daily/time", Some(&body))?;
+    Ok(Some(format!(
+        "your time is number {} of the {} sent in today",
+        value["rank"], value["times"]
+    )))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::io::{BufRead, BufReader};
+    use std::net::TcpListener;
+    use std::thread;
+
+    #[test]
+    fn remote_daily() {
+        assert_eq!(
+            split_url("http://example.org/binox/"),
+            Ok(("example.org:80".into(), "/binox".into()))
+        );
+        assert_eq!(
+            split_url("http://127.0.0.1:8080"),
+            Ok(("127.0.0.1:8080".into(), "".into()))
+        );
+        assert!(split_url("https://example.org").is_err());
+        assert_eq!(unchunk("4\r\nWiki\r\n5\r\npedia\r\n0\r\n\r\n"), "Wikipedia");
+
+        let daily = DailyPuzzle::generate(6, Some(Difficulty::Easy), 100).unwrap();
+        assert_eq!(
+            daily,
+            DailyPuzzle::generate(6, Some(Difficulty::Easy), 100).unwrap()
+        );
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let server = format!("http://{}/binox", listener.local_addr().unwrap());
+        thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            let mut length = 0;
+            loop {
+                let mut header = String::new();
+                reader.read_line(&mut header).unwrap();
+                if header.trim().is_empty() {
+                    break;
+                }
+                if let Some(value) = header.strip_prefix("Content-Length: ") {
+                    length = value.trim().parse().unwrap();
+                }
+            }
+            reader.read_exact(&mut vec![0; length]).unwrap();
+            let body = if line.starts_with("POST /binox/daily/time ") {
+                r#"{"day": 100, "rank": 2, "times": 5}"#
+            } else {
+                r#"{"error": "wrong request"}"#
+            };
+            let mut stream = reader.into_inner();
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n{body}",
+                body.len()
+            )
+            .unwrap();
+        });
+        let solution = Binox::new_from_string(daily.puzzle.clone())
+            .unique_solution()
+            .unwrap();
+        let time = Duration::from_secs(75);
+        assert_eq!(submit(&server, &daily, "................", time), Ok(None));
+        assert_eq!(
+            submit(&server, &daily, &solution.as_string(), time),
+            Ok(Some("your time is number 2 of the 5 sent in today".into()))
+        );
+    }
+}
@@ -0,0 +1,81 @@
+use core::fmt;
+use core::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+    Expert,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 4] = [
+        Difficulty::Easy,
+        Difficulty::Medium,
+        Difficulty::Hard,
+        Difficulty::Expert,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Difficulty::Easy => "easy",
+            Difficulty::Medium => "medium",
+            Difficulty::Hard => "hard",
+            Difficulty::Expert => "expert",
+        }
+    }
+
+    /// Number of extra clues added to a perfect puzzle of the given size.
+    pub fn extras(&self, size: u8) -> usize {
+        let step = (size as usize / 2).saturating_sub(2).max(1);
+        match self {
+            Difficulty::Easy => 3 * step,
+            Difficulty::Medium => 2 * step,
+            Difficulty::Hard => step,
+            Difficulty::Expert => 0,
+        }
+    }
+
+    /// The difficulty of a puzzle with the given logical rating score.
+    pub fn from_score(score: f64) -> Self {
+        if score < 1.5 {
+            Difficulty::Easy
+        } else if score < 1.8 {
+            Difficulty::Medium
+        } else if score < 2.2 {
+            Difficulty::Hard
+        } else {
+            Difficulty::Expert
+        }
+    }
+
+    /// Guesses the difficulty from a file name such as `8x8_hard.binox`.
+    pub fn from_file_name(name: &str) -> Option<Self> {
+        let stem = name.split('/').next_back()?.split('.').next()?;
+        stem.split('_').next_back()?.parse().ok()
+    }
+}
+
+impl FromStr for Difficulty {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "easy" | "e" => Ok(Difficulty::Easy),
+            "medium" | "m" => Ok(Difficulty::Medium),
+            "hard" | "h" => Ok(Difficulty::Hard),
+            "expert" | "x" => Ok(Difficulty::Expert),
+            _ => Err("difficulty must be easy, medium, hard, or expert"),
+        }
+    }
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(self.name())
+    }
+}
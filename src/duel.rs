@@ -0,0 +1,176 @@
+use std::io::{self, BufRead, Write};
+
+use crate::binox::{column_name, Binox, BinoxCell};
+use crate::binox_interpreter::parse_cell;
+use crate::puzzle::Puzzle;
+use crate::theme::current_theme;
+
+/// What a move led to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    /// The other player moves next.
+    Continue,
+    /// The move broke a rule, and the player who made it lost.
+    BrokeRule,
+    /// The other player has no move that keeps to the rules, and lost.
+    NoMoves,
+}
+
+/// Two players taking turns on one puzzle. Each turn places one symbol in an
+/// empty cell; whoever breaks a rule, or has no move that keeps to the rules,
+/// loses.
+pub struct Duel {
+    board: Binox,
+    players: [String; 2],
+    turn: usize,
+    loser: Option<usize>,
+}
+
+impl Duel {
+    pub fn new(puzzle: Binox, players: [String; 2]) -> Self {
+        let mut duel = Duel {
+            board: puzzle,
+            players,
+            turn: 0,
+            loser: None,
+        };
+        if !duel.has_move() {
+            duel.loser = Some(0);
+        }
+        duel
+    }
+
+    pub fn board(&self) -> &Binox {
+        &self.board
+    }
+
+    /// The player whose turn it is.
+    pub fn current(&self) -> &str {
+        &self.players[self.turn]
+    }
+
+    pub fn winner(&self) -> Option<&str> {
+        self.loser.map(|loser| self.players[1 - loser].as_str())
+    }
+
+    pub fn loser(&self) -> Option<&str> {
+        self.loser.map(|loser| self.players[loser].as_str())
+    }
+
+    /// Whether some empty cell can take a symbol without breaking a rule.
+    fn has_move(&self) -> bool {
+        let mut board = self.board.clone();
+        self.board.empty_cells().into_iter().any(|(row, col)| {
+            [BinoxCell::X, BinoxCell::O].into_iter().any(|cell| {
+                board.set_cell(row, col, cell).unwrap();
+                let valid = board.is_valid();
+                board.set_cell(row, col, BinoxCell::EMPTY).unwrap();
+                valid
+            })
+        })
+    }
+
+    /// Places a symbol for the player whose turn it is. Moves that cannot be
+    /// made at all, such as on a filled cell, are errors and do not use up
+    /// the turn.
+    pub fn play(&mut self, row: u8, col: u8, cell: BinoxCell) -> Result<Outcome, String> {
+        if self.loser.is_some() {
+            return Err("the game is over".into());
+        }
+        if cell == BinoxCell::EMPTY {
+            return Err("each turn places an X or an O".into());
+        }
+        if self.board.get_cell(row, col)? != BinoxCell::EMPTY {
+            return Err(format!("{}{row} is already filled", column_name(col)));
+        }
+        self.board.set_cell(row, col, cell)?;
+        if !self.board.is_valid() {
+            self.loser = Some(self.turn);
+            return Ok(Outcome::BrokeRule);
+        }
+        self.turn = 1 - self.turn;
+        if !self.has_move() {
+            self.loser = Some(self.turn);
+            return Ok(Outcome::NoMoves);
+        }
+        Ok(Outcome::Continue)
+    }
+}
+
+/// Plays a duel on this terminal, reading each player's moves in turn.
+pub fn run_duel(puzzle: Binox, players: [String; 2]) -> Result<(), String> {
+    let mut duel = Duel::new(puzzle, players);
+    println!("{}", duel.board());
+    println!("take turns placing a symbol, as in `x a3`. whoever breaks a rule or has no move left loses.");
+    let mut lines = io::stdin().lock().lines();
+    while duel.winner().is_none() {
+        print!("{}> ", duel.current());
+        io::stdout().flush().map_err(|e| e.to_string())?;
+        let Some(Ok(line)) = lines.next() else {
+            return Ok(());
+        };
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let Some(first) = words.first() else {
+            continue;
+        };
+        if matches!(first.to_lowercase().as_str(), "quit" | "exit") {
+            return Ok(());
+        }
+        let Some(cell) = current_theme().cell_for(first) else {
+            println!("place a symbol, as in `x a3`, or type quit");
+            continue;
+        };
+        let outcome = parse_cell(&words[1..]).and_then(|(row, col)| duel.play(row, col, cell));
+        match outcome {
+            Err(text) => println!("{text}"),
+            Ok(outcome) => {
+                println!("{}", duel.board());
+                let (winner, loser) = (duel.winner(), duel.loser());
+                match (outcome, winner, loser) {
+                    (Outcome::BrokeRule, Some(winner), Some(loser)) => {
+                        println!("{loser} broke a rule. {winner} wins!")
+                    }
+                    (Outcome::NoMoves, Some(winner), Some(loser)) => {
+                        println!("{loser} has no move left. {winner} wins!")
+                    }
+                    _ => (),
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn duel(puzzle: &str) -> Duel {
+        Duel::new(
+            Binox::parse_strict(puzzle).unwrap(),
+            ["ann".into(), "bob".into()],
+        )
+    }
+
+    #[test]
+    fn breaking_a_rule_loses() {
+        let mut game = duel("XX..............");
+        assert_eq!(game.current(), "ann");
+        assert!(game.play(0, 0, BinoxCell::O).is_err());
+        assert_eq!(game.play(1, 0, BinoxCell::O), Ok(Outcome::Continue));
+        assert_eq!(game.current(), "bob");
+        assert_eq!(game.play(0, 2, BinoxCell::X), Ok(Outcome::BrokeRule));
+        assert_eq!(game.loser(), Some("bob"));
+        assert_eq!(game.winner(), Some("ann"));
+        assert!(game.play(0, 3, BinoxCell::O).is_err());
+    }
+
+    #[test]
+    fn having_no_move_loses() {
+        let mut game = duel("XOXOOXOXXOOX...O");
+        assert_eq!(game.play(3, 0, BinoxCell::O), Ok(Outcome::Continue));
+        assert_eq!(game.play(3, 1, BinoxCell::X), Ok(Outcome::Continue));
+        assert_eq!(game.play(3, 2, BinoxCell::X), Ok(Outcome::NoMoves));
+        assert_eq!(game.loser(), Some("bob"));
+    }
+}
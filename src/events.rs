@@ -0,0 +1,189 @@
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+
+use crate::binox::{Binox, BinoxCell, BinoxError, BinoxSolution};
+
+/// A change to an observed board.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Event {
+    /// A cell was filled, or changed to the other symbol.
+    CellSet { row: u8, col: u8, cell: BinoxCell },
+    /// A filled cell was emptied.
+    CellCleared { row: u8, col: u8 },
+    /// Every cell but the clues was emptied.
+    Reset,
+    /// The board became a solution of the puzzle.
+    Solved,
+    /// The board started or stopped breaking a rule.
+    ValidityChanged { valid: bool },
+}
+
+/// Identifies a subscriber, for [`ObservedBinox::unsubscribe`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Subscription(usize);
+
+type Subscriber = Box<dyn FnMut(&Event)>;
+
+/// A board that tells its subscribers about every change made through it, so
+/// a GUI can redraw only what changed instead of reading the whole board.
+pub struct ObservedBinox {
+    board: Binox,
+    subscribers: Vec<(Subscription, Subscriber)>,
+    next_id: usize,
+    valid: bool,
+    solved: bool,
+}
+
+impl ObservedBinox {
+    pub fn new(board: Binox) -> Self {
+        ObservedBinox {
+            valid: board.is_valid(),
+            solved: board.is_solved(),
+            board,
+            subscribers: Vec::new(),
+            next_id: 0,
+        }
+    }
+
+    pub fn board(&self) -> &Binox {
+        &self.board
+    }
+
+    pub fn into_inner(self) -> Binox {
+        self.board
+    }
+
+    /// Calls `callback` with every event from now on.
+    pub fn subscribe(&mut self, callback: impl FnMut(&Event) + 'static) -> Subscription {
+        let id = Subscription(self.next_id);
+        self.next_id += 1;
+        self.subscribers.push((id, Box::new(callback)));
+        id
+    }
+
+    /// Sends every event from now on to the returned receiver, which can be
+    /// read on another thread.
+    #[cfg(feature = "std")]
+    pub fn subscribe_channel(&mut self) -> std::sync::mpsc::Receiver<Event> {
+        let (sender, receiver) = std::sync::mpsc::channel();
+        self.subscribe(move |event| {
+            let _ = sender.send(*event);
+        });
+        receiver
+    }
+
+    pub fn unsubscribe(&mut self, subscription: Subscription) {
+        self.subscribers.retain(|(id, _)| *id != subscription);
+    }
+
+    fn emit(&mut self, event: Event) {
+        for (_, callback) in &mut self.subscribers {
+            callback(&event);
+        }
+    }
+
+    /// Emits the events for the board's state after a change.
+    fn check_state(&mut self) {
+        let valid = self.board.is_valid();
+        if valid != self.valid {
+            self.valid = valid;
+            self.emit(Event::ValidityChanged { valid });
+        }
+        let solved = self.board.is_solved();
+        if solved != self.solved {
+            self.solved = solved;
+            if solved {
+                self.emit(Event::Solved);
+            }
+        }
+    }
+
+    /// Sets a cell like [`Binox::set_cell`]. Setting a cell to what it
+    /// already holds emits nothing.
+    pub fn set_cell(&mut self, row: u8, col: u8, cell: BinoxCell) -> Result<(), BinoxError> {
+        let before = self.board.get_cell(row, col).unwrap_or(cell);
+        self.board.set_cell(row, col, cell)?;
+        if before == cell {
+            return Ok(());
+        }
+        self.emit(match cell {
+            BinoxCell::EMPTY => Event::CellCleared { row, col },
+            cell => Event::CellSet { row, col, cell },
+        });
+        self.check_state();
+        Ok(())
+    }
+
+    pub fn clear_cell(&mut self, row: u8, col: u8) -> Result<(), BinoxError> {
+        self.set_cell(row, col, BinoxCell::EMPTY)
+    }
+
+    pub fn reset(&mut self) {
+        self.board.reset();
+        self.emit(Event::Reset);
+        self.check_state();
+    }
+
+    /// Fills the board with a solution, emitting an event for each cell it
+    /// changes.
+    pub fn solve(&mut self) -> Result<(), &'static str> {
+        let solution = match self.board.solve(false) {
+            BinoxSolution::Zero => return Err("puzzle has no solution"),
+            BinoxSolution::One(a) | BinoxSolution::Multiple(a, _) => a,
+        };
+        for (row, col) in self.board.changed_cells(&solution) {
+            let cell = solution.get_cell(row, col).unwrap();
+            self.board.set_cell(row, col, cell).unwrap();
+            self.emit(Event::CellSet { row, col, cell });
+        }
+        self.check_state();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::rc::Rc;
+    use alloc::vec;
+    use core::cell::RefCell;
+
+    use super::*;
+
+    #[test]
+    fn events() {
+        let mut board = ObservedBinox::new(Binox::new_from_string("XX..............".into()));
+        let seen = Rc::new(RefCell::new(Vec::new()));
+        let log = Rc::clone(&seen);
+        let subscription = board.subscribe(move |event| log.borrow_mut().push(*event));
+
+        board.set_cell(0, 2, BinoxCell::X).unwrap();
+        board.set_cell(0, 2, BinoxCell::X).unwrap();
+        board.clear_cell(0, 2).unwrap();
+        assert!(board.set_cell(0, 0, BinoxCell::O).is_err());
+        assert_eq!(
+            *seen.borrow(),
+            vec![
+                Event::CellSet {
+                    row: 0,
+                    col: 2,
+                    cell: BinoxCell::X
+                },
+                Event::ValidityChanged { valid: false },
+                Event::CellCleared { row: 0, col: 2 },
+                Event::ValidityChanged { valid: true },
+            ]
+        );
+
+        seen.borrow_mut().clear();
+        board.solve().unwrap();
+        assert_eq!(seen.borrow().len(), 14 + 1);
+        assert_eq!(seen.borrow().last(), Some(&Event::Solved));
+        board.reset();
+        assert_eq!(seen.borrow().last(), Some(&Event::Reset));
+
+        board.unsubscribe(subscription);
+        seen.borrow_mut().clear();
+        board.set_cell(1, 1, BinoxCell::O).unwrap();
+        assert!(seen.borrow().is_empty());
+    }
+}
@@ -0,0 +1,236 @@
+use std::cell::RefCell;
+use std::ffi::{c_char, c_int, CStr, CString};
+use std::ptr;
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use crate::binox::{Binox, BinoxCell, BinoxSolution, Symmetry};
+
+pub const BINOX_EMPTY: c_int = 0;
+pub const BINOX_X: c_int = 1;
+pub const BINOX_O: c_int = 2;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_error(text: impl ToString) {
+    let text = CString::new(text.to_string()).unwrap_or_default();
+    LAST_ERROR.with(|error| *error.borrow_mut() = Some(text));
+}
+
+/// Boxes a board for C, or records the error and returns null.
+fn into_handle<E: ToString>(result: Result<Binox, E>) -> *mut Binox {
+    match result {
+        Ok(binox) => Box::into_raw(Box::new(binox)),
+        Err(text) => {
+            set_error(text);
+            ptr::null_mut()
+        }
+    }
+}
+
+fn cell_code(cell: BinoxCell) -> c_int {
+    match cell {
+        BinoxCell::EMPTY => BINOX_EMPTY,
+        BinoxCell::X => BINOX_X,
+        BinoxCell::O => BINOX_O,
+    }
+}
+
+/// The message of the last failed call on this thread, or null. The string
+/// stays valid until the next failing call.
+#[no_mangle]
+pub extern "C" fn binox_last_error() -> *const c_char {
+    LAST_ERROR.with(|error| {
+        error
+            .borrow()
+            .as_ref()
+            .map_or(ptr::null(), |text| text.as_ptr())
+    })
+}
+
+/// An empty board of the given size, or null if the size is not supported.
+#[no_mangle]
+pub extern "C" fn binox_new(size: u8) -> *mut Binox {
+    into_handle(Binox::new(size))
+}
+
+/// Reads a puzzle string such as `XXO.O..X.O..X..O`, or returns null if it is
+/// not a valid puzzle.
+///
+/// # Safety
+///
+/// `puzzle` must be a valid null-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn binox_from_string(puzzle: *const c_char) -> *mut Binox {
+    if puzzle.is_null() {
+        set_error("puzzle string is null");
+        return ptr::null_mut();
+    }
+    let puzzle = CStr::from_ptr(puzzle).to_string_lossy();
+    into_handle(Binox::parse_strict(&puzzle))
+}
+
+/// Generates a puzzle with a unique solution. The same seed always gives the
+/// same puzzle.
+#[no_mangle]
+pub extern "C" fn binox_generate(size: u8, seed: u64, perfect: bool) -> *mut Binox {
+    let mut rng = StdRng::seed_from_u64(seed);
+    into_handle(
+        Binox::generate_with_rng(size, perfect, 0, Symmetry::None, &mut rng)
+            .map(|(binox, _)| binox),
+    )
+}
+
+/// Releases a board. Null is ignored.
+///
+/// # Safety
+///
+/// `binox` must come from this library and not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn binox_free(binox: *mut Binox) {
+    if !binox.is_null() {
+        drop(Box::from_raw(binox));
+    }
+}
+
+/// # Safety
+///
+/// `binox` must be a valid board.
+#[no_mangle]
+pub unsafe extern "C" fn binox_size(binox: *const Binox) -> u8 {
+    (*binox).size()
+}
+
+/// The contents of a cell as `BINOX_EMPTY`, `BINOX_X` or `BINOX_O`, or -1 if
+/// the cell is out of range.
+///
+/// # Safety
+///
+/// `binox` must be a valid board.
+#[no_mangle]
+pub unsafe extern "C" fn binox_get_cell(binox: *const Binox, row: u8, col: u8) -> c_int {
+    match (*binox).get_cell(row, col) {
+        Ok(cell) => cell_code(cell),
+        Err(text) => {
+            set_error(text);
+            -1
+        }
+    }
+}
+
+/// Places `BINOX_X` or `BINOX_O`, or clears the cell with `BINOX_EMPTY`.
+/// Returns 0, or -1 if the cell is out of range or given.
+///
+/// # Safety
+///
+/// `binox` must be a valid board.
+#[no_mangle]
+pub unsafe extern "C" fn binox_set_cell(binox: *mut Binox, row: u8, col: u8, cell: c_int) -> c_int {
+    let cell = match cell {
+        BINOX_EMPTY => BinoxCell::EMPTY,
+        BINOX_X => BinoxCell::X,
+        BINOX_O => BinoxCell::O,
+        _ => {
+            set_error("cell must be BINOX_EMPTY, BINOX_X or BINOX_O");
+            return -1;
+        }
+    };
+    match (*binox).set_cell(row, col, cell) {
+        Ok(()) => 0,
+        Err(text) => {
+            set_error(text);
+            -1
+        }
+    }
+}
+
+/// Whether the board breaks no rule so far.
+///
+/// # Safety
+///
+/// `binox` must be a valid board.
+#[no_mangle]
+pub unsafe extern "C" fn binox_is_valid(binox: *const Binox) -> bool {
+    (*binox).is_valid()
+}
+
+/// # Safety
+///
+/// `binox` must be a valid board.
+#[no_mangle]
+pub unsafe extern "C" fn binox_is_solved(binox: *const Binox) -> bool {
+    (*binox).is_solved()
+}
+
+/// The solved board as a new board, or null if the puzzle has no solution or
+/// more than one.
+///
+/// # Safety
+///
+/// `binox` must be a valid board.
+#[no_mangle]
+pub unsafe extern "C" fn binox_solve(binox: *const Binox) -> *mut Binox {
+    into_handle(match (*binox).solve(true) {
+        BinoxSolution::One(solution) => Ok(solution),
+        BinoxSolution::Zero => Err("puzzle has no solution"),
+        BinoxSolution::Multiple(..) => Err("multiple solutions found"),
+    })
+}
+
+/// The board as a puzzle string, which `binox_from_string` reads back. The
+/// string must be released with `binox_string_free`.
+///
+/// # Safety
+///
+/// `binox` must be a valid board.
+#[no_mangle]
+pub unsafe extern "C" fn binox_to_string(binox: *const Binox) -> *mut c_char {
+    CString::new((*binox).as_string()).map_or(ptr::null_mut(), CString::into_raw)
+}
+
+/// Releases a string returned by `binox_to_string`. Null is ignored.
+///
+/// # Safety
+///
+/// `text` must come from `binox_to_string` and not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn binox_string_free(text: *mut c_char) {
+    if !text.is_null() {
+        drop(CString::from_raw(text));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn playing_through_c() {
+        unsafe {
+            let puzzle = CString::new("XXO.O..X.O..X..O").unwrap();
+            let binox = binox_from_string(puzzle.as_ptr());
+            assert!(!binox.is_null());
+            assert_eq!(binox_size(binox), 4);
+            assert_eq!(binox_get_cell(binox, 0, 0), BINOX_X);
+            assert_eq!(binox_set_cell(binox, 0, 0, BINOX_O), -1);
+            let error = CStr::from_ptr(binox_last_error()).to_str().unwrap();
+            assert_eq!(error, "cell a0 is a given and cannot be modified");
+            let solution = binox_solve(binox);
+            assert!(binox_is_solved(solution));
+            let text = binox_to_string(solution);
+            let copy = binox_from_string(text);
+            assert!(binox_is_solved(copy));
+            binox_string_free(text);
+            for board in [binox, solution, copy] {
+                binox_free(board);
+            }
+            assert!(binox_new(5).is_null());
+            let generated = binox_generate(6, 7, false);
+            assert!(binox_is_valid(generated));
+            binox_free(generated);
+        }
+    }
+}
@@ -0,0 +1,321 @@
+use core::fmt;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::difficulty::Difficulty;
+use crate::puzzle_set::{PuzzleEntry, PuzzleSet, SET_EXTENSION};
+
+/// The file formats puzzles can be converted between.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    /// One puzzle string per line (`.binox`).
+    Legacy,
+    /// The `.binoxset` manifest with titles, difficulties and answer keys.
+    V2,
+    /// A JSON object with the set name and a list of puzzles (`.json`).
+    Json,
+    /// A CSV table with a header row (`.csv`).
+    Csv,
+    /// Grids of `1` for X, `0` for O and `.` for empty cells, with a blank line
+    /// between puzzles (`.grid`).
+    Binary,
+}
+
+impl Format {
+    pub const ALL: [Format; 5] = [
+        Format::Legacy,
+        Format::V2,
+        Format::Json,
+        Format::Csv,
+        Format::Binary,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Format::Legacy => "legacy",
+            Format::V2 => "v2",
+            Format::Json => "json",
+            Format::Csv => "csv",
+            Format::Binary => "binary",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Format::Legacy => "binox",
+            Format::V2 => SET_EXTENSION,
+            Format::Json => "json",
+            Format::Csv => "csv",
+            Format::Binary => "grid",
+        }
+    }
+
+    pub fn from_path(path: &str) -> Option<Self> {
+        let extension = Path::new(path).extension()?.to_str()?;
+        Format::ALL
+            .into_iter()
+            .find(|format| format.extension() == extension)
+    }
+
+    pub fn read(&self, name: &str, contents: &str) -> Result<PuzzleSet, String> {
+        match self {
+            Format::Legacy => Ok(PuzzleSet::from_lines(
+                name.into(),
+                contents,
+                Difficulty::from_file_name(name),
+            )),
+            Format::V2 => PuzzleSet::parse_manifest(contents),
+            Format::Json => serde_json::from_str(contents).map_err(|e| e.to_string()),
+            Format::Csv => read_csv(name, contents),
+            Format::Binary => read_binary(name, contents),
+        }
+    }
+
+    pub fn write(&self, puzzles: &PuzzleSet) -> String {
+        match self {
+            Format::Legacy => {
+                let mut result = String::new();
+                for entry in &puzzles.entries {
+                    result.push_str(&entry.puzzle);
+                    result.push('\n');
+                }
+                result
+            }
+            Format::V2 => puzzles.manifest_string(),
+            Format::Json => serde_json::to_string_pretty(puzzles).unwrap() + "\n",
+            Format::Csv => write_csv(puzzles),
+            Format::Binary => write_binary(puzzles),
+        }
+    }
+}
+
+impl FromStr for Format {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "legacy" | "binox" => Ok(Format::Legacy),
+            "v2" | "binoxset" | "set" => Ok(Format::V2),
+            "json" => Ok(Format::Json),
+            "csv" => Ok(Format::Csv),
+            "binary" | "01" | "grid" => Ok(Format::Binary),
+            _ => Err("format must be legacy, v2, json, csv, or binary"),
+        }
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(self.name())
+    }
+}
+
+const CSV_HEADER: &str = "puzzle,difficulty,title,solution,seed,options";
+
+pub(crate) fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.into()
+    }
+}
+
+fn write_csv(puzzles: &PuzzleSet) -> String {
+    let mut result = format!("{CSV_HEADER}\n");
+    for entry in &puzzles.entries {
+        let fields = [
+            csv_field(&entry.puzzle),
+            entry.difficulty.map_or(String::new(), |d| d.name().into()),
+            csv_field(entry.title.as_deref().unwrap_or_default()),
+            csv_field(entry.solution.as_deref().unwrap_or_default()),
+            entry.seed.map_or(String::new(), |s| s.to_string()),
+            entry.options.map_or(String::new(), |o| o.to_string()),
+        ];
+        result.push_str(&fields.join(","));
+        result.push('\n');
+    }
+    result
+}
+
+fn split_csv_line(line: &str) -> Vec<String> {
+    let mut fields = vec![String::new()];
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                fields.last_mut().unwrap().push('"');
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(String::new()),
+            _ => fields.last_mut().unwrap().push(c),
+        }
+    }
+    fields
+}
+
+fn read_csv(name: &str, contents: &str) -> Result<PuzzleSet, String> {
+    let mut entries = Vec::new();
+    for (i, line) in contents.lines().enumerate() {
+        if line.trim().is_empty() || (i == 0 && line.starts_with("puzzle")) {
+            continue;
+        }
+        let mut fields = split_csv_line(line).into_iter();
+        let mut entry = PuzzleEntry::new(fields.next().unwrap_or_default());
+        let optional = |field: Option<String>| field.filter(|f| !f.is_empty());
+        entry.difficulty = match optional(fields.next()) {
+            Some(d) => Some(d.parse().map_err(|e| format!("line {}: {}", i + 1, e))?),
+            None => None,
+        };
+        entry.title = optional(fields.next());
+        entry.solution = optional(fields.next());
+        entry.seed = match optional(fields.next()) {
+            Some(s) => Some(
+                s.parse()
+                    .map_err(|_| format!("line {}: invalid seed {s}", i + 1))?,
+            ),
+            None => None,
+        };
+        entry.options = match optional(fields.next()) {
+            Some(o) => Some(o.parse().map_err(|e| format!("line {}: {e}", i + 1))?),
+            None => None,
+        };
+        entries.push(entry);
+    }
+    Ok(PuzzleSet::new(name.into(), entries))
+}
+
+fn write_binary(puzzles: &PuzzleSet) -> String {
+    let mut grids = Vec::new();
+    for entry in &puzzles.entries {
+        let size = (entry.puzzle.chars().count() as f64).sqrt().round() as usize;
+        let cells: Vec<char> = entry
+            .puzzle
+            .chars()
+            .map(|c| match c {
+                'x' | 'X' => '1',
+                'o' | 'O' => '0',
+                _ => '.',
+            })
+            .collect();
+        let rows: Vec<String> = cells
+            .chunks(size.max(1))
+            .map(|row| row.iter().collect())
+            .collect();
+        grids.push(rows.join("\n") + "\n");
+    }
+    grids.join("\n")
+}
+
+fn read_binary(name: &str, contents: &str) -> Result<PuzzleSet, String> {
+    let mut entries = Vec::new();
+    for grid in contents.split("\n\n") {
+        let mut puzzle = String::new();
+        for c in grid.chars().filter(|c| !c.is_whitespace()) {
+            puzzle.push(match c {
+                '1' => 'X',
+                '0' => 'O',
+                '.' => '.',
+                _ => return Err(format!("invalid character '{c}' in grid")),
+            });
+        }
+        if !puzzle.is_empty() {
+            entries.push(PuzzleEntry::new(puzzle));
+        }
+    }
+    Ok(PuzzleSet::new(name.into(), entries))
+}
+
+fn file_stem(path: &Path) -> String {
+    path.file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("puzzles")
+        .into()
+}
+
+/// Converts one puzzle file. The formats are guessed from the file extensions
+/// unless given.
+pub fn convert_file(
+    input: &str,
+    output: &str,
+    from: Option<Format>,
+    to: Option<Format>,
+) -> Result<usize, String> {
+    let from = from
+        .or_else(|| Format::from_path(input))
+        .ok_or(format!("unknown format of {input}"))?;
+    let to = to
+        .or_else(|| Format::from_path(output))
+        .ok_or(format!("unknown format of {output}"))?;
+    let contents = fs::read_to_string(input).map_err(|_| format!("file not found: {input}"))?;
+    let puzzles = from
+        .read(&file_stem(Path::new(input)), &contents)
+        .map_err(|e| format!("{input}: {e}"))?;
+    fs::write(output, to.write(&puzzles)).map_err(|_| format!("could not write {output}"))?;
+    Ok(puzzles.len())
+}
+
+/// Converts every file of a recognized format in `input_dir` (or only files of
+/// the `from` format) into `output_dir`, returning the converted file names.
+pub fn convert_directory(
+    input_dir: &str,
+    output_dir: &str,
+    from: Option<Format>,
+    to: Format,
+) -> Result<Vec<String>, String> {
+    let dir = fs::read_dir(input_dir).map_err(|_| format!("directory not found: {input_dir}"))?;
+    fs::create_dir_all(output_dir).map_err(|_| format!("could not create {output_dir}"))?;
+    let mut converted = Vec::new();
+    let mut paths: Vec<_> = dir.flatten().map(|item| item.path()).collect();
+    paths.sort();
+    for path in paths {
+        let input = path.to_string_lossy().to_string();
+        let format = match Format::from_path(&input) {
+            Some(format) if from.is_none_or(|from| from == format) => format,
+            _ => continue,
+        };
+        let output = Path::new(output_dir).join(format!("{}.{}", file_stem(&path), to.extension()));
+        let output = output.to_string_lossy().to_string();
+        convert_file(&input, &output, Some(format), Some(to))?;
+        converted.push(output);
+    }
+    Ok(converted)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::binox::Symmetry;
+    use crate::puzzle_set::GenerateOptions;
+
+    #[test]
+    fn round_trips() {
+        let mut puzzles = PuzzleSet::from_lines("set".into(), "X..o.O..x...O...\n", None);
+        puzzles.entries[0].title = Some("a \"quoted\", title".into());
+        puzzles.entries[0].difficulty = Some(Difficulty::Medium);
+        puzzles.entries[0].solution = Some("xxooooxxoxoxxoxo".into());
+        puzzles.entries[0].seed = Some(42);
+        puzzles.entries[0].options = Some(GenerateOptions {
+            perfect: true,
+            extras: 3,
+            symmetry: Symmetry::None,
+        });
+        for format in [Format::V2, Format::Json, Format::Csv] {
+            let text = format.write(&puzzles);
+            assert_eq!(format.read("set", &text).unwrap(), puzzles, "{format}");
+        }
+        puzzles.entries[0].author = Some("Ann Lee".into());
+        puzzles.entries[0].created = Some("2024-05-17".into());
+        puzzles.entries[0].notes = Some("from the spring issue".into());
+        for format in [Format::V2, Format::Json] {
+            let text = format.write(&puzzles);
+            assert_eq!(format.read("set", &text).unwrap(), puzzles, "{format}");
+        }
+        let grid = Format::Binary.write(&puzzles);
+        assert_eq!(grid, "1..0\n.0..\n1...\n0...\n");
+        let read = Format::Binary.read("set", &grid).unwrap();
+        assert_eq!(read.entries[0].puzzle, "X..O.O..X...O...");
+    }
+}
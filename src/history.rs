@@ -0,0 +1,139 @@
+use crate::binox::Binox;
+
+/// A board before a change, and whether the change was made automatically.
+#[derive(Clone, Debug)]
+struct Step {
+    board: Binox,
+    automatic: bool,
+}
+
+/// Earlier boards of the current puzzle, for `undo` and `redo`.
+#[derive(Clone, Debug, Default)]
+pub struct History {
+    undo: Vec<Step>,
+    redo: Vec<Step>,
+}
+
+impl History {
+    pub fn new() -> Self {
+        History::default()
+    }
+
+    /// Records the board as it was before a change. Anything undone can no
+    /// longer be redone.
+    pub fn record(&mut self, before: Binox) {
+        self.push(before, false);
+    }
+
+    /// Records the board as it was before cells were filled automatically.
+    /// The automatic change is undone separately from the move that caused it.
+    pub fn record_automatic(&mut self, before: Binox) {
+        self.push(before, true);
+    }
+
+    fn push(&mut self, board: Binox, automatic: bool) {
+        self.undo.push(Step { board, automatic });
+        self.redo.clear();
+    }
+
+    /// Whether the next step to undo was made automatically.
+    pub fn next_undo_is_automatic(&self) -> bool {
+        self.undo.last().is_some_and(|step| step.automatic)
+    }
+
+    /// Records the change from `before` to `after`, if there is one.
+    pub fn record_change(&mut self, before: &Binox, after: &Binox) {
+        if before.as_string() != after.as_string() {
+            self.record(before.clone());
+        }
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Goes back up to `count` steps from `current`, returning the board and
+    /// the number of steps taken.
+    pub fn undo(&mut self, mut current: Binox, count: usize) -> (Binox, usize) {
+        let mut steps = 0;
+        while steps < count {
+            let Some(previous) = self.undo.pop() else {
+                break;
+            };
+            self.redo.push(Step {
+                board: current,
+                automatic: previous.automatic,
+            });
+            current = previous.board;
+            steps += 1;
+        }
+        (current, steps)
+    }
+
+    /// Goes forward up to `count` undone steps from `current`.
+    pub fn redo(&mut self, mut current: Binox, count: usize) -> (Binox, usize) {
+        let mut steps = 0;
+        while steps < count {
+            let Some(next) = self.redo.pop() else {
+                break;
+            };
+            self.undo.push(Step {
+                board: current,
+                automatic: next.automatic,
+            });
+            current = next.board;
+            steps += 1;
+        }
+        (current, steps)
+    }
+
+    /// Forgets every step, for when another puzzle is opened.
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::binox::BinoxCell;
+
+    #[test]
+    fn undo_and_redo() {
+        let mut history = History::new();
+        let mut binox = Binox::new(4).unwrap();
+        for col in 0..3 {
+            let before = binox.clone();
+            binox.set_cell(0, col, BinoxCell::X).unwrap();
+            history.record_change(&before, &binox);
+        }
+        history.record_change(&binox.clone(), &binox);
+        let (binox, steps) = history.undo(binox, 2);
+        assert_eq!(steps, 2);
+        assert_eq!(binox.as_string(), "x...............");
+        let (binox, steps) = history.redo(binox, 5);
+        assert_eq!(steps, 2);
+        assert_eq!(binox.as_string(), "xxx.............");
+        let (mut binox, _) = history.undo(binox, 1);
+        let before = binox.clone();
+        binox.set_cell(3, 3, BinoxCell::O).unwrap();
+        history.record_change(&before, &binox);
+        assert!(!history.can_redo());
+        let (binox, steps) = history.undo(binox, 10);
+        assert_eq!(steps, 3);
+        assert_eq!(binox.as_string(), "................");
+        assert!(!history.can_undo());
+        history.record(binox.clone());
+        history.record_automatic(binox.clone());
+        assert!(history.next_undo_is_automatic());
+        let (binox, _) = history.undo(binox, 1);
+        assert!(!history.next_undo_is_automatic());
+        history.redo(binox, 1);
+        assert!(history.next_undo_is_automatic());
+    }
+}
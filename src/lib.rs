@@ -1,3 +1,67 @@
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+extern crate alloc;
+
+#[cfg(feature = "std")]
+pub mod autosave;
+#[cfg(feature = "std")]
+pub mod batch;
+#[cfg(feature = "std")]
+pub mod bench;
 pub mod binox;
+#[cfg(feature = "interpreter")]
 pub mod binox_interpreter;
+#[cfg(feature = "interpreter")]
+pub mod campaign;
+#[cfg(feature = "interpreter")]
+pub mod challenge;
+#[cfg(feature = "clipboard")]
+pub mod clipboard;
+#[cfg(feature = "std")]
+pub mod completion;
+#[cfg(feature = "interpreter")]
+pub mod config;
+#[cfg(feature = "interpreter")]
+pub mod daily;
+pub mod difficulty;
+#[cfg(feature = "interpreter")]
+pub mod duel;
+pub mod events;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "files")]
+pub mod formats;
+#[cfg(feature = "std")]
+pub mod history;
+#[cfg(feature = "interpreter")]
+pub mod locale;
+#[cfg(feature = "files")]
 pub mod make_files;
+#[cfg(feature = "std")]
+pub mod marks;
+#[cfg(feature = "files")]
+pub mod metrics;
+#[cfg(feature = "interpreter")]
+pub mod output;
+pub mod puzzle;
+#[cfg(feature = "files")]
+pub mod puzzle_pack;
+#[cfg(feature = "std")]
+pub mod puzzle_set;
+#[cfg(feature = "race")]
+pub mod race;
+#[cfg(feature = "rpc")]
+pub mod rpc;
+#[cfg(feature = "server")]
+pub mod server;
+#[cfg(feature = "interpreter")]
+pub mod stats;
+#[cfg(feature = "async")]
+pub mod task;
+pub mod theme;
+#[cfg(feature = "std")]
+pub mod timer;
+#[cfg(feature = "interpreter")]
+pub mod tui;
+#[cfg(feature = "wasm")]
+pub mod wasm;
@@ -0,0 +1,162 @@
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+use crate::config::default_config_path;
+
+/// Translations of the interpreter's messages.
+///
+/// The English text of a message is its key, so an empty table is English
+/// and anything missing from a translation falls back to English. Messages
+/// with changing parts use named placeholders such as `{cell}`, which the
+/// translation must keep. Translation files are TOML tables of quoted keys:
+///
+/// ```toml
+/// "a mistake has been made" = "ein Fehler wurde gemacht"
+/// "cell {cell} is correct" = "Feld {cell} ist richtig"
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Locale {
+    pub name: String,
+    messages: HashMap<String, String>,
+}
+
+impl Locale {
+    pub fn english() -> Self {
+        Locale {
+            name: "en".into(),
+            messages: HashMap::new(),
+        }
+    }
+
+    pub fn parse(name: &str, contents: &str) -> Result<Self, String> {
+        let messages = toml::from_str(contents).map_err(|e| format!("{name}: {e}"))?;
+        Ok(Locale {
+            name: name.into(),
+            messages,
+        })
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let contents = fs::read_to_string(path)
+            .map_err(|_| format!("translation file not found: {}", path.display()))?;
+        let name = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or_default();
+        Locale::parse(name, &contents)
+    }
+
+    pub fn get(&self, text: &str) -> Option<&str> {
+        self.messages.get(text).map(String::as_str)
+    }
+}
+
+static LOCALE: RwLock<Option<Locale>> = RwLock::new(None);
+
+/// Sets the language of the interpreter's messages.
+pub fn set_locale(locale: Locale) {
+    *LOCALE.write().unwrap() = Some(locale);
+}
+
+/// The translation of a message, if the current language has one.
+pub fn lookup(text: &str) -> Option<String> {
+    LOCALE.read().unwrap().as_ref()?.get(text).map(String::from)
+}
+
+/// Translates a message, or returns it unchanged.
+pub fn tr(text: &str) -> String {
+    lookup(text).unwrap_or_else(|| text.into())
+}
+
+/// Translates a message and fills in its placeholders.
+pub fn tr_with(text: &str, values: &[(&str, &str)]) -> String {
+    fill(&tr(text), values)
+}
+
+/// Replaces each `{name}` in the text with its value.
+pub fn fill(text: &str, values: &[(&str, &str)]) -> String {
+    values
+        .iter()
+        .fold(text.into(), |text: String, (name, value)| {
+            text.replace(&format!("{{{name}}}"), value)
+        })
+}
+
+/// The language named by a `LANG` value such as `de_DE.UTF-8`, or `None` for
+/// English and the C locale.
+fn language_from_env(value: Option<&str>) -> Option<String> {
+    let language = value?.split(['_', '.', '@']).next()?.to_lowercase();
+    match language.as_str() {
+        "" | "c" | "posix" | "en" => None,
+        _ => Some(language),
+    }
+}
+
+/// Translations are looked for in a `locales` directory next to the
+/// settings file, such as `~/.config/binox/locales/de.toml`.
+pub fn locale_dir() -> Option<PathBuf> {
+    Some(default_config_path()?.parent()?.join("locales"))
+}
+
+/// Chooses the language from the settings, or else from `LANG`. A language
+/// chosen in the settings must have a translation file, which may also be
+/// given as a path; one taken from `LANG` falls back to English.
+pub fn init(language: Option<&str>) -> Result<(), String> {
+    let configured = language.is_some();
+    let language = match language {
+        Some(language) if language.eq_ignore_ascii_case("en") => None,
+        Some(language) => Some(language.to_string()),
+        None => language_from_env(env::var("LANG").ok().as_deref()),
+    };
+    let Some(language) = language else {
+        set_locale(Locale::english());
+        return Ok(());
+    };
+    let path = if language.ends_with(".toml") {
+        PathBuf::from(&language)
+    } else {
+        locale_dir()
+            .unwrap_or_default()
+            .join(format!("{language}.toml"))
+    };
+    match Locale::load(&path) {
+        Ok(locale) => set_locale(locale),
+        Err(_) if !configured => set_locale(Locale::english()),
+        Err(text) => {
+            set_locale(Locale::english());
+            return Err(text);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn translations() {
+        let locale = Locale::parse(
+            "de",
+            "\"cell {cell} is correct\" = \"Feld {cell} ist richtig\"\n",
+        )
+        .unwrap();
+        assert_eq!(
+            locale.get("cell {cell} is correct"),
+            Some("Feld {cell} ist richtig")
+        );
+        assert_eq!(locale.get("a mistake has been made"), None);
+        assert_eq!(
+            fill("Feld {cell} ist richtig", &[("cell", "a3")]),
+            "Feld a3 ist richtig"
+        );
+        assert!(Locale::parse("de", "not toml").is_err());
+        assert_eq!(language_from_env(Some("de_DE.UTF-8")), Some("de".into()));
+        assert_eq!(language_from_env(Some("en_US.UTF-8")), None);
+        assert_eq!(language_from_env(Some("C")), None);
+        assert_eq!(language_from_env(None), None);
+    }
+}
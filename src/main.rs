@@ -1,12 +1,11 @@
-use binox::binox_interpreter::run_interpreter;
-use binox::make_files::create_default_files;
+use clap::Parser;
+use colored::Colorize;
 
-const MAKE_FILES: bool = false;
+mod cli;
 
 fn main() {
-    if MAKE_FILES {
-        create_default_files();
-    } else {
-        run_interpreter();
+    if let Err(text) = cli::run(cli::Cli::parse()) {
+        eprintln!("{}", text.red().bold());
+        std::process::exit(1);
     }
 }
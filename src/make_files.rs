@@ -1,4 +1,6 @@
 use crate::binox::Binox;
+use crate::difficulty::Difficulty;
+use crate::puzzle_set::{PuzzleEntry, PuzzleSet};
 use std::{fs::File, io::Write};
 
 pub fn create_binox_file(name: &str, size: u8, perfect: bool, extras: usize, amount: u32) {
@@ -11,16 +13,37 @@ pub fn create_binox_file(name: &str, size: u8, perfect: bool, extras: usize, amo
     }
 }
 
+/// Like `create_binox_file`, but writes a v2 `.binoxset` file that keeps the
+/// answer key of every generated puzzle.
+pub fn create_binox_set_file(name: &str, size: u8, perfect: bool, extras: usize, amount: u32) {
+    let mut entries = Vec::new();
+    for _ in 0..amount {
+        let (binox, solution) = Binox::generate_with_solution(size, perfect, extras).unwrap();
+        entries.push(PuzzleEntry {
+            difficulty: name.parse::<Difficulty>().ok(),
+            solution: Some(solution.as_string()),
+            ..PuzzleEntry::new(binox.as_string())
+        });
+    }
+    let set = PuzzleSet::new(format!("{size}x{size}_{name}"), entries);
+    set.save(&format!("{size}x{size}_{name}.binoxset"))
+        .expect("fail to write");
+}
+
+pub const DEFAULT_SIZES: [u8; 7] = [4, 6, 8, 10, 12, 14, 16];
+
 pub fn create_default_files() {
-    let expert = [0, 0, 0, 0, 0, 0, 0];
-    let hard = [1, 1, 2, 3, 4, 5, 6];
-    let medium = [2, 2, 4, 6, 8, 10, 12];
-    let easy = [3, 3, 6, 9, 12, 15, 18];
-    let sizes = [4, 6, 8, 10, 12, 14, 16];
-    for (i, &size) in sizes.iter().enumerate() {
-        create_binox_file("easy", size, true, easy[i], 32);
-        create_binox_file("medium", size, true, medium[i], 32);
-        create_binox_file("hard", size, true, hard[i], 32);
-        create_binox_file("expert", size, true, expert[i], 32);
+    for size in DEFAULT_SIZES {
+        for difficulty in Difficulty::ALL {
+            create_binox_file(difficulty.name(), size, true, difficulty.extras(size), 32);
+        }
+    }
+}
+
+pub fn create_default_set_files() {
+    for size in DEFAULT_SIZES {
+        for difficulty in Difficulty::ALL {
+            create_binox_set_file(difficulty.name(), size, true, difficulty.extras(size), 32);
+        }
     }
 }
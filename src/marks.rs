@@ -0,0 +1,255 @@
+#[cfg(feature = "color")]
+use colored::{ColoredString, Colorize};
+
+use crate::binox::{Binox, BinoxCell};
+use crate::theme::current_theme;
+
+const X_MARK: u8 = 1;
+const O_MARK: u8 = 2;
+const AUTO: u8 = 4;
+
+/// Pencil marks noting which symbols might go in the empty cells, and which
+/// cells were filled automatically.
+#[derive(Clone, Debug)]
+pub struct Marks {
+    size: u8,
+    cells: Vec<u8>,
+    /// Whether the marks are drawn on the board.
+    pub shown: bool,
+}
+
+fn bit(cell: BinoxCell) -> u8 {
+    match cell {
+        BinoxCell::X => X_MARK,
+        BinoxCell::O => O_MARK,
+        BinoxCell::EMPTY => 0,
+    }
+}
+
+impl Marks {
+    pub fn new(size: u8) -> Self {
+        Marks {
+            size,
+            cells: vec![0; size as usize * size as usize],
+            shown: true,
+        }
+    }
+
+    fn index(&self, row: u8, col: u8) -> Result<usize, &'static str> {
+        if row >= self.size || col >= self.size {
+            return Err("index out of bounds.");
+        }
+        Ok(row as usize * self.size as usize + col as usize)
+    }
+
+    pub fn has(&self, row: u8, col: u8, cell: BinoxCell) -> bool {
+        self.index(row, col)
+            .is_ok_and(|i| self.cells[i] & bit(cell) != 0)
+    }
+
+    /// Notes that `cell` might go in an empty cell of `binox`.
+    pub fn add(
+        &mut self,
+        binox: &Binox,
+        row: u8,
+        col: u8,
+        cell: BinoxCell,
+    ) -> Result<(), &'static str> {
+        let i = self.index(row, col)?;
+        if binox.get_cell(row, col)? != BinoxCell::EMPTY {
+            return Err("only empty cells can be marked.");
+        }
+        self.cells[i] |= bit(cell);
+        Ok(())
+    }
+
+    /// Removes one mark, or every mark of the cell if `cell` is `EMPTY`.
+    pub fn remove(&mut self, row: u8, col: u8, cell: BinoxCell) -> Result<(), &'static str> {
+        let i = self.index(row, col)?;
+        self.cells[i] &= match cell {
+            BinoxCell::EMPTY => AUTO,
+            cell => !bit(cell),
+        };
+        Ok(())
+    }
+
+    /// Drops every mark, for when another puzzle is opened.
+    pub fn clear(&mut self, size: u8) {
+        *self = Marks {
+            shown: self.shown,
+            ..Marks::new(size)
+        };
+    }
+
+    /// The marks as one character per cell, row by row: `x` or `o` for one
+    /// mark, `+` for both and `.` for none. `None` if there are no marks.
+    pub fn encode(&self) -> Option<String> {
+        let text: String = self
+            .cells
+            .iter()
+            .map(|&bits| match bits & (X_MARK | O_MARK) {
+                X_MARK => 'x',
+                O_MARK => 'o',
+                0 => '.',
+                _ => '+',
+            })
+            .collect();
+        text.contains(|c| c != '.').then_some(text)
+    }
+
+    /// Replaces the marks with those of a board of `size` written by
+    /// [`Marks::encode`], or with none if there is no text. Text for a
+    /// board of another size is ignored.
+    pub fn restore(&mut self, size: u8, text: Option<&str>) {
+        self.clear(size);
+        let Some(text) = text.filter(|text| text.len() == self.cells.len()) else {
+            return;
+        };
+        for (bits, c) in self.cells.iter_mut().zip(text.chars()) {
+            *bits = match c {
+                'x' => X_MARK,
+                'o' => O_MARK,
+                '+' => X_MARK | O_MARK,
+                _ => 0,
+            };
+        }
+    }
+
+    /// Marks built from `Binox::compute_candidates`, with every possible
+    /// symbol of every empty cell noted.
+    pub fn from_candidates(binox: &Binox) -> Self {
+        let mut marks = Marks::new(binox.size());
+        for (row, col, symbols) in binox.compute_candidates() {
+            for cell in symbols {
+                marks.add(binox, row, col, cell).unwrap();
+            }
+        }
+        marks
+    }
+
+    /// Flags a cell as filled automatically.
+    pub fn set_auto(&mut self, row: u8, col: u8) -> Result<(), &'static str> {
+        let i = self.index(row, col)?;
+        self.cells[i] |= AUTO;
+        Ok(())
+    }
+
+    pub fn is_auto(&self, row: u8, col: u8) -> bool {
+        self.index(row, col)
+            .is_ok_and(|i| self.cells[i] & AUTO != 0)
+    }
+
+    /// Drops the marks of cells that now hold a symbol, and the automatic flag
+    /// of cells that are empty again. Every mark is dropped
+    /// if the board has changed size.
+    pub fn clear_filled(&mut self, binox: &Binox) {
+        if binox.size() != self.size {
+            self.clear(binox.size());
+            return;
+        }
+        for row in 0..self.size {
+            for col in 0..self.size {
+                let i = self.index(row, col).unwrap();
+                if binox.get_cell(row, col).unwrap() == BinoxCell::EMPTY {
+                    self.cells[i] &= !AUTO;
+                } else {
+                    self.cells[i] &= AUTO;
+                }
+            }
+        }
+    }
+
+    /// Every marked cell as `(row, col, symbols)`, for JSON output.
+    pub fn list(&self) -> Vec<(u8, u8, String)> {
+        let theme = current_theme();
+        let mut result = Vec::new();
+        for row in 0..self.size {
+            for col in 0..self.size {
+                let symbols: String = [BinoxCell::X, BinoxCell::O]
+                    .into_iter()
+                    .filter(|&cell| self.has(row, col, cell))
+                    .map(|cell| theme.symbol(cell))
+                    .collect();
+                if !symbols.is_empty() {
+                    result.push((row, col, symbols));
+                }
+            }
+        }
+        result
+    }
+
+    /// How a cell holding `cell` is drawn: an automatically filled symbol in
+    /// the theme's auto style, and for empty cells a dimmed symbol for one mark
+    /// or `*` for both. `None` if the cell is drawn as usual.
+    #[cfg(feature = "color")]
+    pub fn glyph(&self, row: u8, col: u8, cell: BinoxCell) -> Option<ColoredString> {
+        let theme = current_theme();
+        if cell != BinoxCell::EMPTY {
+            return self
+                .is_auto(row, col)
+                .then(|| theme.styled(theme.paint(cell), &theme.auto_style));
+        }
+        if !self.shown {
+            return None;
+        }
+        let symbol = |cell| theme.symbol(cell).to_lowercase().to_string();
+        match (
+            self.has(row, col, BinoxCell::X),
+            self.has(row, col, BinoxCell::O),
+        ) {
+            (true, true) => Some("*".dimmed()),
+            (true, false) => Some(symbol(BinoxCell::X).color(theme.x_color()).dimmed()),
+            (false, true) => Some(symbol(BinoxCell::O).color(theme.o_color()).dimmed()),
+            (false, false) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn pencil_marks() {
+        let mut binox = Binox::new_from_string("X...............".into());
+        let mut marks = Marks::new(4);
+        assert!(marks.add(&binox, 0, 0, BinoxCell::O).is_err());
+        marks.add(&binox, 0, 1, BinoxCell::O).unwrap();
+        marks.add(&binox, 0, 1, BinoxCell::X).unwrap();
+        marks.add(&binox, 1, 1, BinoxCell::X).unwrap();
+        assert!(marks.has(0, 1, BinoxCell::X));
+        marks.remove(0, 1, BinoxCell::X).unwrap();
+        assert!(!marks.has(0, 1, BinoxCell::X));
+        assert!(marks.has(0, 1, BinoxCell::O));
+        binox.set_cell(0, 1, BinoxCell::X).unwrap();
+        marks.clear_filled(&binox);
+        assert!(!marks.has(0, 1, BinoxCell::O));
+        assert_eq!(marks.list(), vec![(1, 1, "X".into())]);
+        marks.set_auto(0, 1).unwrap();
+        marks.clear_filled(&binox);
+        assert!(marks.is_auto(0, 1));
+        binox.set_cell(0, 1, BinoxCell::EMPTY).unwrap();
+        marks.clear_filled(&binox);
+        assert!(!marks.is_auto(0, 1));
+        marks.clear_filled(&Binox::new(6).unwrap());
+        assert!(marks.list().is_empty());
+    }
+
+    #[test]
+    fn encoding() {
+        let binox = Binox::new(4).unwrap();
+        let mut marks = Marks::new(4);
+        assert_eq!(marks.encode(), None);
+        marks.add(&binox, 0, 2, BinoxCell::X).unwrap();
+        marks.add(&binox, 1, 0, BinoxCell::X).unwrap();
+        marks.add(&binox, 1, 0, BinoxCell::O).unwrap();
+        marks.set_auto(3, 3).unwrap();
+        let text = marks.encode().unwrap();
+        assert_eq!(text, "..x.+...........");
+        let mut restored = Marks::new(6);
+        restored.restore(4, Some(&text));
+        assert_eq!(restored.list(), marks.list());
+        restored.restore(6, Some(&text));
+        assert!(restored.list().is_empty());
+    }
+}
@@ -0,0 +1,142 @@
+use std::sync::atomic::AtomicBool;
+
+use serde::Serialize;
+
+use crate::binox::Binox;
+use crate::difficulty::Difficulty;
+use crate::formats::csv_field;
+use crate::puzzle::{Puzzle, SearchStats, Solution};
+use crate::puzzle_set::PuzzleSet;
+
+const CSV_HEADER: &str =
+    "index,puzzle,size,clues,difficulty,score,solved_logically,techniques,solutions,nodes,seed";
+
+/// What grading and solving one puzzle of a file found, for analysing and
+/// curating puzzle collections.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct PuzzleMetrics {
+    pub index: usize,
+    pub puzzle: String,
+    pub size: u8,
+    pub clues: usize,
+    pub difficulty: Difficulty,
+    pub score: f64,
+    pub solved_logically: bool,
+    /// How many cells each technique was used for, hardest last.
+    pub techniques: Vec<(String, usize)>,
+    /// The number of solutions, counting no further than 2.
+    pub solutions: usize,
+    /// How many boards the solver looked at to prove the solution unique.
+    pub nodes: usize,
+    pub seed: Option<u64>,
+}
+
+impl PuzzleMetrics {
+    pub fn measure(index: usize, puzzle: &str, seed: Option<u64>) -> Self {
+        let mut binox = Binox::new_from_string(puzzle.into());
+        binox.reset();
+        let rating = binox.rate();
+        let mut stats = SearchStats::default();
+        let solution = binox.search_with_stats(true, &AtomicBool::new(false), &mut stats);
+        PuzzleMetrics {
+            index,
+            puzzle: puzzle.into(),
+            size: binox.size(),
+            clues: rating.clues,
+            difficulty: rating.difficulty,
+            score: rating.score,
+            solved_logically: rating.solved_logically,
+            techniques: rating
+                .techniques
+                .iter()
+                .map(|(technique, count)| (technique.name().into(), *count))
+                .collect(),
+            solutions: match solution {
+                Some(Solution::One(_)) => 1,
+                Some(Solution::Multiple(..)) => 2,
+                _ => 0,
+            },
+            nodes: stats.nodes,
+            seed,
+        }
+    }
+}
+
+/// The metrics of every puzzle in a set, in order.
+pub fn measure_set(puzzles: &PuzzleSet) -> Vec<PuzzleMetrics> {
+    puzzles
+        .entries
+        .iter()
+        .enumerate()
+        .map(|(i, entry)| PuzzleMetrics::measure(i, &entry.puzzle, entry.seed))
+        .collect()
+}
+
+/// A CSV table with a header row and one row per puzzle. The techniques are
+/// listed in one field, as in `pair x12; count x3`.
+pub fn to_csv(metrics: &[PuzzleMetrics]) -> String {
+    let mut result = format!("{CSV_HEADER}\n");
+    for m in metrics {
+        let techniques: Vec<String> = m
+            .techniques
+            .iter()
+            .map(|(technique, count)| format!("{technique} x{count}"))
+            .collect();
+        let fields = [
+            m.index.to_string(),
+            csv_field(&m.puzzle),
+            m.size.to_string(),
+            m.clues.to_string(),
+            m.difficulty.name().into(),
+            format!("{:.3}", m.score),
+            m.solved_logically.to_string(),
+            csv_field(&techniques.join("; ")),
+            m.solutions.to_string(),
+            m.nodes.to_string(),
+            m.seed.map_or(String::new(), |s| s.to_string()),
+        ];
+        result.push_str(&fields.join(","));
+        result.push('\n');
+    }
+    result
+}
+
+/// A JSON array with an object per puzzle, in which the techniques are
+/// `[name, count]` pairs.
+pub fn to_json(metrics: &[PuzzleMetrics]) -> String {
+    serde_json::to_string_pretty(metrics).unwrap() + "\n"
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn report() {
+        let mut puzzles = PuzzleSet::from_lines(
+            "set".into(),
+            "X......O...X..XX....X..O....X..XX...\n................\n",
+            None,
+        );
+        puzzles.entries[0].seed = Some(5);
+        let metrics = measure_set(&puzzles);
+        assert_eq!(metrics[0].clues, 10);
+        assert_eq!(metrics[0].solutions, 1);
+        assert_eq!(metrics[0].seed, Some(5));
+        assert!(metrics[0].solved_logically);
+        assert_eq!(metrics[1].solutions, 2);
+        assert!(metrics[1].nodes > metrics[0].nodes);
+
+        let csv = to_csv(&metrics);
+        assert_eq!(csv.lines().next(), Some(CSV_HEADER));
+        assert!(csv
+            .lines()
+            .nth(1)
+            .unwrap()
+            .starts_with("0,X......O...X..XX....X..O....X..XX...,6,10,"));
+        assert!(csv.lines().nth(1).unwrap().ends_with(",1,2,5"));
+        let json: serde_json::Value = serde_json::from_str(&to_json(&metrics)).unwrap();
+        assert_eq!(json[0]["seed"], 5);
+        assert_eq!(json[1]["seed"], serde_json::Value::Null);
+    }
+}
@@ -0,0 +1,947 @@
+use colored::Colorize;
+use serde_json::{json, Value};
+
+use std::cell::RefCell;
+use std::env;
+use std::io::{self, IsTerminal};
+use std::path::Path;
+use std::time::Duration;
+
+use crate::binox::logic::{Deduction, Rating, Technique};
+use crate::binox::{column_name, Binox, BinoxCell};
+use crate::campaign::{Campaign, Level};
+use crate::completion::Completed;
+use crate::config::Config;
+use crate::difficulty::Difficulty;
+use crate::locale::{self, tr, tr_with};
+use crate::marks::Marks;
+use crate::puzzle_set::{MetaField, PuzzleEntry, PuzzleSet};
+use crate::stats::{Stats, Summary};
+use crate::theme::{self, current_theme, BoardStyle};
+use crate::timer::format_duration;
+
+/// How boards are shown in text mode.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Rendering {
+    /// The board drawing in the theme's colors and style.
+    #[default]
+    Color,
+    /// An ASCII drawing without color codes.
+    Plain,
+    /// A one-line summary for screen readers; the `read` command describes the cells.
+    Spoken,
+    /// Whichever of the colored and plain drawings suits the terminal, as
+    /// chosen by [`Terminal::choose`].
+    Auto,
+}
+
+/// What the terminal on stdout can show.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Terminal {
+    /// ANSI escape codes for colors and bold text.
+    pub ansi: bool,
+    /// Characters beyond ASCII, such as box drawing.
+    pub unicode: bool,
+    /// The width in columns, if it is known.
+    pub width: Option<u16>,
+}
+
+impl Terminal {
+    /// Looks at stdout, `TERM`, the locale and the window size.
+    pub fn detect() -> Self {
+        let var = |name| env::var(name).ok().filter(|value| !value.is_empty());
+        let locale = var("LC_ALL")
+            .or_else(|| var("LC_CTYPE"))
+            .or_else(|| var("LANG"));
+        let width = ratatui::crossterm::terminal::size()
+            .ok()
+            .map(|(width, _)| width)
+            .or_else(|| var("COLUMNS")?.parse().ok());
+        let ansi = io::stdout().is_terminal()
+            && theme::color_enabled()
+            && var("TERM").as_deref() != Some("dumb");
+        Terminal {
+            ansi,
+            unicode: locale.is_some_and(|locale| {
+                let locale = locale.to_lowercase();
+                locale.contains("utf-8") || locale.contains("utf8")
+            }),
+            width,
+        }
+    }
+
+    /// The drawing for a board of `size` cells a side in the theme's `style`:
+    /// plain ASCII without ANSI codes, the classic grid without Unicode, and
+    /// the narrower boxed grid when the classic one does not fit.
+    pub fn choose(&self, style: BoardStyle, size: u8) -> (Rendering, BoardStyle) {
+        let classic_width = 4 * (size as u16 + 1);
+        if !self.ansi {
+            (Rendering::Plain, style)
+        } else if !self.unicode {
+            (Rendering::Color, BoardStyle::Classic)
+        } else if self.width.is_some_and(|width| width < classic_width) {
+            (Rendering::Color, BoardStyle::Boxed)
+        } else {
+            (Rendering::Color, style)
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutputMode {
+    /// Colored prose and board drawings for people.
+    #[default]
+    Text,
+    /// One JSON object per line for programs.
+    Json,
+}
+
+/// Writes interpreter responses either as colored text or as JSON lines.
+///
+/// Every JSON object has a `type` field: `board`, `info`, `success`, `warning`,
+/// `error`, `help`, `verify`, `mistakes`, `config`, `list` or `puzzle_info`.
+#[derive(Default)]
+pub struct Output {
+    mode: OutputMode,
+    rendering: Rendering,
+    /// Detected when the rendering is first set to [`Rendering::Auto`].
+    terminal: Option<Terminal>,
+    tallies: bool,
+    /// Cells changed by the most recent move, set apart on the board.
+    recent: Vec<(u8, u8)>,
+    /// Cells of the latest hint, highlighted on the next board only.
+    hinted: RefCell<Vec<(u8, u8)>>,
+    /// Title of the current puzzle, shown above its board.
+    title: Option<String>,
+}
+
+/// "row 3" or "column b".
+fn line_name(is_row: bool, index: u8) -> String {
+    if is_row {
+        format!("row {index}")
+    } else {
+        format!("column {}", column_name(index))
+    }
+}
+
+/// The symbols of one row or column separated by spaces, with `.` for empty
+/// cells.
+fn line_symbols(binox: &Binox, is_row: bool, index: u8) -> String {
+    let theme = current_theme();
+    (0..binox.size())
+        .map(|i| {
+            let (row, col) = if is_row { (index, i) } else { (i, index) };
+            match binox.get_cell(row, col).unwrap() {
+                BinoxCell::EMPTY => ".".into(),
+                cell => theme.symbol(cell).to_string(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+impl Output {
+    pub fn new(mode: OutputMode) -> Self {
+        if mode == OutputMode::Json {
+            colored::control::set_override(false);
+        }
+        Output {
+            mode,
+            rendering: Rendering::Color,
+            terminal: None,
+            tallies: false,
+            recent: Vec::new(),
+            hinted: RefCell::default(),
+            title: None,
+        }
+    }
+
+    pub fn mode(&self) -> OutputMode {
+        self.mode
+    }
+
+    /// Switches how boards are shown. Plain and spoken output have no color codes.
+    pub fn set_rendering(&mut self, rendering: Rendering) {
+        self.rendering = rendering;
+        if rendering == Rendering::Auto && self.terminal.is_none() {
+            self.terminal = Some(Terminal::detect());
+        }
+        if matches!(rendering, Rendering::Plain | Rendering::Spoken)
+            || self.mode == OutputMode::Json
+        {
+            colored::control::set_override(false);
+        } else {
+            theme::init_color();
+        }
+    }
+
+    fn emit(&self, value: Value) {
+        println!("{value}");
+    }
+
+    fn message(&self, kind: &str, text: &str, styled: impl FnOnce(&str) -> String) {
+        let text = &locale::tr(text);
+        match self.mode {
+            OutputMode::Text => println!("{}", styled(text)),
+            OutputMode::Json => self.emit(json!({"type": kind, "text": text})),
+        }
+    }
+
+    /// Shows the X and O counts of each row and column next to boards.
+    pub fn set_tallies(&mut self, tallies: bool) {
+        self.tallies = tallies;
+    }
+
+    /// Sets apart the cells changed by the most recent move. Changes to more
+    /// cells than a row holds are not set apart.
+    pub fn set_recent(&mut self, before: &Binox, after: &Binox) {
+        self.recent = before.changed_cells(after);
+        if self.recent.len() > after.size() as usize {
+            self.recent.clear();
+        }
+    }
+
+    pub fn clear_recent(&mut self) {
+        self.recent.clear();
+    }
+
+    /// Names the puzzle whose boards are shown next.
+    pub fn set_title(&mut self, title: Option<&str>) {
+        self.title = title.map(String::from);
+    }
+
+    /// Highlights the cells of a hint the next time a board is shown.
+    pub fn set_hinted(&self, cells: Vec<(u8, u8)>) {
+        *self.hinted.borrow_mut() = cells;
+    }
+
+    fn board_text(&self, binox: &Binox, marks: Option<&Marks>) -> String {
+        let text = self.drawing(binox, marks);
+        if self.tallies && self.rendering != Rendering::Spoken {
+            binox.with_tallies(&text)
+        } else {
+            text
+        }
+    }
+
+    /// The rendering and board style that boards of `size` cells a side are
+    /// actually drawn in, with [`Rendering::Auto`] resolved for the terminal.
+    fn resolved(&self, size: u8) -> (Rendering, BoardStyle) {
+        let style = current_theme().style;
+        match (self.rendering, self.terminal) {
+            (Rendering::Auto, Some(terminal)) => terminal.choose(style, size),
+            (Rendering::Auto, None) => (Rendering::Color, style),
+            (rendering, _) => (rendering, style),
+        }
+    }
+
+    fn drawing(&self, binox: &Binox, marks: Option<&Marks>) -> String {
+        match self.resolved(binox.size()) {
+            (Rendering::Plain, _) => binox.render_plain(),
+            (Rendering::Spoken, _) => binox.describe_board(),
+            (Rendering::Color, style)
+                if marks.is_some()
+                    || !self.recent.is_empty()
+                    || !self.hinted.borrow().is_empty() =>
+            {
+                let empty = Marks::new(binox.size());
+                let hinted = self.hinted.take();
+                binox.render_marked(marks.unwrap_or(&empty), &self.recent, &hinted, style)
+            }
+            (Rendering::Color, BoardStyle::Classic) => binox.to_string(),
+            (Rendering::Color, BoardStyle::Boxed) => binox.render_boxed(),
+            (Rendering::Auto, _) => unreachable!("the automatic rendering is always resolved"),
+        }
+    }
+
+    /// Shows what a change did to the board without printing all of it: the
+    /// changed row or column, or a list of the changed cells. Large changes
+    /// and new boards are printed in full.
+    pub fn changes(&self, before: &Binox, after: &Binox, marks: Option<&Marks>) {
+        if before.size() != after.size() {
+            return self.board_with_marks(after, marks);
+        }
+        let cells = before.changed_cells(after);
+        if cells.len() > after.size() as usize {
+            return self.board_with_marks(after, marks);
+        }
+        if self.mode == OutputMode::Json {
+            let cells: Vec<Value> = cells
+                .iter()
+                .map(|&(row, col)| {
+                    let symbol = char::from(after.get_cell(row, col).unwrap());
+                    json!({"row": row, "col": col, "symbol": symbol.to_string().trim()})
+                })
+                .collect();
+            self.emit(json!({"type": "changes", "cells": cells}));
+            return;
+        }
+        let line = match cells.as_slice() {
+            [] => None,
+            [(row, _), rest @ ..] if rest.iter().all(|(r, _)| r == row) => Some((true, *row)),
+            [(_, col), rest @ ..] if rest.iter().all(|(_, c)| c == col) => Some((false, *col)),
+            _ => None,
+        };
+        match (line, cells.len()) {
+            (_, 0) => self.info("nothing changed"),
+            (Some((is_row, index)), _) => self.info(&format!(
+                "{}: {}",
+                tr(&line_name(is_row, index)),
+                line_symbols(after, is_row, index)
+            )),
+            (None, count) => {
+                let names: Vec<String> = cells
+                    .iter()
+                    .map(|&(row, col)| format!("{}{row}", column_name(col)))
+                    .collect();
+                self.info(&tr_with(
+                    "{count} cells changed: {cells}",
+                    &[("count", &count.to_string()), ("cells", &names.join(", "))],
+                ))
+            }
+        }
+    }
+
+    /// Shows the solution next to the board, or one line of it if `line` is
+    /// given as `(is_row, index)`.
+    pub fn solution(&self, binox: &Binox, solution: &Binox, line: Option<(bool, u8)>) {
+        let line_text = |is_row, index| line_symbols(solution, is_row, index);
+        match (self.mode, line) {
+            (OutputMode::Json, Some((is_row, index))) => self.emit(json!({
+                "type": "solution",
+                "line": line_name(is_row, index),
+                "symbols": line_text(is_row, index).replace(' ', ""),
+            })),
+            (OutputMode::Json, None) => self.emit(json!({
+                "type": "solution",
+                "puzzle": solution.as_string(),
+            })),
+            (OutputMode::Text, Some((is_row, index))) => self.info(&tr_with(
+                "solution of {line}: {symbols}",
+                &[
+                    ("line", &tr(&line_name(is_row, index))),
+                    ("symbols", &line_text(is_row, index)),
+                ],
+            )),
+            (OutputMode::Text, None) if self.rendering == Rendering::Spoken => {
+                for row in 0..solution.size() {
+                    self.info(&tr_with(
+                        "solution of {line}: {symbols}",
+                        &[
+                            ("line", &tr(&line_name(true, row))),
+                            ("symbols", &line_text(true, row)),
+                        ],
+                    ));
+                }
+            }
+            (OutputMode::Text, None) => {
+                let board = self.board_text(binox, None);
+                let width = board.lines().next().map_or(0, |l| l.chars().count());
+                println!("{:<width$}    solution", "your board");
+                for (left, right) in board.lines().zip(self.board_text(solution, None).lines()) {
+                    println!("{left}    {right}");
+                }
+            }
+        }
+    }
+
+    pub fn board(&self, binox: &Binox) {
+        self.board_with_marks(binox, None);
+    }
+
+    /// Like `board`, but also shows pencil marks if they are turned on.
+    pub fn board_with_marks(&self, binox: &Binox, marks: Option<&Marks>) {
+        match self.mode {
+            OutputMode::Text => {
+                if let Some(title) = &self.title {
+                    println!("{}", title.bold());
+                }
+                println!("{}", self.board_text(binox, marks));
+            }
+            OutputMode::Json => self.emit(json!({
+                "type": "board",
+                "title": self.title,
+                "size": binox.size(),
+                "puzzle": binox.as_string(),
+                "full": binox.is_full(),
+                "valid": binox.is_valid(),
+                "solved": binox.is_solved(),
+                "marks": marks.filter(|marks| marks.shown).map(|marks| {
+                    marks
+                        .list()
+                        .into_iter()
+                        .map(|(row, col, symbols)| json!({"row": row, "col": col, "symbols": symbols}))
+                        .collect::<Vec<_>>()
+                }),
+            })),
+        }
+    }
+
+    /// Prints a puzzle string on its own, so it can be copied.
+    pub fn puzzle(&self, puzzle: &str) {
+        self.message("puzzle", puzzle, |t| t.into());
+    }
+
+    pub fn info(&self, text: &str) {
+        self.message("info", text, |t| t.into());
+    }
+
+    pub fn success(&self, text: &str) {
+        self.message("success", text, |t| t.green().bold().to_string());
+    }
+
+    pub fn warning(&self, text: &str) {
+        self.message("warning", text, |t| t.yellow().bold().to_string());
+    }
+
+    pub fn error(&self, text: &str) {
+        self.message("error", text, |t| t.red().bold().to_string());
+    }
+
+    pub fn help(&self, text: &str) {
+        self.message("help", text, |t| t.into());
+    }
+
+    /// Reports whether the board is solved, unfinished without mistakes, or broken.
+    pub fn verify(&self, binox: &Binox) {
+        let status = match (binox.is_full(), binox.is_valid()) {
+            (true, true) => "solved",
+            (false, true) => "in_progress",
+            (_, false) => "mistake",
+        };
+        match (self.mode, status) {
+            (OutputMode::Json, _) => self.emit(json!({"type": "verify", "status": status})),
+            (_, "solved") => self.success("the puzzle has been solved"),
+            (_, "in_progress") => self.warning("no mistakes so far"),
+            _ => self.error("a mistake has been made"),
+        }
+    }
+
+    /// Lists the solving techniques from simplest to hardest.
+    pub fn techniques(&self) {
+        if self.mode == OutputMode::Json {
+            let techniques: Vec<Value> = Technique::ALL
+                .iter()
+                .map(|t| json!({"technique": t.name(), "weight": t.weight()}))
+                .collect();
+            self.emit(json!({"type": "techniques", "techniques": techniques}));
+            return;
+        }
+        for technique in Technique::ALL {
+            println!(
+                "{}: {}",
+                tr(technique.name()).bold(),
+                tr(technique.description())
+            );
+        }
+        self.info("type `explain (technique)` to see an example");
+    }
+
+    /// Describes a technique and shows a board where it finds the next cell,
+    /// with that cell and the cells that explain it highlighted.
+    pub fn technique(&self, technique: Technique, example: Option<&(Binox, Deduction)>) {
+        if self.mode == OutputMode::Json {
+            self.emit(json!({
+                "type": "technique",
+                "technique": technique.name(),
+                "description": technique.description(),
+                "example": example.map(|(board, deduction)| json!({
+                    "board": board.as_string(),
+                    "cell": {"row": deduction.row, "col": deduction.col},
+                    "symbol": char::from(deduction.cell).to_string(),
+                    "reasons": deduction
+                        .reasons
+                        .iter()
+                        .map(|&(row, col)| json!({"row": row, "col": col}))
+                        .collect::<Vec<Value>>(),
+                    "explanation": deduction.explanation(),
+                })),
+            }));
+            return;
+        }
+        println!(
+            "{}: {}",
+            tr(technique.name()).bold(),
+            tr(technique.description())
+        );
+        let Some((board, deduction)) = example else {
+            return;
+        };
+        let mut cells = vec![(deduction.row, deduction.col)];
+        cells.extend(&deduction.reasons);
+        self.set_hinted(cells);
+        println!("{}", self.board_text(board, None));
+        self.info(&tr_with(
+            "for example, cell {cell} is {symbol}: {reason}",
+            &[
+                (
+                    "cell",
+                    &format!("{}{}", column_name(deduction.col), deduction.row),
+                ),
+                (
+                    "symbol",
+                    &current_theme().symbol(deduction.cell).to_string(),
+                ),
+                ("reason", &deduction.explanation()),
+            ],
+        ));
+    }
+
+    /// Shows the grade of a puzzle, the techniques it needs and its clue count.
+    pub fn rating(&self, rating: &Rating) {
+        let techniques: Vec<String> = rating
+            .techniques
+            .iter()
+            .map(|(technique, count)| format!("{technique} x{count}"))
+            .collect();
+        if self.mode == OutputMode::Json {
+            let techniques: Vec<Value> = rating
+                .techniques
+                .iter()
+                .map(|(technique, count)| json!({"technique": technique.name(), "cells": count}))
+                .collect();
+            self.emit(json!({
+                "type": "rating",
+                "difficulty": rating.difficulty.name(),
+                "score": rating.score,
+                "solved_logically": rating.solved_logically,
+                "techniques": techniques,
+                "clues": rating.clues,
+                "cells": rating.cells,
+            }));
+            return;
+        }
+        let difficulty = if rating.solved_logically {
+            "difficulty: {difficulty} (score {score})"
+        } else {
+            "difficulty: {difficulty} (score {score}), needs guessing"
+        };
+        self.info(&tr_with(
+            difficulty,
+            &[
+                ("difficulty", &tr(rating.difficulty.name())),
+                ("score", &format!("{:.2}", rating.score)),
+            ],
+        ));
+        if !techniques.is_empty() {
+            self.info(&tr_with(
+                "techniques: {techniques}",
+                &[("techniques", &techniques.join(", "))],
+            ));
+        }
+        self.info(&tr_with(
+            "clues: {clues} of {cells} cells ({percent}%)",
+            &[
+                ("clues", &rating.clues.to_string()),
+                ("cells", &rating.cells.to_string()),
+                (
+                    "percent",
+                    &format!("{:.0}", 100.0 * rating.clues as f64 / rating.cells as f64),
+                ),
+            ],
+        ));
+    }
+
+    /// Summarizes every puzzle solved so far.
+    pub fn stats(&self, stats: &Stats, today: u64) {
+        let summary = |s: &Summary| {
+            json!({
+                "solved": s.solved,
+                "average_seconds": s.average.as_secs(),
+                "best_seconds": s.best.as_secs(),
+            })
+        };
+        if self.mode == OutputMode::Json {
+            let sizes: Vec<Value> = stats
+                .by_size()
+                .iter()
+                .map(|(size, s)| json!({"size": size, "summary": summary(s)}))
+                .collect();
+            let difficulties: Vec<Value> = stats
+                .by_difficulty()
+                .iter()
+                .map(|(difficulty, s)| json!({"difficulty": difficulty.name(), "summary": summary(s)}))
+                .collect();
+            self.emit(json!({
+                "type": "stats",
+                "total": stats.total().as_ref().map(summary),
+                "sizes": sizes,
+                "difficulties": difficulties,
+                "hints": stats.hints(),
+                "hint_points": stats.hint_points(),
+                "streak": stats.streak(today),
+                "best_challenge": stats.best_challenge().map(|run| run.score),
+            }));
+            return;
+        }
+        let Some(total) = stats.total() else {
+            self.info("no puzzles solved yet");
+            return;
+        };
+        let line = |name: String, s: &Summary| {
+            format!(
+                "{name:<10} {:>4} solved, average {:>7}, best {:>7}",
+                s.solved,
+                format_duration(s.average),
+                format_duration(s.best)
+            )
+        };
+        self.info(&line("total".into(), &total));
+        for (size, s) in stats.by_size() {
+            self.info(&line(format!("{size}x{size}"), &s));
+        }
+        for (difficulty, s) in stats.by_difficulty() {
+            self.info(&line(difficulty.to_string(), &s));
+        }
+        self.info(&tr_with(
+            "hints used: {hints} ({average} per puzzle), costing {points} hint points",
+            &[
+                ("hints", &stats.hints().to_string()),
+                ("points", &stats.hint_points().to_string()),
+                (
+                    "average",
+                    &format!("{:.1}", stats.hints() as f64 / total.solved as f64),
+                ),
+            ],
+        ));
+        match stats.streak(today) {
+            1 => self.info("current streak: 1 day"),
+            days => self.info(&tr_with(
+                "current streak: {days} days",
+                &[("days", &days.to_string())],
+            )),
+        }
+        if let Some(run) = stats.best_challenge() {
+            self.info(&tr_with(
+                "best challenge: {score} points, {solved} solved in {time} on {size}x{size} {difficulty}",
+                &[
+                    ("score", &run.score.to_string()),
+                    ("solved", &run.solved.to_string()),
+                    ("time", &format_duration(Duration::from_secs(run.seconds))),
+                    ("size", &run.size.to_string()),
+                    ("difficulty", &tr(run.difficulty.name())),
+                ],
+            ));
+        }
+    }
+
+    /// Reports how many solutions the board has, up to `limit`.
+    pub fn solution_count(&self, count: usize, limit: usize) {
+        if self.mode == OutputMode::Json {
+            self.emit(json!({"type": "count", "solutions": count, "capped": count >= limit}));
+            return;
+        }
+        match count {
+            0 => self.error("the board has no solutions"),
+            1 => self.success("the board has exactly one solution"),
+            _ if count >= limit => self.warning(&tr_with(
+                "the board has at least {count} solutions",
+                &[("count", &count.to_string())],
+            )),
+            _ => self.warning(&tr_with(
+                "the board has {count} solutions",
+                &[("count", &count.to_string())],
+            )),
+        }
+    }
+
+    /// Shows the cells that differ from the solution without revealing it.
+    pub fn mistakes(&self, binox: &Binox, cells: &[(u8, u8)]) {
+        if self.mode == OutputMode::Json {
+            let cells: Vec<Value> = cells
+                .iter()
+                .map(|&(row, col)| json!({"row": row, "col": col}))
+                .collect();
+            self.emit(json!({"type": "mistakes", "cells": cells}));
+            return;
+        }
+        if cells.is_empty() {
+            self.success("every entered cell matches the solution");
+            return;
+        }
+        if let (Rendering::Color, style) = self.resolved(binox.size()) {
+            println!("{}", binox.render_highlighted(cells, style));
+        }
+        let names: Vec<String> = cells
+            .iter()
+            .map(|&(row, col)| format!("{}{row}", column_name(col)))
+            .collect();
+        let text = if cells.len() == 1 {
+            "{count} cell differs from the solution: {cells}"
+        } else {
+            "{count} cells differ from the solution: {cells}"
+        };
+        self.warning(&tr_with(
+            text,
+            &[
+                ("count", &cells.len().to_string()),
+                ("cells", &names.join(", ")),
+            ],
+        ));
+    }
+
+    pub fn config(&self, config: &Config, path: Option<&Path>) {
+        let path = path.map(|p| p.display().to_string());
+        match self.mode {
+            OutputMode::Text => {
+                match &path {
+                    Some(path) => println!("{} {path}", "settings from".bold()),
+                    None => println!("{}", "default settings".bold()),
+                }
+                print!("{}", config.to_toml());
+            }
+            OutputMode::Json => {
+                self.emit(json!({"type": "config", "path": path, "config": config}))
+            }
+        }
+    }
+
+    /// Shows the campaign as a table of sizes and difficulties, marking the
+    /// cleared levels and the next one to play.
+    pub fn campaign(&self, campaign: &Campaign) {
+        let state = |level: Level| {
+            if campaign.is_cleared(level) {
+                "cleared"
+            } else if campaign.is_unlocked(level) {
+                "next"
+            } else {
+                "locked"
+            }
+        };
+        if self.mode == OutputMode::Json {
+            let levels: Vec<Value> = Level::all()
+                .map(|level| {
+                    json!({
+                        "size": level.size,
+                        "difficulty": level.difficulty.name(),
+                        "state": state(level),
+                    })
+                })
+                .collect();
+            self.emit(json!({"type": "campaign", "levels": levels}));
+            return;
+        }
+        self.info(&tr_with(
+            "campaign: {cleared} of {total} levels cleared",
+            &[
+                ("cleared", &campaign.cleared().to_string()),
+                ("total", &Level::all().count().to_string()),
+            ],
+        ));
+        let mut header = format!("{:<7}", "");
+        for difficulty in Difficulty::ALL {
+            header.push_str(&format!(" {:<8}", tr(difficulty.name())));
+        }
+        println!("{}", header.trim_end());
+        let levels: Vec<Level> = Level::all().collect();
+        for row in levels.chunks(Difficulty::ALL.len()) {
+            let mut line = format!("{:<7}", format!("{0}x{0}", row[0].size));
+            for &level in row {
+                line.push_str(&format!(" {:<8}", tr(state(level))));
+            }
+            if campaign.next().is_some_and(|next| next.size == row[0].size) {
+                println!("{}", current_theme().highlight(line.trim_end()));
+            } else {
+                println!("{}", line.trim_end());
+            }
+        }
+    }
+
+    /// The author, source, creation date and notes of a puzzle.
+    pub fn meta(&self, entry: &PuzzleEntry) {
+        if self.mode == OutputMode::Json {
+            let mut value = json!({"type": "meta"});
+            for field in MetaField::ALL {
+                value[field.name()] = json!(entry.meta(field));
+            }
+            self.emit(value);
+            return;
+        }
+        let mut any = false;
+        for field in MetaField::ALL {
+            if let Some(text) = entry.meta(field) {
+                println!("{}: {text}", tr(field.name()));
+                any = true;
+            }
+        }
+        if !any {
+            self.info("this puzzle has no author, source, date or notes");
+        }
+    }
+
+    /// Describes the selected puzzle of a set, and for a generated one, the
+    /// command that generates it again.
+    pub fn puzzle_info(&self, puzzles: &PuzzleSet, size: u8) {
+        let entry = puzzles.current();
+        let command = entry
+            .seed
+            .map(|seed| entry.options.unwrap_or_default().command(size, seed));
+        if self.mode == OutputMode::Json {
+            self.emit(json!({
+                "type": "puzzle_info",
+                "number": puzzles.selected(),
+                "size": size,
+                "puzzle": entry,
+                "command": command,
+            }));
+            return;
+        }
+        let mut line = tr_with(
+            "puzzle {number} ({total} in the collection), {size}x{size}",
+            &[
+                ("number", &puzzles.selected().to_string()),
+                ("total", &puzzles.len().to_string()),
+                ("size", &size.to_string()),
+            ],
+        );
+        if let Some(difficulty) = entry.difficulty {
+            line = format!("{line}, {}", tr(difficulty.name()));
+        }
+        if let Some(title) = &entry.title {
+            line = format!("{line}: {title}");
+        }
+        self.info(&line);
+        match (entry.seed, &command) {
+            (Some(seed), Some(command)) => {
+                let options = entry.options.map_or(String::new(), |o| format!(", {o}"));
+                self.info(&tr_with(
+                    "generated from seed {seed}{options}",
+                    &[("seed", &seed.to_string()), ("options", &options)],
+                ));
+                self.info(&tr_with(
+                    "to generate it again: {command}",
+                    &[("command", command)],
+                ));
+            }
+            _ => self.info(&tr("the seed of this puzzle is not known")),
+        }
+    }
+
+    /// Lists the puzzles of a set with their size, how much of each is
+    /// filled in, and whether it has been solved.
+    pub fn puzzle_list(&self, puzzles: &PuzzleSet, completed: &Completed, stats: &Stats) {
+        let boards: Vec<Binox> = puzzles
+            .entries
+            .iter()
+            .map(|entry| Binox::new_from_string(entry.puzzle.clone()))
+            .collect();
+        let solved: Vec<bool> = puzzles
+            .entries
+            .iter()
+            .zip(&boards)
+            .map(|(entry, binox)| completed.contains(&entry.puzzle) || binox.is_solved())
+            .collect();
+        let filled: Vec<usize> = boards
+            .iter()
+            .map(|binox| {
+                let cells = binox.as_string();
+                100 * cells.chars().filter(|&c| c != '.').count() / cells.len().max(1)
+            })
+            .collect();
+        let ratings: Vec<Option<f64>> = puzzles
+            .entries
+            .iter()
+            .map(|entry| stats.average_rating(&entry.puzzle))
+            .collect();
+        if self.mode == OutputMode::Json {
+            self.emit(json!({
+                "type": "list",
+                "name": puzzles.name,
+                "selected": puzzles.selected(),
+                "puzzles": puzzles.entries,
+                "sizes": boards.iter().map(Binox::size).collect::<Vec<u8>>(),
+                "filled_percent": filled,
+                "solved": solved,
+                "ratings": ratings,
+            }));
+            return;
+        }
+        let plain = self.resolved(boards[puzzles.selected()].size()).0 == Rendering::Plain;
+        let check = if plain { "*" } else { "✓" };
+        println!("{}", puzzles.name.bold());
+        for (i, entry) in puzzles.entries.iter().enumerate() {
+            let marker = if i == puzzles.selected() { ">" } else { " " };
+            let done = if solved[i] { check } else { " " };
+            let size = format!("{0}x{0}", boards[i].size());
+            let title = entry.title.clone().unwrap_or(format!("puzzle {}", i + 1));
+            let line = format!("{marker}{i:>4} {done} {size:>5} {:>3}%  {title}", filled[i]);
+            let line = match entry.difficulty {
+                Some(difficulty) => format!("{line} ({difficulty})"),
+                None => line,
+            };
+            let line = match ratings[i] {
+                Some(stars) if plain => {
+                    format!("{line} {stars:.1} stars")
+                }
+                Some(stars) => format!("{line} {stars:.1}★"),
+                None => line,
+            };
+            if i == puzzles.selected() {
+                println!("{}", current_theme().highlight(&line));
+            } else {
+                println!("{line}");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn terminal_choices() {
+        let terminal = Terminal {
+            ansi: true,
+            unicode: true,
+            width: Some(80),
+        };
+        let classic = BoardStyle::Classic;
+        assert_eq!(terminal.choose(classic, 16), (Rendering::Color, classic));
+        let narrow = Terminal {
+            width: Some(60),
+            ..terminal
+        };
+        assert_eq!(
+            narrow.choose(classic, 16),
+            (Rendering::Color, BoardStyle::Boxed)
+        );
+        assert_eq!(narrow.choose(classic, 10), (Rendering::Color, classic));
+        let ascii = Terminal {
+            unicode: false,
+            ..narrow
+        };
+        assert_eq!(
+            ascii.choose(BoardStyle::Boxed, 16),
+            (Rendering::Color, classic)
+        );
+        let dumb = Terminal {
+            ansi: false,
+            ..terminal
+        };
+        assert_eq!(dumb.choose(BoardStyle::Boxed, 8).0, Rendering::Plain);
+    }
+
+    #[test]
+    fn marked_boards_keep_the_chosen_style() {
+        let binox = Binox::new(6).unwrap();
+        let mut marks = Marks::new(6);
+        marks.add(&binox, 0, 0, BinoxCell::X).unwrap();
+        let mut out = Output::new(OutputMode::Text);
+        out.rendering = Rendering::Auto;
+        out.terminal = Some(Terminal {
+            ansi: true,
+            unicode: false,
+            width: Some(200),
+        });
+        let drawing = out.drawing(&binox, Some(&marks));
+        assert!(drawing.contains("---+") && !drawing.contains('│'));
+        out.terminal = Some(Terminal {
+            ansi: true,
+            unicode: true,
+            width: Some(20),
+        });
+        let drawing = out.drawing(&binox, Some(&marks));
+        assert!(drawing.contains('│') && !drawing.contains("---+"));
+    }
+}
@@ -0,0 +1,853 @@
+//! The search behind solving, counting and generating, written once for every
+//! puzzle played by filling a square grid with two symbols. Binox implements
+//! [`Puzzle`]; a sibling such as Binairo Plus or Takuzu without the uniqueness
+//! rule only has to describe its cells and rules to get the same solver and
+//! generator.
+
+use alloc::vec;
+use alloc::vec::Vec;
+use core::ops::Add;
+use core::str::FromStr;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use rand::prelude::SliceRandom;
+use rand::Rng;
+
+use crate::binox::propagation::{propagate, Trial};
+
+use Solution::*;
+
+pub enum PresolveResult {
+    Good,
+    Bad,
+}
+
+/// How many solutions a search found: none, exactly one, or at least two, with
+/// two of them as an example.
+pub enum Solution<P> {
+    Zero,
+    One(P),
+    Multiple(P, P),
+}
+
+impl<P> Add for Solution<P> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        match self {
+            Multiple(..) => self,
+            One(a) => match rhs {
+                Zero => One(a),
+                One(b) => Multiple(a, b),
+                Multiple(..) => rhs,
+            },
+            Zero => rhs,
+        }
+    }
+}
+
+/// What [`Puzzle::solve_in_place`] did to the board.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SolveStatus {
+    /// The board cannot be completed and was left as it was.
+    NoSolution,
+    /// The board was filled with its solution, the only one if uniqueness
+    /// was checked.
+    Solved,
+    /// The board was filled with one of several solutions.
+    Ambiguous,
+}
+
+/// What a search did, for measuring how hard a puzzle is to solve.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SearchStats {
+    /// Boards looked at. A puzzle solved by propagation alone takes two: the
+    /// puzzle and the filled board.
+    pub nodes: usize,
+    /// Boards where the search guessed a cell.
+    pub guesses: usize,
+    /// Guesses tried, counting both symbols of a cell when the first was
+    /// wrong or when both had to be looked at.
+    pub branches: usize,
+    /// The most guesses in force at once.
+    pub depth: usize,
+    /// The guesses in force now.
+    level: usize,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Symmetry {
+    #[default]
+    None,
+    Rotational,
+    Mirror,
+}
+
+impl Symmetry {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Symmetry::None => "none",
+            Symmetry::Rotational => "rotational",
+            Symmetry::Mirror => "mirror",
+        }
+    }
+
+    /// The cells whose clues must be kept or removed together with the given cell.
+    fn group(&self, size: u8, row: u8, col: u8) -> Vec<(u8, u8)> {
+        let partner = match self {
+            Symmetry::None => return vec![(row, col)],
+            Symmetry::Rotational => (size - 1 - row, size - 1 - col),
+            Symmetry::Mirror => (row, size - 1 - col),
+        };
+        vec![(row, col), partner]
+    }
+}
+
+impl FromStr for Symmetry {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "none" => Ok(Symmetry::None),
+            "rotational" | "rotate" | "r" => Ok(Symmetry::Rotational),
+            "mirror" | "m" => Ok(Symmetry::Mirror),
+            _ => Err("symmetry must be none, rotational, or mirror"),
+        }
+    }
+}
+
+/// Turns a seed given as text into the number a generator is seeded with. A
+/// whole number is its own seed; any other text, such as a passphrase like
+/// `october-challenge`, is hashed with 64-bit FNV-1a, so that the same text
+/// gives the same puzzle on every machine and in every version.
+pub fn seed_from_str(text: &str) -> u64 {
+    if let Ok(seed) = text.parse() {
+        return seed;
+    }
+    text.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// A square grid puzzle whose cells each take one of two symbols.
+///
+/// Implementors describe the cell domain and the rules; the provided methods
+/// do the searching. The rules are split in two so that the cheap checks on
+/// single lines can run on their own: [`Puzzle::lines_valid`] looks at each
+/// row and column by itself, and [`Puzzle::global_valid`] at how they relate.
+pub trait Puzzle: Clone {
+    type Cell: Copy + Eq;
+
+    /// The two symbols, in the order the search tries them.
+    const SYMBOLS: [Self::Cell; 2];
+    const EMPTY: Self::Cell;
+
+    /// An empty board, or an error if the puzzle has no boards of that size.
+    fn blank(size: u8) -> Result<Self, &'static str>;
+
+    fn size(&self) -> u8;
+
+    /// The contents of a cell inside the board.
+    fn cell(&self, row: u8, col: u8) -> Self::Cell;
+
+    /// Fills or empties a cell inside the board, clues included.
+    fn put(&mut self, row: u8, col: u8, cell: Self::Cell);
+
+    /// Turns every filled cell into a clue.
+    fn lock_filled(&mut self);
+
+    /// Whether no row or column, on its own, breaks a rule.
+    fn lines_valid(&self) -> bool;
+
+    /// Whether the rules spanning several lines hold. None by default.
+    fn global_valid(&self) -> bool {
+        true
+    }
+
+    /// A weaker check than [`Puzzle::is_valid`] that catches only the most
+    /// obvious mistakes. The generator adds its extra clues among the cells
+    /// this check cannot fill, so they are never trivial.
+    fn is_valid_simple(&self) -> bool {
+        self.lines_valid()
+    }
+
+    fn is_valid(&self) -> bool {
+        self.lines_valid() && self.global_valid()
+    }
+
+    fn is_full(&self) -> bool {
+        self.empty_cells().is_empty()
+    }
+
+    fn empty_cells(&self) -> Vec<(u8, u8)> {
+        let mut result = Vec::new();
+        for row in 0..self.size() {
+            for col in 0..self.size() {
+                if self.cell(row, col) == Self::EMPTY {
+                    result.push((row, col));
+                }
+            }
+        }
+        result
+    }
+
+    fn empty_count(&self) -> usize {
+        (0..self.size())
+            .flat_map(|row| (0..self.size()).map(move |col| (row, col)))
+            .filter(|&(row, col)| self.cell(row, col) == Self::EMPTY)
+            .count()
+    }
+
+    /// The empty cell whose row and column have the fewest other empty
+    /// cells, where a guess is most likely to force others. Ties go to the
+    /// first such cell.
+    fn most_constrained_cell(&self) -> Option<(u8, u8)> {
+        let size = self.size() as usize;
+        let mut row_empty = vec![0; size];
+        let mut col_empty = vec![0; size];
+        for row in 0..self.size() {
+            for col in 0..self.size() {
+                if self.cell(row, col) == Self::EMPTY {
+                    row_empty[row as usize] += 1;
+                    col_empty[col as usize] += 1;
+                }
+            }
+        }
+        (0..self.size())
+            .flat_map(|row| (0..self.size()).map(move |col| (row, col)))
+            .filter(|&(row, col)| self.cell(row, col) == Self::EMPTY)
+            .min_by_key(|&(row, col)| row_empty[row as usize] + col_empty[col as usize])
+    }
+
+    /// Whether a puzzle that had a unique solution still has it with the
+    /// clues of `removed` taken out. Only the removed cells can go another
+    /// way, so rather than counting every solution again this asks whether
+    /// propagation puts the clues back, and if not, whether any removed cell
+    /// can take the other symbol.
+    fn still_unique(
+        &self,
+        removed: &[(u8, u8)],
+        solution: &Self,
+        stop: &AtomicBool,
+    ) -> Result<bool, &'static str> {
+        let mut propagated = self.clone();
+        if let PresolveResult::Good = propagated.propagate() {
+            if removed
+                .iter()
+                .all(|&(row, col)| propagated.cell(row, col) == solution.cell(row, col))
+            {
+                return Ok(true);
+            }
+        }
+        let [first, second] = Self::SYMBOLS;
+        for &(row, col) in removed {
+            let mut other = self.clone();
+            let symbol = if solution.cell(row, col) == first {
+                second
+            } else {
+                first
+            };
+            other.put(row, col, symbol);
+            match other.search(false, stop) {
+                None => return Err("generation was cancelled"),
+                Some(Zero) => (),
+                Some(_) => return Ok(false),
+            }
+        }
+        Ok(true)
+    }
+
+    /// The cells whose symbol differs between two boards of the same size.
+    fn changed_cells(&self, other: &Self) -> Vec<(u8, u8)> {
+        let mut result = Vec::new();
+        if self.size() != other.size() {
+            return result;
+        }
+        for row in 0..self.size() {
+            for col in 0..self.size() {
+                if self.cell(row, col) != other.cell(row, col) {
+                    result.push((row, col));
+                }
+            }
+        }
+        result
+    }
+
+    /// Fills every empty cell where only one symbol passes `valid`, until
+    /// none is left, or reports a cell where neither does and leaves the
+    /// board as it was, so that presolving twice changes nothing more. Runs
+    /// the propagation engine with [`Trial`] as its only rule.
+    fn presolve_with(&mut self, valid: fn(&Self) -> bool) -> PresolveResult {
+        let propagation = propagate(self, &[&Trial { valid }]);
+        if !propagation.contradiction {
+            return PresolveResult::Good;
+        }
+        for ((row, col), _) in propagation.deductions {
+            self.put(row, col, Self::EMPTY);
+        }
+        PresolveResult::Bad
+    }
+
+    fn presolve(&mut self) -> PresolveResult {
+        self.presolve_with(Self::is_valid)
+    }
+
+    /// Fills what can be deduced before the search guesses, or reports that
+    /// the board cannot be completed. [`Puzzle::presolve`] unless the puzzle
+    /// has something faster or stronger; it only has to be sound.
+    fn propagate(&mut self) -> PresolveResult {
+        self.presolve()
+    }
+
+    /// Searches for solutions. With `multiple`, keeps looking after the first
+    /// to tell a unique solution from several.
+    fn solve(&self, multiple: bool) -> Solution<Self> {
+        self.search(multiple, &AtomicBool::new(false)).unwrap()
+    }
+
+    /// Solves like [`Puzzle::solve`], but fills this board with the solution
+    /// instead of handing back copies, keeping the given cells as they are.
+    ///
+    /// The search works on this board itself and only copies it to keep the
+    /// other guess of each branch, and a second solution is never built.
+    fn solve_in_place(&mut self, multiple: bool) -> SolveStatus {
+        let original = self.clone();
+        let stop = AtomicBool::new(false);
+        let stats = &mut SearchStats::default();
+        match search_in_place(self, multiple, !multiple, &stop, stats).unwrap() {
+            0 => {
+                *self = original;
+                SolveStatus::NoSolution
+            }
+            1 => SolveStatus::Solved,
+            _ => SolveStatus::Ambiguous,
+        }
+    }
+
+    /// Searches like [`Puzzle::solve`], but gives up with `None` once `stop`
+    /// is set, so that a search on another thread can be cancelled.
+    fn search(&self, multiple: bool, stop: &AtomicBool) -> Option<Solution<Self>> {
+        self.search_with_stats(multiple, stop, &mut SearchStats::default())
+    }
+
+    /// Searches like [`Puzzle::search`], adding what the search did to
+    /// `stats`.
+    fn search_with_stats(
+        &self,
+        multiple: bool,
+        stop: &AtomicBool,
+        stats: &mut SearchStats,
+    ) -> Option<Solution<Self>> {
+        // A search for any one solution orders its guesses by what they
+        // force. A search for two keeps the fixed order throughout, even
+        // while it looks for the second, so that a seed keeps generating the
+        // same puzzle.
+        search_from(self, multiple, !multiple, stop, stats)
+    }
+
+    /// The number of ways this board can be completed, counting no further
+    /// than `limit`.
+    fn count_solutions(&self, limit: usize) -> usize {
+        self.count_with_stop(limit, &AtomicBool::new(false))
+            .unwrap()
+    }
+
+    /// Counts like [`Puzzle::count_solutions`], but gives up with `None` once
+    /// `stop` is set.
+    fn count_with_stop(&self, limit: usize, stop: &AtomicBool) -> Option<usize> {
+        if stop.load(Ordering::Relaxed) {
+            return None;
+        }
+        if limit == 0 {
+            return Some(0);
+        }
+        match (self.is_full(), self.is_valid()) {
+            (true, true) => return Some(1),
+            (false, true) => (),
+            (_, false) => return Some(0),
+        }
+        let mut first = self.clone();
+        match first.propagate() {
+            PresolveResult::Good => (),
+            PresolveResult::Bad => return Some(0),
+        };
+        let Some(&(row, col)) = first.empty_cells().first() else {
+            return first.count_with_stop(limit, stop);
+        };
+        let mut second = first.clone();
+        first.put(row, col, Self::SYMBOLS[0]);
+        second.put(row, col, Self::SYMBOLS[1]);
+        let count = first.count_with_stop(limit, stop)?;
+        Some(count + second.count_with_stop(limit - count, stop)?)
+    }
+
+    /// Generates a puzzle and its unique solution using the given random number
+    /// generator, so that a seeded generator always produces the same puzzle.
+    /// The clues are placed according to `symmetry`. With `perfect`, every
+    /// clue that is not needed is removed; `extras` then adds clues back.
+    fn generate_with_rng<R: Rng>(
+        size: u8,
+        perfect: bool,
+        extras: usize,
+        symmetry: Symmetry,
+        rng: &mut R,
+    ) -> Result<(Self, Self), &'static str> {
+        let stop = AtomicBool::new(false);
+        Self::generate_with_stop(size, perfect, extras, symmetry, rng, &stop)
+    }
+
+    /// Generates like [`Puzzle::generate_with_rng`], but gives up once `stop`
+    /// is set.
+    fn generate_with_stop<R: Rng>(
+        size: u8,
+        perfect: bool,
+        extras: usize,
+        symmetry: Symmetry,
+        rng: &mut R,
+        stop: &AtomicBool,
+    ) -> Result<(Self, Self), &'static str> {
+        let search = |puzzle: &Self, multiple| {
+            puzzle
+                .search(multiple, stop)
+                .ok_or("generation was cancelled")
+        };
+        let [first, second] = Self::SYMBOLS;
+        let random_symbol = |rng: &mut R| if rng.gen() { first } else { second };
+
+        //phase 1 - add some symbols randomly to get started
+        let mut puzzle = Self::blank(size)?;
+        let mut rows = (0u8..size).collect::<Vec<u8>>();
+        let cols = (0u8..size).collect::<Vec<u8>>();
+        rows.shuffle(rng);
+        for i in 0..size {
+            puzzle.put(rows[i as usize], cols[i as usize], random_symbol(rng));
+        }
+
+        //phase 2 - continue adding symbols until there is only one solution
+        loop {
+            match search(&puzzle, true)? {
+                Zero => return Err("something went wrong"),
+                One(_) => break,
+                Multiple(a, b) => {
+                    let diff = a.changed_cells(&b);
+                    if diff.is_empty() {
+                        break;
+                    }
+                    let (row, col) = diff[rng.gen_range(0..diff.len())];
+                    puzzle.put(row, col, random_symbol(rng));
+                }
+            }
+        }
+
+        //if symmetry is set, add the clues that mirror the existing ones
+        let solution = match search(&puzzle, false)? {
+            One(a) => a,
+            _ => return Err("something went wrong"),
+        };
+        if symmetry != Symmetry::None {
+            for row in 0..size {
+                for col in 0..size {
+                    if puzzle.cell(row, col) != Self::EMPTY {
+                        for (r, c) in symmetry.group(size, row, col) {
+                            puzzle.put(r, c, solution.cell(r, c));
+                        }
+                    }
+                }
+            }
+        }
+
+        //phase 3 - remove symbols that are not needed to find the solution
+        for row in 0..size {
+            for col in 0..size {
+                if puzzle.cell(row, col) != Self::EMPTY {
+                    let group = symmetry.group(size, row, col);
+                    let mut clone = puzzle.clone();
+                    for &(r, c) in &group {
+                        clone.put(r, c, Self::EMPTY);
+                    }
+                    clone.presolve();
+                    if group
+                        .iter()
+                        .all(|&(r, c)| clone.cell(r, c) == solution.cell(r, c))
+                    {
+                        for &(r, c) in &group {
+                            puzzle.put(r, c, Self::EMPTY);
+                        }
+                    }
+                }
+            }
+        }
+
+        //phase 4 - if perfect generation is set, remove even more symbols that are not needed to find the solution
+        if perfect {
+            for row in 0..size {
+                for col in 0..size {
+                    if puzzle.cell(row, col) != Self::EMPTY {
+                        let group = symmetry.group(size, row, col);
+                        for &(r, c) in &group {
+                            puzzle.put(r, c, Self::EMPTY);
+                        }
+                        if !puzzle.still_unique(&group, &solution, stop)? {
+                            for &(r, c) in &group {
+                                puzzle.put(r, c, solution.cell(r, c));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        //phase 5 - add more cells if specified
+        if extras > 0 {
+            let mut clone = puzzle.clone();
+            clone.presolve_with(Self::is_valid_simple);
+            let mut empties = clone.empty_cells();
+            let num = extras.min(empties.len());
+            empties.shuffle(rng);
+            for &(row, col) in empties.iter().take(num) {
+                for (r, c) in symmetry.group(size, row, col) {
+                    puzzle.put(r, c, solution.cell(r, c));
+                }
+            }
+        }
+
+        puzzle.lock_filled();
+        Ok((puzzle, solution))
+    }
+}
+
+/// The search behind [`Puzzle::search_with_stats`]. With `informed`, each
+/// guess goes to the most constrained cell, and the symbol that propagates
+/// further is tried first.
+fn search_from<P: Puzzle>(
+    board: &P,
+    multiple: bool,
+    informed: bool,
+    stop: &AtomicBool,
+    stats: &mut SearchStats,
+) -> Option<Solution<P>> {
+    if stop.load(Ordering::Relaxed) {
+        return None;
+    }
+    stats.nodes += 1;
+    match (board.is_full(), board.is_valid()) {
+        (true, true) => return Some(One(board.clone())),
+        (false, true) => (),
+        (_, false) => return Some(Zero),
+    }
+    let mut first = board.clone();
+    match first.propagate() {
+        PresolveResult::Good => (),
+        PresolveResult::Bad => return Some(Zero),
+    };
+    let Some(second) = guess(&mut first, informed) else {
+        return search_from(&first, multiple, informed, stop, stats);
+    };
+    stats.guesses += 1;
+    stats.level += 1;
+    stats.depth = stats.depth.max(stats.level);
+    let mut branch = |board: &P, multiple| {
+        stats.branches += 1;
+        search_from(board, multiple, informed, stop, stats)
+    };
+    let solution = match (branch(&first, multiple)?, multiple) {
+        (Zero, multiple) => branch(&second, multiple)?,
+        (One(a), true) => One(a) + branch(&second, false)?,
+        (One(a), false) => One(a),
+        (Multiple(a, b), true) => Multiple(a, b),
+        (Multiple(a, _), false) => One(a),
+    };
+    stats.level -= 1;
+    Some(solution)
+}
+
+/// Fills the cell the search guesses next in `board` with the first symbol
+/// to try, and returns a copy with the other one, or `None` if the board is
+/// full. With `informed`, the most constrained cell is chosen and both
+/// guesses are propagated to pick the one to try first: not one that fails,
+/// and otherwise the one that fills more cells, since it leads to a solution
+/// or a contradiction sooner.
+fn guess<P: Puzzle>(board: &mut P, informed: bool) -> Option<P> {
+    let (row, col) = if informed {
+        board.most_constrained_cell()
+    } else {
+        alternated_range(board.size())
+            .flat_map(|row| alternated_range(board.size()).map(move |col| (row, col)))
+            .find(|&(row, col)| board.cell(row, col) == P::EMPTY)
+    }?;
+    let mut second = board.clone();
+    board.put(row, col, P::SYMBOLS[0]);
+    second.put(row, col, P::SYMBOLS[1]);
+    if informed {
+        let first_failed = matches!(board.propagate(), PresolveResult::Bad);
+        let second_failed = matches!(second.propagate(), PresolveResult::Bad);
+        if (first_failed && !second_failed)
+            || (first_failed == second_failed && second.empty_count() < board.empty_count())
+        {
+            core::mem::swap(board, &mut second);
+        }
+    }
+    Some(second)
+}
+
+/// The search behind [`Puzzle::solve_in_place`], which is
+/// [`search_from`] working on `board` itself. Returns how many solutions it
+/// found, up to 2, and leaves the first in `board`; when there is none,
+/// `board` is left in whatever state the search reached.
+fn search_in_place<P: Puzzle>(
+    board: &mut P,
+    multiple: bool,
+    informed: bool,
+    stop: &AtomicBool,
+    stats: &mut SearchStats,
+) -> Option<usize> {
+    if stop.load(Ordering::Relaxed) {
+        return None;
+    }
+    stats.nodes += 1;
+    match (board.is_full(), board.is_valid()) {
+        (true, true) => return Some(1),
+        (false, true) => (),
+        (_, false) => return Some(0),
+    }
+    if let PresolveResult::Bad = board.propagate() {
+        return Some(0);
+    }
+    let Some(mut second) = guess(board, informed) else {
+        return search_in_place(board, multiple, informed, stop, stats);
+    };
+    stats.guesses += 1;
+    stats.level += 1;
+    stats.depth = stats.depth.max(stats.level);
+    stats.branches += 1;
+    let found = match search_in_place(board, multiple, informed, stop, stats)? {
+        0 => {
+            *board = second;
+            stats.branches += 1;
+            search_in_place(board, multiple, informed, stop, stats)?
+        }
+        1 if multiple => {
+            stats.branches += 1;
+            1 + search_in_place(&mut second, false, informed, stop, stats)?
+        }
+        found => found,
+    };
+    stats.level -= 1;
+    Some(found)
+}
+
+/// The numbers below `n`, even ones first. The search branches on cells in
+/// this order, which spreads its guesses over the board.
+fn alternated_range(n: u8) -> impl Iterator<Item = u8> {
+    (0..n).step_by(2).chain((1..n).step_by(2))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::binox::{Binox, BinoxCell};
+
+    /// Binox without the rule that rows and columns must be unique.
+    #[derive(Clone)]
+    struct Loose(Binox);
+
+    impl Puzzle for Loose {
+        type Cell = BinoxCell;
+
+        const SYMBOLS: [BinoxCell; 2] = Binox::SYMBOLS;
+        const EMPTY: BinoxCell = BinoxCell::EMPTY;
+
+        fn blank(size: u8) -> Result<Self, &'static str> {
+            Binox::new(size).map(Loose)
+        }
+
+        fn size(&self) -> u8 {
+            self.0.size()
+        }
+
+        fn cell(&self, row: u8, col: u8) -> BinoxCell {
+            self.0.cell(row, col)
+        }
+
+        fn put(&mut self, row: u8, col: u8, cell: BinoxCell) {
+            self.0.put(row, col, cell)
+        }
+
+        fn lock_filled(&mut self) {
+            self.0.lock_filled()
+        }
+
+        fn lines_valid(&self) -> bool {
+            self.0.lines_valid()
+        }
+    }
+
+    #[test]
+    fn sibling_rules() {
+        let blank = Loose::blank(4).unwrap();
+        assert!(blank.count_solutions(1000) > Binox::new(4).unwrap().count_solutions(1000));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn sibling_generation() {
+        use rand::rngs::StdRng;
+        use rand::SeedableRng;
+        let mut rng = StdRng::seed_from_u64(4);
+        let (puzzle, solution) =
+            Loose::generate_with_rng(6, true, 0, Symmetry::None, &mut rng).unwrap();
+        assert!(
+            matches!(puzzle.solve(true), One(found) if found.changed_cells(&solution).is_empty())
+        );
+        assert!(solution.is_full() && solution.is_valid());
+    }
+
+    #[test]
+    fn first_solution() {
+        // Blank boards have the most solutions to wander between; the search
+        // for any one of them should go straight to it.
+        for size in [4, 8, 12, 16] {
+            let blank = Binox::new(size).unwrap();
+            let mut stats = SearchStats::default();
+            let solution = blank.search_with_stats(false, &AtomicBool::new(false), &mut stats);
+            assert!(matches!(solution, Some(One(found)) if found.is_full() && found.is_valid()));
+            assert!(
+                stats.nodes < 10 * size as usize,
+                "{size}: {} nodes",
+                stats.nodes
+            );
+        }
+    }
+
+    #[test]
+    fn presolve_fixpoint() {
+        let mut board = Binox::new_from_string("X......O...X..XX....X..O....X..XX...".into());
+        assert!(matches!(board.presolve(), PresolveResult::Good));
+        assert!(board.is_solved());
+        let mut broken = Binox::new_from_string("XX.X............".into());
+        assert!(matches!(broken.presolve(), PresolveResult::Bad));
+    }
+
+    /// A second presolve finds nothing the first one missed, even on boards
+    /// that turn out to have no solution.
+    #[test]
+    #[cfg(feature = "std")]
+    fn presolve_is_idempotent() {
+        use rand::rngs::StdRng;
+        use rand::{Rng, SeedableRng};
+
+        let mut rng = StdRng::seed_from_u64(416);
+        let mut boards = vec![Binox::new_from_string(
+            ".....XX..X...O....OO..X......O..O..X".into(),
+        )];
+        for _ in 0..2000 {
+            let mut board = Binox::new(6).unwrap();
+            for _ in 0..10 {
+                let (row, col) = (rng.gen_range(0..6), rng.gen_range(0..6));
+                let cell = Binox::SYMBOLS[rng.gen_range(0..2)];
+                board.put(row, col, cell);
+            }
+            boards.push(board);
+        }
+        for mut once in boards {
+            let before = once.as_string();
+            let result = once.presolve();
+            if matches!(result, PresolveResult::Bad) {
+                assert_eq!(once.as_string(), before);
+            }
+            let mut twice = once.clone();
+            assert_eq!(
+                matches!(twice.presolve(), PresolveResult::Good),
+                matches!(result, PresolveResult::Good)
+            );
+            assert_eq!(twice.as_string(), once.as_string());
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn clue_removal() {
+        use rand::rngs::StdRng;
+        use rand::SeedableRng;
+
+        let mut rng = StdRng::seed_from_u64(8);
+        let (puzzle, solution) =
+            Binox::generate_with_rng(8, false, 6, Symmetry::None, &mut rng).unwrap();
+        let stop = AtomicBool::new(false);
+        for (row, col) in solution.changed_cells(&Binox::new(8).unwrap()) {
+            if puzzle.cell(row, col) == BinoxCell::EMPTY {
+                continue;
+            }
+            let mut removed = puzzle.clone();
+            removed.put(row, col, BinoxCell::EMPTY);
+            let unique = matches!(removed.solve(true), One(_));
+            assert_eq!(
+                removed.still_unique(&[(row, col)], &solution, &stop),
+                Ok(unique)
+            );
+        }
+    }
+
+    #[test]
+    fn solving_in_place() {
+        let mut board = Binox::new_from_string("X......O...X..XX....X..O....X..XX...".into());
+        assert_eq!(board.solve_in_place(true), SolveStatus::Solved);
+        assert!(board.is_solved() && board.is_default(0, 0).unwrap());
+        assert!(!board.is_default(0, 1).unwrap());
+        let mut blank = Binox::new(4).unwrap();
+        assert_eq!(blank.solve_in_place(true), SolveStatus::Ambiguous);
+        assert!(blank.is_full());
+        let mut broken = Binox::new_from_string("XXX.............".into());
+        assert_eq!(broken.solve_in_place(false), SolveStatus::NoSolution);
+        assert_eq!(broken.as_string(), "XXX.............");
+    }
+
+    /// Solving in place reaches the same verdict and solution as `solve`.
+    #[test]
+    #[cfg(feature = "std")]
+    fn in_place_matches_solve() {
+        use rand::rngs::StdRng;
+        use rand::{Rng, SeedableRng};
+
+        let mut rng = StdRng::seed_from_u64(414);
+        for _ in 0..500 {
+            let mut board = Binox::new(6).unwrap();
+            for _ in 0..rng.gen_range(0..12) {
+                let (row, col) = (rng.gen_range(0..6), rng.gen_range(0..6));
+                board.put(row, col, Binox::SYMBOLS[rng.gen_range(0..2)]);
+            }
+            for multiple in [false, true] {
+                let mut in_place = board.clone();
+                let status = in_place.solve_in_place(multiple);
+                match board.solve(multiple) {
+                    Zero => {
+                        assert_eq!(status, SolveStatus::NoSolution);
+                        assert_eq!(in_place.as_string(), board.as_string());
+                    }
+                    One(solution) => {
+                        assert_eq!(status, SolveStatus::Solved);
+                        assert_eq!(in_place.as_string(), solution.as_string());
+                    }
+                    Multiple(..) => {
+                        assert_eq!(status, SolveStatus::Ambiguous);
+                        assert!(in_place.is_solved());
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn text_seeds() {
+        assert_eq!(seed_from_str("42"), 42);
+        assert_eq!(seed_from_str("a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(
+            seed_from_str("october-challenge"),
+            seed_from_str("october-challenge")
+        );
+        assert_ne!(
+            seed_from_str("october-challenge"),
+            seed_from_str("november-challenge")
+        );
+    }
+}
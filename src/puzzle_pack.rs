@@ -0,0 +1,196 @@
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::path::Path;
+
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+use crate::puzzle_set::puzzle_lines;
+
+pub const PACK_EXTENSION: &str = "binoxpack";
+pub const MANIFEST_NAME: &str = "index.txt";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PackEntry {
+    pub category: String,
+    pub count: usize,
+}
+
+impl PackEntry {
+    pub fn file_name(&self) -> String {
+        format!("{}.binox", self.category)
+    }
+}
+
+fn count_puzzles(contents: &str) -> usize {
+    puzzle_lines(contents).len()
+}
+
+fn manifest_string(entries: &[PackEntry]) -> String {
+    let mut result = String::new();
+    for entry in entries {
+        result.push_str(&format!("{} {}\n", entry.category, entry.count));
+    }
+    result
+}
+
+fn parse_manifest(contents: &str) -> Result<Vec<PackEntry>, String> {
+    let mut result = Vec::new();
+    for (i, line) in contents.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let mut words = line.split_whitespace();
+        let (category, count) = match (words.next(), words.next()) {
+            (Some(category), Some(count)) => (category, count),
+            _ => return Err(format!("malformed manifest entry on line {}", i + 1)),
+        };
+        let count = count
+            .parse()
+            .map_err(|_| format!("malformed puzzle count on line {}", i + 1))?;
+        result.push(PackEntry {
+            category: category.into(),
+            count,
+        });
+    }
+    Ok(result)
+}
+
+/// Packs every `.binox` file in `source_dir` into a single compressed file.
+/// Each file becomes a category named after its file stem.
+pub fn pack(source_dir: &str, output: &str) -> Result<Vec<PackEntry>, String> {
+    let dir = fs::read_dir(source_dir).map_err(|_| format!("directory not found: {source_dir}"))?;
+    let mut files = Vec::new();
+    for item in dir.flatten() {
+        let path = item.path();
+        if path.extension().and_then(|e| e.to_str()) == Some("binox") {
+            if let Some(stem) = path.file_stem().and_then(|s| s.to_str()) {
+                files.push((stem.to_string(), path.clone()));
+            }
+        }
+    }
+    if files.is_empty() {
+        return Err(format!("no .binox files found in {source_dir}"));
+    }
+    files.sort();
+
+    let file = File::create(output).map_err(|_| format!("could not create file: {output}"))?;
+    let mut zip = ZipWriter::new(file);
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    let mut entries = Vec::new();
+    for (category, path) in files {
+        let contents =
+            fs::read_to_string(&path).map_err(|_| format!("could not read {}", path.display()))?;
+        let entry = PackEntry {
+            count: count_puzzles(&contents),
+            category,
+        };
+        zip.start_file(entry.file_name(), options)
+            .map_err(|e| e.to_string())?;
+        zip.write_all(contents.as_bytes())
+            .map_err(|e| e.to_string())?;
+        entries.push(entry);
+    }
+    zip.start_file(MANIFEST_NAME, options)
+        .map_err(|e| e.to_string())?;
+    zip.write_all(manifest_string(&entries).as_bytes())
+        .map_err(|e| e.to_string())?;
+    zip.finish().map_err(|e| e.to_string())?;
+    Ok(entries)
+}
+
+fn open_pack(pack_path: &str) -> Result<ZipArchive<File>, String> {
+    let file = File::open(pack_path).map_err(|_| format!("file not found: {pack_path}"))?;
+    ZipArchive::new(file).map_err(|_| format!("not a valid puzzle pack: {pack_path}"))
+}
+
+fn read_member(archive: &mut ZipArchive<File>, name: &str) -> Result<String, String> {
+    let mut member = archive
+        .by_name(name)
+        .map_err(|_| format!("puzzle pack does not contain {name}"))?;
+    let mut contents = String::new();
+    member
+        .read_to_string(&mut contents)
+        .map_err(|e| e.to_string())?;
+    Ok(contents)
+}
+
+/// Reads the manifest of a puzzle pack.
+pub fn read_manifest(pack_path: &str) -> Result<Vec<PackEntry>, String> {
+    let mut archive = open_pack(pack_path)?;
+    parse_manifest(&read_member(&mut archive, MANIFEST_NAME)?)
+}
+
+/// Reads the puzzles of one category of a puzzle pack.
+pub fn read_category(pack_path: &str, category: &str) -> Result<Vec<String>, String> {
+    let mut archive = open_pack(pack_path)?;
+    let entries = parse_manifest(&read_member(&mut archive, MANIFEST_NAME)?)?;
+    let entry = entries
+        .iter()
+        .find(|entry| entry.category == category)
+        .ok_or(format!("puzzle pack has no category named {category}"))?;
+    let contents = read_member(&mut archive, &entry.file_name())?;
+    Ok(contents
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.to_string())
+        .collect())
+}
+
+/// Extracts every category of a puzzle pack into `output_dir` as `.binox` files.
+pub fn unpack(pack_path: &str, output_dir: &str) -> Result<Vec<PackEntry>, String> {
+    let mut archive = open_pack(pack_path)?;
+    let entries = parse_manifest(&read_member(&mut archive, MANIFEST_NAME)?)?;
+    fs::create_dir_all(output_dir).map_err(|_| format!("could not create {output_dir}"))?;
+    for entry in &entries {
+        let contents = read_member(&mut archive, &entry.file_name())?;
+        let path = Path::new(output_dir).join(entry.file_name());
+        fs::write(&path, contents).map_err(|_| format!("could not write {}", path.display()))?;
+    }
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn manifest_round_trip() {
+        let entries = vec![
+            PackEntry {
+                category: "4x4_easy".into(),
+                count: 32,
+            },
+            PackEntry {
+                category: "16x16_expert".into(),
+                count: 3,
+            },
+        ];
+        assert_eq!(parse_manifest(&manifest_string(&entries)), Ok(entries));
+        assert!(parse_manifest("4x4_easy").is_err());
+        assert!(parse_manifest("4x4_easy many").is_err());
+    }
+
+    #[test]
+    fn pack_and_unpack() {
+        let dir = std::env::temp_dir().join(format!("binox_pack_test_{}", std::process::id()));
+        let source = dir.join("source");
+        let output = dir.join("output");
+        fs::create_dir_all(&source).unwrap();
+        fs::write(source.join("4x4_easy.binox"), "X...\n....\n\n").unwrap();
+        fs::write(source.join("ignored.txt"), "nothing").unwrap();
+        let pack_path = dir.join("puzzles.binoxpack");
+        let pack_path = pack_path.to_str().unwrap();
+
+        let entries = pack(source.to_str().unwrap(), pack_path).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].count, 2);
+        assert_eq!(read_manifest(pack_path).unwrap(), entries);
+        assert_eq!(read_category(pack_path, "4x4_easy").unwrap().len(), 2);
+        assert!(read_category(pack_path, "4x4_hard").is_err());
+
+        unpack(pack_path, output.to_str().unwrap()).unwrap();
+        assert!(output.join("4x4_easy.binox").exists());
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
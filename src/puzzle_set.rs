@@ -0,0 +1,897 @@
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::atomic::AtomicBool;
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use serde::{Deserialize, Serialize};
+
+use crate::binox::{Binox, Symmetry};
+use crate::difficulty::Difficulty;
+use crate::puzzle::Puzzle;
+
+pub const SET_EXTENSION: &str = "binoxset";
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PuzzleEntry {
+    pub puzzle: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub difficulty: Option<Difficulty>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub solution: Option<String>,
+    /// The seed that the puzzle was generated from.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+    /// The other options that the puzzle was generated with.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub options: Option<GenerateOptions>,
+    /// The player's pencil marks, as written by [`Marks::encode`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub marks: Option<String>,
+    /// The seconds the player has spent on the puzzle.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub elapsed: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    /// Where the puzzle comes from, such as a book or a website.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// The date the puzzle was made, as `2024-05-17`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+}
+
+/// The descriptive fields of a puzzle that the `meta` command sets.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MetaField {
+    Author,
+    Source,
+    Created,
+    Notes,
+}
+
+impl MetaField {
+    pub const ALL: [MetaField; 4] = [
+        MetaField::Author,
+        MetaField::Source,
+        MetaField::Created,
+        MetaField::Notes,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            MetaField::Author => "author",
+            MetaField::Source => "source",
+            MetaField::Created => "created",
+            MetaField::Notes => "notes",
+        }
+    }
+}
+
+impl FromStr for MetaField {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "author" => Ok(MetaField::Author),
+            "source" => Ok(MetaField::Source),
+            "created" | "date" => Ok(MetaField::Created),
+            "notes" | "note" => Ok(MetaField::Notes),
+            _ => Err(format!(
+                "unknown field '{s}'; use author, source, created or notes"
+            )),
+        }
+    }
+}
+
+/// Text on a single line, since tabs and line breaks would split a record.
+/// `None` for text that is only whitespace.
+fn single_line(text: &str) -> Option<String> {
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    Some(text).filter(|text| !text.is_empty())
+}
+
+/// Whether a date is written as `2024-05-17`.
+fn is_date(text: &str) -> bool {
+    let parts: Vec<&str> = text.split('-').collect();
+    let number = |part: &str, len: usize, max: u32| {
+        part.len() == len
+            && part
+                .parse::<u32>()
+                .is_ok_and(|n| (len == 4 || n >= 1) && n <= max)
+    };
+    parts.len() == 3
+        && number(parts[0], 4, 9999)
+        && number(parts[1], 2, 12)
+        && number(parts[2], 2, 31)
+}
+
+/// The options besides size and seed that a puzzle is generated with. They
+/// are written as in `extras=3 perfect symmetry=mirror`, with `perfect` and
+/// the symmetry left out when they are not used.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
+pub struct GenerateOptions {
+    pub perfect: bool,
+    pub extras: usize,
+    pub symmetry: Symmetry,
+}
+
+impl GenerateOptions {
+    /// The `binox generate` command that makes the same puzzle again.
+    pub fn command(&self, size: u8, seed: u64) -> String {
+        let mut command = format!("binox generate {size} --seed {seed}");
+        if self.perfect {
+            command.push_str(" --perfect");
+        }
+        if self.extras > 0 {
+            command.push_str(&format!(" --extras {}", self.extras));
+        }
+        if self.symmetry != Symmetry::None {
+            command.push_str(&format!(" --symmetry {}", self.symmetry.name()));
+        }
+        command
+    }
+}
+
+impl fmt::Display for GenerateOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "extras={}", self.extras)?;
+        if self.perfect {
+            write!(f, " perfect")?;
+        }
+        if self.symmetry != Symmetry::None {
+            write!(f, " symmetry={}", self.symmetry.name())?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for GenerateOptions {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut options = GenerateOptions::default();
+        for word in s.split_whitespace() {
+            match word.split_once('=') {
+                None if word == "perfect" => options.perfect = true,
+                Some(("extras", n)) => {
+                    options.extras = n.parse().map_err(|_| format!("invalid extras {n}"))?
+                }
+                Some(("symmetry", symmetry)) => options.symmetry = symmetry.parse()?,
+                _ => return Err(format!("unknown generation option {word}")),
+            }
+        }
+        Ok(options)
+    }
+}
+
+impl From<GenerateOptions> for String {
+    fn from(options: GenerateOptions) -> Self {
+        options.to_string()
+    }
+}
+
+impl TryFrom<String> for GenerateOptions {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl PuzzleEntry {
+    pub fn new(puzzle: String) -> Self {
+        PuzzleEntry {
+            puzzle,
+            title: None,
+            difficulty: None,
+            solution: None,
+            seed: None,
+            options: None,
+            marks: None,
+            elapsed: None,
+            author: None,
+            source: None,
+            created: None,
+            notes: None,
+        }
+    }
+
+    /// The puzzle with only its clues, without the player's progress, as it
+    /// should be graded.
+    pub fn clues(&self) -> Binox {
+        let mut binox = Binox::new_from_string(self.puzzle.clone());
+        binox.reset();
+        binox
+    }
+
+    pub fn meta(&self, field: MetaField) -> Option<&str> {
+        match field {
+            MetaField::Author => self.author.as_deref(),
+            MetaField::Source => self.source.as_deref(),
+            MetaField::Created => self.created.as_deref(),
+            MetaField::Notes => self.notes.as_deref(),
+        }
+        .filter(|value| !value.is_empty())
+    }
+
+    /// Sets one of the descriptive fields, or clears it with `None`. Dates
+    /// must be written as `2024-05-17`.
+    pub fn set_meta(&mut self, field: MetaField, value: Option<&str>) -> Result<(), String> {
+        let value = value.and_then(single_line);
+        if field == MetaField::Created {
+            if let Some(date) = value.as_deref().filter(|date| !is_date(date)) {
+                return Err(format!("invalid date '{date}'; write it as 2024-05-17"));
+            }
+        }
+        *match field {
+            MetaField::Author => &mut self.author,
+            MetaField::Source => &mut self.source,
+            MetaField::Created => &mut self.created,
+            MetaField::Notes => &mut self.notes,
+        } = value;
+        Ok(())
+    }
+
+    /// The entry as one line of a v2 manifest, which keeps everything about
+    /// it: the player's entries apart from the clues, the pencil marks, the
+    /// time spent and the metadata.
+    pub fn to_record(&self) -> String {
+        let seed = self.seed.map_or("-".into(), |s| s.to_string());
+        let options = self.options.map_or("-".into(), |o| o.to_string());
+        let elapsed = self.elapsed.map_or("-".into(), |e| e.to_string());
+        let mut fields = vec![
+            self.difficulty.map_or("-", |d| d.name()),
+            self.title.as_deref().unwrap_or(""),
+            self.solution.as_deref().unwrap_or("-"),
+            &seed,
+            &options,
+            self.marks.as_deref().unwrap_or("-"),
+            &elapsed,
+            self.author.as_deref().unwrap_or(""),
+            self.source.as_deref().unwrap_or(""),
+            self.created.as_deref().unwrap_or(""),
+            self.notes.as_deref().unwrap_or(""),
+        ];
+        while fields.last().is_some_and(|f| f.is_empty() || *f == "-") {
+            fields.pop();
+        }
+        let mut result = self.puzzle.clone();
+        for field in fields {
+            result.push('\t');
+            result.push_str(field);
+        }
+        result
+    }
+
+    /// Reads an entry back from a line written by [`PuzzleEntry::to_record`].
+    pub fn from_record(record: &str) -> Result<Self, String> {
+        let fields: Vec<&str> = record.split('\t').collect();
+        let field = |i: usize| {
+            fields
+                .get(i)
+                .copied()
+                .filter(|f| !f.is_empty() && *f != "-")
+        };
+        let text = |i: usize| fields.get(i).and_then(|t| single_line(t));
+        Ok(PuzzleEntry {
+            difficulty: field(1).map(str::parse).transpose()?,
+            title: text(2),
+            solution: field(3).map(String::from),
+            seed: field(4)
+                .map(|s| s.parse().map_err(|_| format!("invalid seed {s}")))
+                .transpose()?,
+            options: field(5).map(str::parse).transpose()?,
+            marks: field(6).map(String::from),
+            elapsed: field(7)
+                .map(|e| e.parse().map_err(|_| format!("invalid time {e}")))
+                .transpose()?,
+            author: text(8),
+            source: text(9),
+            created: text(10),
+            notes: text(11),
+            ..PuzzleEntry::new(fields[0].into())
+        })
+    }
+
+    /// Generates a puzzle from a seed, keeping the answer key, the seed and
+    /// the options so that the same puzzle can be generated again.
+    pub fn generate(
+        size: u8,
+        seed: u64,
+        options: GenerateOptions,
+    ) -> Result<PuzzleEntry, &'static str> {
+        PuzzleEntry::generate_with_stop(size, seed, options, &AtomicBool::new(false))
+    }
+
+    /// Generates like [`PuzzleEntry::generate`], but gives up once `stop` is
+    /// set.
+    pub fn generate_with_stop(
+        size: u8,
+        seed: u64,
+        options: GenerateOptions,
+        stop: &AtomicBool,
+    ) -> Result<PuzzleEntry, &'static str> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let (puzzle, solution) = Binox::generate_with_stop(
+            size,
+            options.perfect,
+            options.extras,
+            options.symmetry,
+            &mut rng,
+            stop,
+        )?;
+        Ok(PuzzleEntry {
+            solution: Some(solution.as_string()),
+            seed: Some(seed),
+            options: Some(options),
+            ..PuzzleEntry::new(puzzle.as_string())
+        })
+    }
+}
+
+/// A named, ordered collection of puzzles with a cursor on the selected one.
+///
+/// Sets are stored in the v2 puzzle format: a `[name]` header line followed by
+/// one line per entry holding the puzzle string, difficulty, title, answer key,
+/// generation seed, generation options, pencil marks, seconds played, author,
+/// source, creation date and notes separated by tabs. Trailing fields may be
+/// omitted, and `-` stands for an unknown or missing field other than the
+/// title and the four after the seconds, which are left empty instead.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PuzzleSet {
+    pub name: String,
+    #[serde(rename = "puzzles")]
+    pub entries: Vec<PuzzleEntry>,
+    #[serde(skip)]
+    selected: usize,
+}
+
+/// A puzzle in a file that cannot be played as it is written, and where it
+/// is in the file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ImportProblem {
+    /// The puzzle's index among the puzzles of the file.
+    pub entry: usize,
+    /// The line of the file, counting from 1.
+    pub line: usize,
+    /// The character of the line where the problem is, counting from 1.
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ImportProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {}, character {}: {}",
+            self.line, self.column, self.message
+        )
+    }
+}
+
+/// A puzzle of a legacy puzzle file, with where it is and the directives
+/// that apply to it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PuzzleLine<'a> {
+    /// The line of the file, counting from 1.
+    pub line: usize,
+    /// How many characters come before the puzzle on its line.
+    pub indent: usize,
+    pub puzzle: &'a str,
+    /// From a `title:` line just before the puzzle.
+    pub title: Option<&'a str>,
+    /// From the last `size:` line before the puzzle.
+    pub size: Option<&'a str>,
+}
+
+/// The puzzles of a legacy puzzle file. Blank lines and `#` comments, on
+/// lines of their own or after a puzzle, are left out, and the whitespace
+/// around each puzzle is trimmed. A `title: ...` line names the puzzle that
+/// follows it, and a `size: 8` line gives the size of all the puzzles after
+/// it.
+pub fn puzzle_lines(contents: &str) -> Vec<PuzzleLine<'_>> {
+    let mut result = Vec::new();
+    let (mut title, mut size) = (None, None);
+    for (i, line) in contents.lines().enumerate() {
+        let text = line.split('#').next().unwrap_or_default();
+        let puzzle = text.trim();
+        if puzzle.is_empty() {
+            continue;
+        }
+        if let Some((key, value)) = puzzle.split_once(':') {
+            match key.trim().to_lowercase().as_str() {
+                "title" => {
+                    title = Some(value.trim());
+                    continue;
+                }
+                "size" => {
+                    size = Some(value.trim());
+                    continue;
+                }
+                _ => (),
+            }
+        }
+        result.push(PuzzleLine {
+            line: i + 1,
+            indent: text.chars().take_while(|c| c.is_whitespace()).count(),
+            puzzle,
+            title: title.take(),
+            size,
+        });
+    }
+    result
+}
+
+/// Finds the puzzles of a legacy puzzle file, or with `manifest` of a
+/// `.binoxset` manifest, that have characters other than X, O and dots, a
+/// length that makes no board, or a size other than the one a `size:` line
+/// gives or, without one, the first puzzle's.
+pub fn import_problems(contents: &str, manifest: bool) -> Vec<ImportProblem> {
+    let mut problems = Vec::new();
+    let mut first_size = None;
+    let lines = if manifest {
+        contents
+            .lines()
+            .enumerate()
+            .skip(1)
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(i, line)| PuzzleLine {
+                line: i + 1,
+                indent: 0,
+                puzzle: line.split('\t').next().unwrap_or_default(),
+                title: None,
+                size: None,
+            })
+            .collect()
+    } else {
+        puzzle_lines(contents)
+    };
+    for (entry, line) in lines.into_iter().enumerate() {
+        let puzzle = line.puzzle;
+        let problem = |column: usize, message| ImportProblem {
+            entry,
+            line: line.line,
+            column: line.indent + column,
+            message,
+        };
+        let invalid = puzzle
+            .chars()
+            .enumerate()
+            .find(|(_, c)| !matches!(c, 'x' | 'X' | 'o' | 'O' | '.' | ' '));
+        if let Some((position, c)) = invalid {
+            let message = format!("invalid character '{}'", c.escape_default());
+            problems.push(problem(position + 1, message));
+            continue;
+        }
+        match Binox::parse_strict(puzzle) {
+            Err(message) => problems.push(problem(puzzle.chars().count() + 1, message)),
+            Ok(binox) => {
+                let size = match line.size.map(str::parse::<u8>) {
+                    Some(Ok(size)) => size,
+                    Some(Err(_)) => {
+                        let message = format!("invalid size '{}'", line.size.unwrap());
+                        problems.push(problem(1, message));
+                        continue;
+                    }
+                    None => *first_size.get_or_insert(binox.size()),
+                };
+                if binox.size() != size {
+                    let message =
+                        format!("{0}x{0} puzzle among {1}x{1} puzzles", binox.size(), size);
+                    problems.push(problem(1, message));
+                }
+            }
+        }
+    }
+    problems
+}
+
+impl PuzzleSet {
+    pub fn new(name: String, entries: Vec<PuzzleEntry>) -> Self {
+        PuzzleSet {
+            name,
+            entries,
+            selected: 0,
+        }
+    }
+
+    /// Builds a set from the lines of a legacy puzzle file, one puzzle per
+    /// line, as read by [`puzzle_lines`].
+    pub fn from_lines(name: String, contents: &str, difficulty: Option<Difficulty>) -> Self {
+        let entries = puzzle_lines(contents)
+            .into_iter()
+            .map(|line| PuzzleEntry {
+                difficulty,
+                title: line.title.map(String::from),
+                ..PuzzleEntry::new(line.puzzle.into())
+            })
+            .collect();
+        PuzzleSet::new(name, entries)
+    }
+
+    pub fn parse_manifest(contents: &str) -> Result<Self, String> {
+        let mut lines = contents.lines().enumerate();
+        let name = match lines.next() {
+            Some((_, line)) if line.starts_with('[') && line.trim_end().ends_with(']') => {
+                line.trim_end()[1..line.trim_end().len() - 1].to_string()
+            }
+            _ => return Err("manifest must start with a [name] line".into()),
+        };
+        let mut entries = Vec::new();
+        for (i, line) in lines {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let entry =
+                PuzzleEntry::from_record(line).map_err(|e| format!("line {}: {e}", i + 1))?;
+            entries.push(entry);
+        }
+        Ok(PuzzleSet::new(name, entries))
+    }
+
+    pub fn manifest_string(&self) -> String {
+        let mut result = format!("[{}]\n", self.name);
+        for entry in &self.entries {
+            result.push_str(&entry.to_record());
+            result.push('\n');
+        }
+        result
+    }
+
+    /// Loads a set from a manifest file, or from a legacy puzzle file when the
+    /// extension is not `.binoxset`.
+    pub fn load(filename: &str) -> Result<Self, String> {
+        Ok(PuzzleSet::load_checked(filename)?.0)
+    }
+
+    /// Loads a set like [`PuzzleSet::load`], together with the problems of
+    /// its puzzles, which are loaded all the same.
+    pub fn load_checked(filename: &str) -> Result<(Self, Vec<ImportProblem>), String> {
+        let contents =
+            fs::read_to_string(filename).map_err(|_| format!("file not found: {filename}"))?;
+        let path = Path::new(filename);
+        if path.extension().and_then(|e| e.to_str()) == Some(SET_EXTENSION) {
+            let set = PuzzleSet::parse_manifest(&contents)?;
+            return Ok((set, import_problems(&contents, true)));
+        }
+        let name = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or(filename);
+        let set =
+            PuzzleSet::from_lines(name.into(), &contents, Difficulty::from_file_name(filename));
+        Ok((set, import_problems(&contents, false)))
+    }
+
+    /// Takes out the puzzles that have problems, and selects the first one
+    /// left.
+    pub fn skip_problems(&mut self, problems: &[ImportProblem]) {
+        let mut index = 0;
+        self.entries.retain(|_| {
+            index += 1;
+            !problems.iter().any(|problem| problem.entry == index - 1)
+        });
+        self.selected = 0;
+    }
+
+    /// The puzzle strings alone, one per line, as in a legacy puzzle file.
+    pub fn lines_string(&self) -> String {
+        let mut result = String::new();
+        for entry in &self.entries {
+            if let Some(title) = &entry.title {
+                result.push_str(&format!("title: {title}\n"));
+            }
+            result.push_str(&entry.puzzle);
+            result.push('\n');
+        }
+        result
+    }
+
+    /// Writes the set as a manifest when the extension is `.binoxset`, or as
+    /// a legacy puzzle file otherwise, which keeps titles as `title:` lines
+    /// but drops grades and answer keys.
+    pub fn write(&self, filename: &str) -> Result<(), String> {
+        if Path::new(filename).extension().and_then(|e| e.to_str()) == Some(SET_EXTENSION) {
+            return self.save(filename);
+        }
+        fs::write(filename, self.lines_string())
+            .map_err(|_| format!("could not write file: {filename}"))
+    }
+
+    pub fn save(&self, filename: &str) -> Result<(), String> {
+        fs::write(filename, self.manifest_string())
+            .map_err(|_| format!("could not write file: {filename}"))
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    pub fn current(&self) -> &PuzzleEntry {
+        &self.entries[self.selected]
+    }
+
+    /// Replaces the selected entry, metadata and all.
+    pub fn replace_current(&mut self, entry: PuzzleEntry) {
+        self.entries[self.selected] = entry;
+    }
+
+    /// Replaces the puzzle string of the selected entry, keeping its metadata.
+    pub fn update_current(&mut self, puzzle: String) {
+        self.entries[self.selected].puzzle = puzzle;
+    }
+
+    /// Names the selected puzzle, or removes its name with `None`. All
+    /// whitespace in it becomes single spaces.
+    pub fn set_title(&mut self, title: Option<&str>) {
+        self.entries[self.selected].title = title.and_then(single_line);
+    }
+
+    /// Sets a descriptive field of the selected puzzle, as
+    /// [`PuzzleEntry::set_meta`] does.
+    pub fn set_meta(&mut self, field: MetaField, value: Option<&str>) -> Result<(), String> {
+        self.entries[self.selected].set_meta(field, value)
+    }
+
+    /// Keeps the pencil marks and the time spent on the selected puzzle.
+    pub fn update_progress(&mut self, marks: Option<String>, elapsed: u64) {
+        let entry = &mut self.entries[self.selected];
+        entry.marks = marks;
+        entry.elapsed = Some(elapsed).filter(|&e| e > 0);
+    }
+
+    pub fn select(&mut self, index: usize) -> Result<&PuzzleEntry, &'static str> {
+        if index >= self.entries.len() {
+            return Err("there is no puzzle with that number");
+        }
+        self.selected = index;
+        Ok(self.current())
+    }
+
+    /// Appends a puzzle to the set and returns its number.
+    pub fn add(&mut self, entry: PuzzleEntry) -> usize {
+        self.entries.push(entry);
+        self.entries.len() - 1
+    }
+
+    /// Removes a puzzle from the set. The selection stays on the same
+    /// puzzle, or moves to the one after it if that is the one removed.
+    pub fn remove(&mut self, index: usize) -> Result<PuzzleEntry, &'static str> {
+        if index >= self.entries.len() {
+            return Err("there is no puzzle with that number");
+        }
+        if self.entries.len() == 1 {
+            return Err("the last puzzle of a collection cannot be removed");
+        }
+        let entry = self.entries.remove(index);
+        if self.selected > index || self.selected == self.entries.len() {
+            self.selected -= 1;
+        }
+        Ok(entry)
+    }
+
+    /// Puts the puzzles in a random order, the same for the same seed, and
+    /// keeps the selected puzzle selected. Returns the order, in which the
+    /// puzzle now at `i` was at `order[i]` before.
+    pub fn shuffle(&mut self, seed: u64) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..self.entries.len()).collect();
+        order.shuffle(&mut StdRng::seed_from_u64(seed));
+        let mut entries: Vec<Option<PuzzleEntry>> = std::mem::take(&mut self.entries)
+            .into_iter()
+            .map(Some)
+            .collect();
+        self.entries = order.iter().map(|&i| entries[i].take().unwrap()).collect();
+        self.selected = order.iter().position(|&i| i == self.selected).unwrap_or(0);
+        order
+    }
+
+    pub fn next_puzzle(&mut self) -> &PuzzleEntry {
+        self.selected = if self.selected >= self.entries.len() - 1 {
+            0
+        } else {
+            self.selected + 1
+        };
+        self.current()
+    }
+
+    pub fn previous_puzzle(&mut self) -> &PuzzleEntry {
+        self.selected = if self.selected == 0 {
+            self.entries.len() - 1
+        } else {
+            self.selected - 1
+        };
+        self.current()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn tolerant_files() {
+        let contents = "# starter puzzles\n\n  title: First one\nX...............  # easy\nsize: 4\n\t..O.............\n";
+        let set = PuzzleSet::from_lines("file".into(), contents, None);
+        assert_eq!(set.len(), 2);
+        assert_eq!(set.entries[0].puzzle, "X...............");
+        assert_eq!(set.entries[0].title.as_deref(), Some("First one"));
+        assert_eq!(set.entries[1].title, None);
+        let reread = PuzzleSet::from_lines("again".into(), &set.lines_string(), None);
+        assert_eq!(reread.entries[0].title.as_deref(), Some("First one"));
+        assert_eq!(reread.entries[1].title, None);
+        assert!(import_problems(contents, false).is_empty());
+        let problems = import_problems("size: 6\n  X..q\nX...............\n", false);
+        assert_eq!((problems[0].line, problems[0].column), (2, 6));
+        assert_eq!(problems[1].message, "4x4 puzzle among 6x6 puzzles");
+    }
+
+    #[test]
+    fn problems_with_positions() {
+        let contents = "X...............\n\nX..q............\nX.O.............O\n....O.......X.......................\n..O.............\n";
+        let problems = import_problems(contents, false);
+        let found: Vec<_> = problems
+            .iter()
+            .map(|problem| (problem.entry, problem.line, problem.column))
+            .collect();
+        assert_eq!(found, [(1, 3, 4), (2, 4, 18), (3, 5, 1)]);
+        assert_eq!(
+            problems[0].to_string(),
+            "line 3, character 4: invalid character 'q'"
+        );
+        assert_eq!(problems[2].message, "6x6 puzzle among 4x4 puzzles");
+        let mut set = PuzzleSet::from_lines("file".into(), contents, None);
+        set.skip_problems(&problems);
+        assert_eq!(set.len(), 2);
+        assert_eq!(set.entries[1].puzzle, "..O.............");
+        let manifest = "[set]\nX...............\thard\nX..q\n";
+        assert_eq!(import_problems(manifest, true)[0].line, 3);
+    }
+
+    #[test]
+    fn manifest_round_trip() {
+        let mut set = PuzzleSet::from_lines("starter".into(), "X...\n\n.O..\n", None);
+        assert_eq!(set.len(), 2);
+        set.entries[0].difficulty = Some(Difficulty::Hard);
+        set.entries[1].title = Some("second puzzle".into());
+        set.entries[1].solution = Some("XOXO".into());
+        set.entries[1].seed = Some(7);
+        set.entries[1].options = Some(GenerateOptions {
+            perfect: true,
+            extras: 3,
+            symmetry: Symmetry::Mirror,
+        });
+        let parsed = PuzzleSet::parse_manifest(&set.manifest_string()).unwrap();
+        assert_eq!(parsed, set);
+        assert!(!set.manifest_string().contains("\t-\n"));
+        assert!(PuzzleSet::parse_manifest("X...\n").is_err());
+        assert!(PuzzleSet::parse_manifest("[a]\nX...\tsilly\n").is_err());
+    }
+
+    #[test]
+    fn record_round_trip() {
+        let entry = PuzzleEntry {
+            title: Some("-".into()),
+            marks: Some("..x.+...........".into()),
+            elapsed: Some(95),
+            ..PuzzleEntry::new("Xo..............".into())
+        };
+        let record = entry.to_record();
+        assert_eq!(
+            record,
+            "Xo..............\t-\t-\t-\t-\t-\t..x.+...........\t95"
+        );
+        assert_eq!(PuzzleEntry::from_record(&record), Ok(entry));
+        let plain = PuzzleEntry::new("X...............".into());
+        assert_eq!(plain.to_record(), "X...............");
+        assert_eq!(PuzzleEntry::from_record(&plain.to_record()), Ok(plain));
+        assert!(PuzzleEntry::from_record("X...\t-\t\t-\t-\t-\t-\tlong").is_err());
+    }
+
+    #[test]
+    fn shuffling() {
+        let lines: String = (0..8)
+            .map(|i| format!("{}\n", ".".repeat(i) + &"X".repeat(16 - i)))
+            .collect();
+        let mut set = PuzzleSet::from_lines("set".into(), &lines, None);
+        set.select(3).unwrap();
+        let original = set.clone();
+        let order = set.shuffle(42);
+        assert_ne!(set.entries, original.entries);
+        assert_eq!(set.current(), &original.entries[3]);
+        for (i, &before) in order.iter().enumerate() {
+            assert_eq!(set.entries[i], original.entries[before]);
+        }
+        let mut again = original.clone();
+        again.shuffle(42);
+        assert_eq!(again, set);
+    }
+
+    #[test]
+    fn metadata() {
+        let mut entry = PuzzleEntry::new("X...............".into());
+        entry.set_meta(MetaField::Author, Some("Ann\tLee")).unwrap();
+        entry
+            .set_meta(MetaField::Notes, Some("from the\nspring issue"))
+            .unwrap();
+        entry
+            .set_meta(MetaField::Created, Some("2024-05-17"))
+            .unwrap();
+        assert!(entry
+            .set_meta(MetaField::Created, Some("17/05/2024"))
+            .is_err());
+        assert!(entry
+            .set_meta(MetaField::Created, Some("2024-13-01"))
+            .is_err());
+        assert_eq!(entry.meta(MetaField::Author), Some("Ann Lee"));
+        assert_eq!(entry.meta(MetaField::Source), None);
+        let record = entry.to_record();
+        assert_eq!(
+            record,
+            "X...............\t-\t\t-\t-\t-\t-\t-\tAnn Lee\t\t2024-05-17\tfrom the spring issue"
+        );
+        assert_eq!(PuzzleEntry::from_record(&record), Ok(entry.clone()));
+        entry.set_meta(MetaField::Author, None).unwrap();
+        assert_eq!(entry.author, None);
+        assert_eq!("date".parse(), Ok(MetaField::Created));
+    }
+
+    #[test]
+    fn generated_again() {
+        let options = GenerateOptions {
+            perfect: true,
+            extras: 2,
+            symmetry: Symmetry::Rotational,
+        };
+        let entry = PuzzleEntry::generate(6, 42, options).unwrap();
+        assert_eq!(entry, PuzzleEntry::generate(6, 42, options).unwrap());
+        assert_eq!(entry.seed, Some(42));
+        assert_eq!(
+            options.command(6, 42),
+            "binox generate 6 --seed 42 --perfect --extras 2 --symmetry rotational"
+        );
+        assert_eq!(options.to_string().parse(), Ok(options));
+        assert!("extras=two".parse::<GenerateOptions>().is_err());
+    }
+
+    #[test]
+    fn navigation_wraps() {
+        let mut set = PuzzleSet::from_lines("set".into(), "a\nb\nc", None);
+        assert_eq!(set.previous_puzzle().puzzle, "c");
+        assert_eq!(set.next_puzzle().puzzle, "a");
+        assert_eq!(set.next_puzzle().puzzle, "b");
+        set.update_current("B".into());
+        assert_eq!(set.entries[1].puzzle, "B");
+    }
+
+    #[test]
+    fn adding_and_removing() {
+        let mut set = PuzzleSet::from_lines("set".into(), "a\nb\nc", None);
+        assert_eq!(set.add(PuzzleEntry::new("d".into())), 3);
+        set.select(2).unwrap();
+        set.remove(0).unwrap();
+        assert_eq!(set.current().puzzle, "c");
+        set.remove(1).unwrap();
+        assert_eq!(set.current().puzzle, "d");
+        set.remove(1).unwrap();
+        assert_eq!(set.current().puzzle, "b");
+        assert!(set.remove(1).is_err());
+        assert!(set.remove(0).is_err());
+    }
+}
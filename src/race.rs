@@ -0,0 +1,368 @@
+use std::io::ErrorKind;
+use std::net::{TcpListener, TcpStream};
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde_json::{json, Value};
+use tungstenite::stream::MaybeTlsStream;
+use tungstenite::{Message, WebSocket};
+
+use crate::binox::{Binox, BinoxCell};
+use crate::binox_interpreter::{interpret, stdin_lines, BIR};
+use crate::completion::canonical;
+use crate::theme::current_theme;
+use crate::timer::format_duration;
+
+/// How long a connection waits for a message before checking for messages
+/// to send.
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// A race of several players on the same puzzle. The puzzle is sent to
+/// everyone at once when the last expected player joins, every player's
+/// progress is shared as they play, and the first to solve it wins.
+///
+/// Messages are JSON objects with a `type`. Players send `join {name}` and
+/// `progress {grid}`; the host sends `players {names}`, `puzzle {puzzle}`,
+/// `progress {name, percent}`, `winner {name, time}` and `error {message}`.
+pub struct Race {
+    puzzle: Binox,
+    expected: usize,
+    players: Vec<String>,
+    started: Option<Instant>,
+    winner: Option<String>,
+}
+
+impl Race {
+    pub fn new(puzzle: Binox, expected: usize) -> Self {
+        Race {
+            puzzle,
+            expected: expected.max(1),
+            players: Vec::new(),
+            started: None,
+            winner: None,
+        }
+    }
+
+    pub fn winner(&self) -> Option<&str> {
+        self.winner.as_deref()
+    }
+
+    /// Adds a player and returns the messages for everyone.
+    pub fn join(&mut self, name: &str) -> Result<Vec<Value>, String> {
+        if name.trim().is_empty() {
+            return Err("a name is required".into());
+        }
+        if self.started.is_some() {
+            return Err("the race has already started".into());
+        }
+        if self.players.iter().any(|player| player == name) {
+            return Err(format!("{name} is already taken"));
+        }
+        self.players.push(name.into());
+        let mut messages = vec![json!({
+            "type": "players",
+            "names": self.players,
+            "expected": self.expected,
+        })];
+        if self.players.len() >= self.expected {
+            self.started = Some(Instant::now());
+            messages.push(json!({ "type": "puzzle", "puzzle": self.puzzle.as_string() }));
+        }
+        Ok(messages)
+    }
+
+    /// Removes a player who has disconnected.
+    pub fn leave(&mut self, name: &str) {
+        self.players.retain(|player| player != name);
+    }
+
+    /// How much of the puzzle a grid has filled in, from 0 to 100.
+    fn percent(&self, grid: &Binox) -> u8 {
+        let size = self.puzzle.size();
+        let cells: Vec<(u8, u8)> = (0..size)
+            .flat_map(|row| (0..size).map(move |col| (row, col)))
+            .filter(|&(row, col)| self.puzzle.get_cell(row, col) == Ok(BinoxCell::EMPTY))
+            .collect();
+        let filled = cells
+            .iter()
+            .filter(|&&(row, col)| grid.get_cell(row, col) != Ok(BinoxCell::EMPTY))
+            .count();
+        (filled * 100 / cells.len().max(1)) as u8
+    }
+
+    /// Records a player's board and returns the messages for everyone.
+    pub fn progress(&mut self, name: &str, grid: &str) -> Result<Vec<Value>, String> {
+        let Some(started) = self.started else {
+            return Err("the race has not started yet".into());
+        };
+        if self.winner.is_some() {
+            return Err("the race is over".into());
+        }
+        let binox = Binox::parse_strict(grid)?;
+        if canonical(grid) != self.puzzle.as_string() {
+            return Err("that is not the race puzzle".into());
+        }
+        if !self.players.iter().any(|player| player == name) {
+            return Err("join the race first".into());
+        }
+        let percent = self.percent(&binox);
+        let mut messages = vec![json!({ "type": "progress", "name": name, "percent": percent })];
+        if binox.is_solved() {
+            self.winner = Some(name.into());
+            messages.push(json!({
+                "type": "winner",
+                "name": name,
+                "time": format_duration(started.elapsed()),
+            }));
+        }
+        Ok(messages)
+    }
+}
+
+fn error_message(text: &str) -> String {
+    json!({ "type": "error", "message": text }).to_string()
+}
+
+/// The race and a way to reach every connected player.
+struct Hub {
+    race: Race,
+    players: Vec<Sender<String>>,
+}
+
+impl Hub {
+    fn broadcast(&mut self, messages: &[Value]) {
+        for message in messages {
+            let text = message.to_string();
+            self.players
+                .retain(|player| player.send(text.clone()).is_ok());
+        }
+    }
+}
+
+/// Whether a failed read only means that nothing has arrived yet.
+fn is_timeout(error: &tungstenite::Error) -> bool {
+    matches!(error, tungstenite::Error::Io(e)
+        if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut))
+}
+
+fn handle_player(stream: TcpStream, hub: Arc<Mutex<Hub>>) -> Result<(), String> {
+    let mut socket = tungstenite::accept(stream).map_err(|e| e.to_string())?;
+    socket
+        .get_mut()
+        .set_read_timeout(Some(POLL_INTERVAL))
+        .map_err(|e| e.to_string())?;
+    let (sender, receiver) = mpsc::channel();
+    hub.lock().unwrap().players.push(sender.clone());
+    let mut name = None;
+    let result = loop {
+        match socket.read() {
+            Ok(Message::Text(text)) => {
+                let request: Value = serde_json::from_str(text.as_str()).unwrap_or_default();
+                let mut hub = hub.lock().unwrap();
+                let result = match (request["type"].as_str(), &name) {
+                    (Some("join"), None) => {
+                        let joining = request["name"].as_str().unwrap_or_default().trim();
+                        let result = hub.race.join(joining);
+                        if result.is_ok() {
+                            eprintln!("{joining} joined the race");
+                            name = Some(joining.to_string());
+                        }
+                        result
+                    }
+                    (Some("join"), Some(_)) => Err("you have already joined".into()),
+                    (Some("progress"), Some(name)) => {
+                        let grid = request["grid"].as_str().unwrap_or_default();
+                        hub.race.progress(name, grid)
+                    }
+                    (Some("progress"), None) => Err("join the race first".into()),
+                    _ => Err("unknown message".into()),
+                };
+                match result {
+                    Ok(messages) => {
+                        if let Some(winner) = messages.iter().find(|m| m["type"] == "winner") {
+                            eprintln!(
+                                "{} won the race",
+                                winner["name"].as_str().unwrap_or_default()
+                            );
+                        }
+                        hub.broadcast(&messages)
+                    }
+                    Err(text) => {
+                        let _ = sender.send(error_message(&text));
+                    }
+                }
+            }
+            Ok(Message::Close(_)) => break Ok(()),
+            Ok(_) => (),
+            Err(e) if is_timeout(&e) => (),
+            Err(e) => break Err(e.to_string()),
+        }
+        let sent = receiver
+            .try_iter()
+            .try_for_each(|text| socket.send(Message::text(text)));
+        if let Err(e) = sent {
+            break Err(e.to_string());
+        }
+    };
+    if let Some(name) = name {
+        hub.lock().unwrap().race.leave(&name);
+        eprintln!("{name} left the race");
+    }
+    result
+}
+
+/// Hosts a race on the given puzzle, starting once `players` have joined.
+pub fn host(address: &str, puzzle: Binox, players: usize) -> Result<(), String> {
+    let listener =
+        TcpListener::bind(address).map_err(|e| format!("could not listen on {address}: {e}"))?;
+    eprintln!("waiting for {players} players on ws://{address}");
+    let hub = Arc::new(Mutex::new(Hub {
+        race: Race::new(puzzle, players),
+        players: Vec::new(),
+    }));
+    for stream in listener.incoming() {
+        let Ok(stream) = stream else { continue };
+        let hub = Arc::clone(&hub);
+        thread::spawn(move || {
+            if let Err(text) = handle_player(stream, hub) {
+                eprintln!("{text}");
+            }
+        });
+    }
+    Ok(())
+}
+
+type Connection = WebSocket<MaybeTlsStream<TcpStream>>;
+
+fn send(socket: &mut Connection, message: Value) -> Result<(), String> {
+    socket
+        .send(Message::text(message.to_string()))
+        .map_err(|e| e.to_string())
+}
+
+/// Whether a line is a move, the only commands allowed in a race.
+fn is_move(line: &str) -> bool {
+    let word = line.split(' ').next().unwrap_or_default().to_lowercase();
+    matches!(word.as_str(), "x" | "o" | "erase") || current_theme().cell_for(&word).is_some()
+}
+
+/// Joins a race as a player, playing with the interpreter's move commands.
+pub fn join(url: &str, name: &str) -> Result<(), String> {
+    let (mut socket, _) =
+        tungstenite::connect(url).map_err(|e| format!("could not connect to {url}: {e}"))?;
+    if let MaybeTlsStream::Plain(stream) = socket.get_mut() {
+        stream
+            .set_read_timeout(Some(POLL_INTERVAL))
+            .map_err(|e| e.to_string())?;
+    }
+    send(&mut socket, json!({ "type": "join", "name": name }))?;
+    let lines = stdin_lines();
+    let mut binox: Option<Binox> = None;
+    loop {
+        match socket.read() {
+            Ok(Message::Text(text)) => {
+                let message: Value = serde_json::from_str(text.as_str()).unwrap_or_default();
+                match message["type"].as_str() {
+                    Some("players") => println!(
+                        "players: {} (waiting for {})",
+                        message["names"]
+                            .as_array()
+                            .map(|names| names
+                                .iter()
+                                .filter_map(Value::as_str)
+                                .collect::<Vec<_>>()
+                                .join(", "))
+                            .unwrap_or_default(),
+                        message["expected"]
+                    ),
+                    Some("puzzle") => {
+                        let puzzle = message["puzzle"].as_str().unwrap_or_default();
+                        let board = Binox::new_from_string(puzzle.into());
+                        println!("the race has started! use x, o and erase to play.\n{board}");
+                        binox = Some(board);
+                    }
+                    Some("progress") if message["name"] != name => {
+                        println!(
+                            "{}: {}%",
+                            message["name"].as_str().unwrap_or_default(),
+                            message["percent"]
+                        );
+                    }
+                    Some("winner") => {
+                        println!(
+                            "{} won the race in {}",
+                            message["name"].as_str().unwrap_or_default(),
+                            message["time"].as_str().unwrap_or_default()
+                        );
+                        let _ = socket.close(None);
+                        return Ok(());
+                    }
+                    Some("error") => {
+                        println!("{}", message["message"].as_str().unwrap_or_default())
+                    }
+                    _ => (),
+                }
+            }
+            Ok(Message::Close(_)) => return Err("the host closed the race".into()),
+            Ok(_) => (),
+            Err(e) if is_timeout(&e) => (),
+            Err(e) => return Err(e.to_string()),
+        }
+        for line in lines.try_iter() {
+            let Some(board) = binox.take() else {
+                println!("waiting for the race to start");
+                continue;
+            };
+            if !is_move(&line) {
+                println!("only x, o and erase can be used in a race");
+                binox = Some(board);
+                continue;
+            }
+            let (board, result) = interpret(board, line);
+            if let BIR::Error(text) = result {
+                println!("{text}");
+            } else {
+                println!("{board}");
+                send(
+                    &mut socket,
+                    json!({ "type": "progress", "grid": board.as_string() }),
+                )?;
+            }
+            binox = Some(board);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn first_to_solve_wins() {
+        let puzzle = Binox::parse_strict("XXO.O..X.O..X..O").unwrap();
+        let solution = puzzle.unique_solution().unwrap().as_string().to_lowercase();
+        let mut race = Race::new(puzzle, 2);
+        let messages = race.join("ann").unwrap();
+        assert_eq!(messages.len(), 1);
+        assert!(race.progress("ann", "XXO.O..X.O..X..O").is_err());
+        assert!(race.join("ann").is_err());
+        let messages = race.join("bob").unwrap();
+        assert_eq!(messages[1]["type"], "puzzle");
+        assert!(race.join("cat").is_err());
+        let messages = race.progress("bob", "XXOoO..X.O..X..O").unwrap();
+        assert_eq!(messages[0]["percent"], 12);
+        assert!(race.progress("bob", "XX..O..X.O..X..O").is_err());
+        let grid: String = "XXO.O..X.O..X..O"
+            .chars()
+            .zip(solution.chars())
+            .map(|(clue, cell)| if clue == '.' { cell } else { clue })
+            .collect();
+        let messages = race.progress("ann", &grid).unwrap();
+        assert_eq!(messages[0]["percent"], 100);
+        assert_eq!(messages[1]["name"], "ann");
+        assert_eq!(race.winner(), Some("ann"));
+        assert!(race.progress("bob", &grid).is_err());
+    }
+}
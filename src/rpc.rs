@@ -0,0 +1,304 @@
+use std::io::{self, BufRead, Write};
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use serde_json::{json, Value};
+
+use crate::binox::logic::Deduction;
+use crate::binox::{column_name, Binox, BinoxCell, BinoxSolution, Symmetry};
+use crate::difficulty::Difficulty;
+use crate::puzzle_set::PuzzleSet;
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+/// Failures of the puzzle itself, such as a move on a given cell.
+const PUZZLE_ERROR: i64 = 1;
+
+/// The name of a cell as typed in the interpreter, such as `c4`.
+pub fn cell_name(row: u8, col: u8) -> String {
+    format!("{}{row}", column_name(col))
+}
+
+/// A hint as a JSON object.
+pub fn deduction_value(deduction: &Deduction) -> Value {
+    json!({
+        "cell": cell_name(deduction.row, deduction.col),
+        "row": deduction.row,
+        "col": deduction.col,
+        "symbol": char::from(deduction.cell).to_string(),
+        "technique": deduction.technique.name(),
+        "explanation": deduction.explanation(),
+    })
+}
+
+struct Failure {
+    code: i64,
+    message: String,
+}
+
+fn invalid_params(message: impl ToString) -> Failure {
+    Failure {
+        code: INVALID_PARAMS,
+        message: message.to_string(),
+    }
+}
+
+fn puzzle_error(message: impl ToString) -> Failure {
+    Failure {
+        code: PUZZLE_ERROR,
+        message: message.to_string(),
+    }
+}
+
+fn u8_param(params: &Value, name: &str) -> Result<Option<u8>, Failure> {
+    match &params[name] {
+        Value::Null => Ok(None),
+        value => value
+            .as_u64()
+            .and_then(|n| u8::try_from(n).ok())
+            .map(Some)
+            .ok_or_else(|| invalid_params(format!("{name} must be a small number"))),
+    }
+}
+
+fn required_u8(params: &Value, name: &str) -> Result<u8, Failure> {
+    u8_param(params, name)?.ok_or_else(|| invalid_params(format!("{name} is required")))
+}
+
+fn str_param<'a>(params: &'a Value, name: &str) -> Result<Option<&'a str>, Failure> {
+    match &params[name] {
+        Value::Null => Ok(None),
+        value => value
+            .as_str()
+            .map(Some)
+            .ok_or_else(|| invalid_params(format!("{name} must be a string"))),
+    }
+}
+
+/// One puzzle driven by JSON-RPC 2.0 requests, one per line. A frontend
+/// starts `binox rpc` as a child process, writes requests to its stdin and
+/// reads one response line per request from its stdout.
+///
+/// Methods:
+/// - `generate {size, difficulty?, seed?}` starts a new puzzle
+/// - `import {puzzle}` or `import {file, index?}` loads a puzzle
+/// - `get_board` returns the board
+/// - `set_cell {row, col, symbol}` places `"X"` or `"O"`, or clears with `""`
+/// - `hint` returns the next deducible cell, or null
+/// - `solve` fills in the unique solution
+///
+/// Every method that changes the board returns it as `get_board` does.
+#[derive(Default)]
+pub struct Session {
+    binox: Option<Binox>,
+}
+
+impl Session {
+    fn binox(&mut self) -> Result<&mut Binox, Failure> {
+        self.binox
+            .as_mut()
+            .ok_or_else(|| puzzle_error("no puzzle is loaded; call generate or import first"))
+    }
+
+    fn board(&mut self) -> Result<Value, Failure> {
+        let binox = self.binox()?;
+        Ok(json!({
+            "puzzle": binox.as_string(),
+            "size": binox.size(),
+            "valid": binox.is_valid(),
+            "solved": binox.is_solved(),
+        }))
+    }
+
+    fn call(&mut self, method: &str, params: &Value) -> Result<Value, Failure> {
+        match method {
+            "get_board" => self.board(),
+            "set_cell" => {
+                let (row, col) = (required_u8(params, "row")?, required_u8(params, "col")?);
+                let cell = match str_param(params, "symbol")?
+                    .map(str::to_uppercase)
+                    .as_deref()
+                {
+                    Some("X") => BinoxCell::X,
+                    Some("O") => BinoxCell::O,
+                    Some("") => BinoxCell::EMPTY,
+                    _ => return Err(invalid_params("symbol must be \"X\", \"O\" or \"\"")),
+                };
+                self.binox()?
+                    .set_cell(row, col, cell)
+                    .map_err(puzzle_error)?;
+                self.board()
+            }
+            "hint" => {
+                let binox = self.binox()?;
+                if matches!(binox.solve(false), BinoxSolution::Zero) {
+                    return Err(puzzle_error("a mistake has been made"));
+                }
+                Ok(binox
+                    .next_deduction()
+                    .map_or(Value::Null, |deduction| deduction_value(&deduction)))
+            }
+            "solve" => {
+                let binox = self.binox()?;
+                match binox.solve(true) {
+                    BinoxSolution::One(solution) => *binox = solution,
+                    BinoxSolution::Zero => return Err(puzzle_error("puzzle has no solution")),
+                    BinoxSolution::Multiple(..) => {
+                        return Err(puzzle_error("multiple solutions found"))
+                    }
+                }
+                self.board()
+            }
+            "generate" => {
+                let size = required_u8(params, "size")?;
+                let difficulty = str_param(params, "difficulty")?
+                    .map(str::parse::<Difficulty>)
+                    .transpose()
+                    .map_err(invalid_params)?;
+                let mut rng = match &params["seed"] {
+                    Value::Null => StdRng::from_entropy(),
+                    seed => StdRng::seed_from_u64(
+                        seed.as_u64()
+                            .ok_or_else(|| invalid_params("seed must be a number"))?,
+                    ),
+                };
+                let extras = difficulty.map_or(0, |d| d.extras(size));
+                let (binox, _) = Binox::generate_with_rng(
+                    size,
+                    difficulty.is_some(),
+                    extras,
+                    Symmetry::None,
+                    &mut rng,
+                )
+                .map_err(invalid_params)?;
+                self.binox = Some(binox);
+                self.board()
+            }
+            "import" => {
+                let puzzle = match (str_param(params, "puzzle")?, str_param(params, "file")?) {
+                    (Some(puzzle), None) => puzzle.to_string(),
+                    (None, Some(file)) => {
+                        let puzzles = PuzzleSet::load(file).map_err(puzzle_error)?;
+                        let index = params["index"].as_u64().unwrap_or(0) as usize;
+                        puzzles
+                            .entries
+                            .get(index)
+                            .ok_or_else(|| invalid_params("index is past the end of the file"))?
+                            .puzzle
+                            .clone()
+                    }
+                    _ => return Err(invalid_params("give either puzzle or file")),
+                };
+                self.binox = Some(Binox::parse_strict(&puzzle).map_err(invalid_params)?);
+                self.board()
+            }
+            _ => Err(Failure {
+                code: METHOD_NOT_FOUND,
+                message: format!("unknown method: {method}"),
+            }),
+        }
+    }
+
+    /// Answers one request line. Notifications, which have no id, get no
+    /// answer.
+    pub fn handle(&mut self, line: &str) -> Option<Value> {
+        let request: Value = match serde_json::from_str(line) {
+            Ok(request) => request,
+            Err(e) => return Some(error_response(Value::Null, PARSE_ERROR, e.to_string())),
+        };
+        let id = request.get("id").cloned();
+        let Some(method) = request["method"].as_str() else {
+            return Some(error_response(
+                id.unwrap_or_default(),
+                INVALID_REQUEST,
+                "method must be a string".into(),
+            ));
+        };
+        let result = self.call(method, &request["params"]);
+        let id = id?;
+        Some(match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err(failure) => error_response(id, failure.code, failure.message),
+        })
+    }
+}
+
+fn error_response(id: Value, code: i64, message: String) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": code, "message": message },
+    })
+}
+
+/// Answers requests from stdin until it is closed.
+pub fn run_rpc() -> Result<(), String> {
+    let mut session = Session::default();
+    let mut stdout = io::stdout().lock();
+    for line in io::stdin().lock().lines() {
+        let line = line.map_err(|e| e.to_string())?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(response) = session.handle(&line) {
+            writeln!(stdout, "{response}")
+                .and_then(|_| stdout.flush())
+                .map_err(|e| e.to_string())?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn call(session: &mut Session, request: &str) -> Value {
+        session.handle(request).unwrap()
+    }
+
+    #[test]
+    fn protocol() {
+        let mut session = Session::default();
+        let response = call(
+            &mut session,
+            r#"{"jsonrpc":"2.0","id":1,"method":"get_board"}"#,
+        );
+        assert_eq!(response["error"]["code"], PUZZLE_ERROR);
+        let response = call(
+            &mut session,
+            r#"{"jsonrpc":"2.0","id":2,"method":"import","params":{"puzzle":"XXO.O..X.O..X..O"}}"#,
+        );
+        assert_eq!(response["id"], 2);
+        assert_eq!(response["result"]["size"], 4);
+        let response = call(&mut session, r#"{"jsonrpc":"2.0","id":3,"method":"hint"}"#);
+        let hint = &response["result"];
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": 4,
+            "method": "set_cell",
+            "params": {"row": hint["row"], "col": hint["col"], "symbol": hint["symbol"]},
+        });
+        let response = call(&mut session, &request.to_string());
+        assert_eq!(response["result"]["valid"], true);
+        let request = r#"{"jsonrpc":"2.0","id":5,"method":"set_cell","params":{"row":0,"col":0,"symbol":"O"}}"#;
+        assert_eq!(call(&mut session, request)["error"]["code"], PUZZLE_ERROR);
+        let response = call(&mut session, r#"{"jsonrpc":"2.0","id":6,"method":"solve"}"#);
+        assert_eq!(response["result"]["solved"], true);
+        let request =
+            r#"{"jsonrpc":"2.0","id":7,"method":"generate","params":{"size":6,"seed":7}}"#;
+        let generated = call(&mut session, request);
+        assert_eq!(generated["result"]["size"], 6);
+        assert_eq!(call(&mut session, request), generated);
+        assert!(session
+            .handle(r#"{"jsonrpc":"2.0","method":"get_board"}"#)
+            .is_none());
+        assert_eq!(call(&mut session, "not json")["error"]["code"], PARSE_ERROR);
+        let response = call(&mut session, r#"{"jsonrpc":"2.0","id":8,"method":"fly"}"#);
+        assert_eq!(response["error"]["code"], METHOD_NOT_FOUND);
+        let request = r#"{"jsonrpc":"2.0","id":9,"method":"set_cell","params":{"row":0}}"#;
+        assert_eq!(call(&mut session, request)["error"]["code"], INVALID_PARAMS);
+    }
+}
@@ -0,0 +1,285 @@
+use std::collections::HashMap;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde_json::{json, Value};
+use tiny_http::{Header, Method, Response, Server};
+
+use crate::binox::{seed_from_str, Binox, BinoxSolution, Symmetry};
+use crate::difficulty::Difficulty;
+use crate::rpc::{cell_name, deduction_value};
+use crate::timer;
+
+const DEFAULT_SIZE: u8 = 8;
+
+/// The value of one `name=value` pair in a query string.
+fn query_value<'a>(query: &'a str, name: &str) -> Option<&'a str> {
+    query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|&(key, _)| key == name)
+        .map(|(_, value)| value)
+}
+
+fn bad_request(text: impl ToString) -> (u16, Value) {
+    (400, json!({ "error": text.to_string() }))
+}
+
+/// Generates a puzzle the way `binox generate` does, so the same size,
+/// difficulty and seed give the same puzzle in both.
+fn generate(query: &str, seed: u64) -> Result<(Binox, Option<Difficulty>), String> {
+    let size = match query_value(query, "size") {
+        Some(size) => size.parse().map_err(|_| "size must be a number")?,
+        None => DEFAULT_SIZE,
+    };
+    let difficulty = query_value(query, "difficulty")
+        .map(str::parse::<Difficulty>)
+        .transpose()?;
+    let extras = difficulty.map_or(0, |d| d.extras(size));
+    let mut rng = StdRng::seed_from_u64(seed);
+    let (binox, _) =
+        Binox::generate_with_rng(size, difficulty.is_some(), extras, Symmetry::None, &mut rng)?;
+    Ok((binox, difficulty))
+}
+
+fn puzzle_response(query: &str, seed: u64) -> (u16, Value) {
+    match generate(query, seed) {
+        Ok((binox, difficulty)) => (
+            200,
+            json!({
+                "puzzle": binox.as_string(),
+                "size": binox.size(),
+                "difficulty": difficulty,
+                "seed": seed,
+            }),
+        ),
+        Err(text) => bad_request(text),
+    }
+}
+
+/// Reads the `grid` field of a request body.
+fn grid(body: &str) -> Result<Binox, String> {
+    let body: Value = serde_json::from_str(body).map_err(|e| format!("invalid JSON: {e}"))?;
+    let grid = body["grid"]
+        .as_str()
+        .ok_or("the body must have a grid string")?;
+    Binox::parse_strict(grid)
+}
+
+fn verify(body: &str) -> (u16, Value) {
+    let binox = match grid(body) {
+        Ok(binox) => binox,
+        Err(text) => return bad_request(text),
+    };
+    let mistakes: Vec<String> = match binox.unique_solution() {
+        Some(solution) => binox
+            .differences(&solution)
+            .into_iter()
+            .map(|(row, col)| cell_name(row, col))
+            .collect(),
+        None => {
+            return (
+                422,
+                json!({ "error": "puzzle does not have exactly one solution" }),
+            )
+        }
+    };
+    (
+        200,
+        json!({
+            "valid": binox.is_valid(),
+            "solved": binox.is_solved(),
+            "mistakes": mistakes,
+        }),
+    )
+}
+
+fn hint(body: &str) -> (u16, Value) {
+    let binox = match grid(body) {
+        Ok(binox) => binox,
+        Err(text) => return bad_request(text),
+    };
+    if matches!(binox.solve(false), BinoxSolution::Zero) {
+        return (422, json!({ "error": "a mistake has been made" }));
+    }
+    match binox.next_deduction() {
+        Some(deduction) => (200, deduction_value(&deduction)),
+        None => (422, json!({ "error": "no cell can be deduced" })),
+    }
+}
+
+/// The times sent in for each day's puzzles, so that each can be ranked
+/// among the others.
+#[derive(Debug, Default)]
+pub struct DailyTimes {
+    times: HashMap<(u64, String), Vec<u64>>,
+}
+
+impl DailyTimes {
+    /// Answers `POST /daily/time`, whose body has the `day`, `size` and
+    /// `difficulty` of a daily puzzle, the solved `grid` and the `seconds` it
+    /// took. The answer is the time's `rank` among the day's times, from 1,
+    /// and the number of `times` so far.
+    pub fn record(&mut self, body: &str, today: u64) -> (u16, Value) {
+        let solved = match grid(body) {
+            Ok(binox) => binox,
+            Err(text) => return bad_request(text),
+        };
+        let body: Value = serde_json::from_str(body).unwrap_or_default();
+        let Some(seconds) = body["seconds"].as_u64() else {
+            return bad_request("seconds must be a number");
+        };
+        if body["day"].as_u64() != Some(today) {
+            return (
+                422,
+                json!({ "error": "times are only taken for today's puzzle" }),
+            );
+        }
+        let mut query = format!("size={}", solved.size());
+        if let Some(difficulty) = body["difficulty"].as_str() {
+            query.push_str(&format!("&difficulty={difficulty}"));
+        }
+        let daily = match generate(&query, today) {
+            Ok((daily, _)) => daily,
+            Err(text) => return bad_request(text),
+        };
+        let mut clues = solved.clone();
+        clues.reset();
+        if !solved.is_solved() || clues.as_string() != daily.as_string() {
+            return (
+                422,
+                json!({ "error": "the grid is not a solution of today's puzzle" }),
+            );
+        }
+        let times = self.times.entry((today, query)).or_default();
+        times.push(seconds);
+        let rank = times.iter().filter(|&&time| time < seconds).count() + 1;
+        (
+            200,
+            json!({ "day": today, "rank": rank, "times": times.len() }),
+        )
+    }
+}
+
+/// Answers one request. `today` picks the daily puzzle, which is the same for
+/// everyone on a given day and size.
+pub fn respond(method: &str, url: &str, body: &str, today: u64) -> (u16, Value) {
+    let (path, query) = url.split_once('?').unwrap_or((url, ""));
+    match (method, path) {
+        ("GET", "/puzzle") => {
+            let seed = match query_value(query, "seed") {
+                Some(seed) => seed_from_str(seed),
+                None => rand::thread_rng().gen(),
+            };
+            puzzle_response(query, seed)
+        }
+        ("GET", "/daily") => {
+            let (status, mut value) = puzzle_response(query, today);
+            value["day"] = json!(today);
+            (status, value)
+        }
+        ("POST", "/verify") => verify(body),
+        ("POST", "/hint") => hint(body),
+        (_, "/puzzle" | "/daily" | "/daily/time" | "/verify" | "/hint") => (
+            405,
+            json!({ "error": format!("{method} is not allowed here") }),
+        ),
+        _ => (404, json!({ "error": format!("no such endpoint: {path}") })),
+    }
+}
+
+/// Serves puzzles over HTTP until the process is stopped.
+pub fn serve(address: &str) -> Result<(), String> {
+    let server =
+        Server::http(address).map_err(|e| format!("could not listen on {address}: {e}"))?;
+    eprintln!("serving puzzles on http://{address}");
+    let content_type = Header::from_bytes("Content-Type", "application/json").unwrap();
+    let mut times = DailyTimes::default();
+    for mut request in server.incoming_requests() {
+        let mut body = String::new();
+        let (status, value) = match request.as_reader().read_to_string(&mut body) {
+            Ok(_) => {
+                let method = match request.method() {
+                    Method::Get => "GET",
+                    Method::Post => "POST",
+                    _ => "OTHER",
+                };
+                if method == "POST" && request.url() == "/daily/time" {
+                    times.record(&body, timer::today())
+                } else {
+                    respond(method, request.url(), &body, timer::today())
+                }
+            }
+            Err(_) => bad_request("the body must be UTF-8 text"),
+        };
+        let response = Response::from_string(value.to_string())
+            .with_status_code(status)
+            .with_header(content_type.clone());
+        if let Err(e) = request.respond(response) {
+            eprintln!("could not answer a request: {e}");
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn endpoints() {
+        let (status, puzzle) = respond("GET", "/puzzle?size=6&seed=7&difficulty=hard", "", 0);
+        assert_eq!(status, 200);
+        assert_eq!(puzzle["size"], 6);
+        assert_eq!(puzzle["difficulty"], "hard");
+        let (_, again) = respond("GET", "/puzzle?seed=7&size=6&difficulty=h", "", 0);
+        assert_eq!(again["puzzle"], puzzle["puzzle"]);
+        let (_, daily) = respond("GET", "/daily?size=6", "", 20000);
+        assert_eq!(daily["day"], 20000);
+        assert_eq!(daily, respond("GET", "/daily?size=6", "", 20000).1);
+        assert_eq!(respond("GET", "/puzzle?size=5", "", 0).0, 400);
+
+        let body = r#"{"grid": "XXO.O..X.O..X..O"}"#;
+        let (status, verified) = respond("POST", "/verify", body, 0);
+        assert_eq!(status, 200);
+        assert_eq!(verified["solved"], false);
+        assert_eq!(verified["mistakes"], json!([]));
+        let (_, verified) = respond("POST", "/verify", r#"{"grid": "XXOxO..X.O..X..O"}"#, 0);
+        assert_eq!(verified["mistakes"], json!(["d0"]));
+        let (status, hint) = respond("POST", "/hint", body, 0);
+        assert_eq!(status, 200);
+        assert!(hint["explanation"].is_string());
+        assert_eq!(respond("POST", "/hint", "{}", 0).0, 400);
+        assert_eq!(respond("DELETE", "/hint", "", 0).0, 405);
+        assert_eq!(respond("GET", "/nothing", "", 0).0, 404);
+    }
+
+    #[test]
+    fn daily_times() {
+        let (_, daily) = respond("GET", "/daily?size=4", "", 100);
+        let puzzle = Binox::new_from_string(daily["puzzle"].as_str().unwrap().into());
+        let solved = puzzle.unique_solution().unwrap();
+        let body = |seconds: u64, day: u64, grid: &str| {
+            json!({ "day": day, "size": 4, "grid": grid, "seconds": seconds }).to_string()
+        };
+        let mut times = DailyTimes::default();
+        let grid = solved.as_string();
+        let (status, ranked) = times.record(&body(90, 100, &grid), 100);
+        assert_eq!(status, 200);
+        assert_eq!(
+            (ranked["rank"].as_u64(), ranked["times"].as_u64()),
+            (Some(1), Some(1))
+        );
+        let (_, ranked) = times.record(&body(60, 100, &grid), 100);
+        assert_eq!(
+            (ranked["rank"].as_u64(), ranked["times"].as_u64()),
+            (Some(1), Some(2))
+        );
+        let (_, ranked) = times.record(&body(120, 100, &grid), 100);
+        assert_eq!(ranked["rank"], 3);
+        assert_eq!(times.record(&body(60, 99, &grid), 100).0, 422);
+        let unsolved = puzzle.as_string();
+        assert_eq!(times.record(&body(60, 100, &unsolved), 100).0, 422);
+        assert_eq!(times.record("{}", 100).0, 400);
+    }
+}
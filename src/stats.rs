@@ -0,0 +1,291 @@
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::completion::canonical;
+use crate::difficulty::Difficulty;
+
+const STATS_FILE_NAME: &str = ".binox_stats.toml";
+
+/// The stats file lives in the home directory, or the working directory if
+/// no home directory is known.
+pub fn stats_path() -> PathBuf {
+    match env::var_os("HOME").or_else(|| env::var_os("USERPROFILE")) {
+        Some(home) => PathBuf::from(home).join(STATS_FILE_NAME),
+        None => PathBuf::from(STATS_FILE_NAME),
+    }
+}
+
+/// One solved puzzle.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Solve {
+    pub size: u8,
+    pub difficulty: Option<Difficulty>,
+    pub seconds: u64,
+    #[serde(default)]
+    pub hints: usize,
+    /// What the hints cost, with more for hints that give more away.
+    #[serde(default)]
+    pub hint_points: usize,
+    /// The day of the solve, as returned by [`crate::timer::today`].
+    pub day: u64,
+}
+
+/// One timed challenge, from start until the clock ran out or the player
+/// quit.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ChallengeRun {
+    pub size: u8,
+    pub difficulty: Difficulty,
+    /// The length of the clock.
+    pub seconds: u64,
+    pub solved: usize,
+    pub hints: usize,
+    #[serde(default)]
+    pub hint_points: usize,
+    pub mistakes: usize,
+    pub score: u64,
+    pub day: u64,
+}
+
+/// The stars, from 1 to 5, that the player gave a puzzle after solving it.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Rating {
+    /// The puzzle's clues, in the canonical form of [`canonical`].
+    pub puzzle: String,
+    pub stars: u8,
+    pub day: u64,
+}
+
+/// Solve counts and times for a group of solves.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Summary {
+    pub solved: usize,
+    pub average: Duration,
+    pub best: Duration,
+}
+
+impl Summary {
+    fn of<'a>(solves: impl Iterator<Item = &'a Solve>) -> Option<Self> {
+        let seconds: Vec<u64> = solves.map(|solve| solve.seconds).collect();
+        let best = *seconds.iter().min()?;
+        Some(Summary {
+            solved: seconds.len(),
+            average: Duration::from_secs(seconds.iter().sum::<u64>() / seconds.len() as u64),
+            best: Duration::from_secs(best),
+        })
+    }
+}
+
+/// Every puzzle solved in the interpreter.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Stats {
+    #[serde(default)]
+    pub solves: Vec<Solve>,
+    #[serde(default)]
+    pub challenges: Vec<ChallengeRun>,
+    #[serde(default)]
+    pub ratings: Vec<Rating>,
+}
+
+impl Stats {
+    pub fn parse(contents: &str) -> Result<Self, String> {
+        toml::from_str(contents).map_err(|e| format!("invalid stats file: {e}"))
+    }
+
+    /// Reads the stats file. A missing or unreadable file means nothing has
+    /// been solved yet.
+    pub fn load() -> Self {
+        fs::read_to_string(stats_path())
+            .ok()
+            .and_then(|contents| Stats::parse(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let path = stats_path();
+        let contents = toml::to_string(self).map_err(|e| e.to_string())?;
+        fs::write(&path, contents)
+            .map_err(|_| format!("could not write stats file {}", path.display()))
+    }
+
+    pub fn record(&mut self, solve: Solve) {
+        self.solves.push(solve);
+    }
+
+    pub fn record_challenge(&mut self, run: ChallengeRun) {
+        self.challenges.push(run);
+    }
+
+    /// Gives a puzzle from 1 to 5 stars. Every rating is kept, so a puzzle
+    /// that is solved again can be rated again.
+    pub fn rate(&mut self, puzzle: &str, stars: u8, day: u64) -> Result<(), &'static str> {
+        if !(1..=5).contains(&stars) {
+            return Err("a rating is from 1 to 5 stars");
+        }
+        self.ratings.push(Rating {
+            puzzle: canonical(puzzle),
+            stars,
+            day,
+        });
+        Ok(())
+    }
+
+    /// The average stars given to a puzzle, whether it is given with its
+    /// clues only or partly filled in.
+    pub fn average_rating(&self, puzzle: &str) -> Option<f64> {
+        let puzzle = canonical(puzzle);
+        let stars: Vec<u8> = self
+            .ratings
+            .iter()
+            .filter(|rating| rating.puzzle == puzzle)
+            .map(|rating| rating.stars)
+            .collect();
+        if stars.is_empty() {
+            return None;
+        }
+        Some(stars.iter().map(|&s| f64::from(s)).sum::<f64>() / stars.len() as f64)
+    }
+
+    /// The challenge with the highest score, the earliest of any ties.
+    pub fn best_challenge(&self) -> Option<&ChallengeRun> {
+        self.challenges.iter().rev().max_by_key(|run| run.score)
+    }
+
+    pub fn total(&self) -> Option<Summary> {
+        Summary::of(self.solves.iter())
+    }
+
+    pub fn by_size(&self) -> Vec<(u8, Summary)> {
+        let mut sizes: Vec<u8> = self.solves.iter().map(|solve| solve.size).collect();
+        sizes.sort();
+        sizes.dedup();
+        sizes
+            .into_iter()
+            .filter_map(|size| {
+                Summary::of(self.solves.iter().filter(|solve| solve.size == size))
+                    .map(|summary| (size, summary))
+            })
+            .collect()
+    }
+
+    pub fn by_difficulty(&self) -> Vec<(Difficulty, Summary)> {
+        Difficulty::ALL
+            .into_iter()
+            .filter_map(|difficulty| {
+                Summary::of(
+                    self.solves
+                        .iter()
+                        .filter(|solve| solve.difficulty == Some(difficulty)),
+                )
+                .map(|summary| (difficulty, summary))
+            })
+            .collect()
+    }
+
+    pub fn hints(&self) -> usize {
+        self.solves.iter().map(|solve| solve.hints).sum()
+    }
+
+    pub fn hint_points(&self) -> usize {
+        self.solves.iter().map(|solve| solve.hint_points).sum()
+    }
+
+    /// The number of days in a row, ending today or yesterday, with at least
+    /// one solve.
+    pub fn streak(&self, today: u64) -> u64 {
+        let mut days: Vec<u64> = self.solves.iter().map(|solve| solve.day).collect();
+        days.sort();
+        days.dedup();
+        let mut expected = match days.last() {
+            Some(&last) if last + 1 >= today => last,
+            _ => return 0,
+        };
+        let mut streak = 0;
+        for &day in days.iter().rev() {
+            if day != expected {
+                break;
+            }
+            streak += 1;
+            expected = expected.saturating_sub(1);
+        }
+        streak
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::binox::{Binox, BinoxCell};
+
+    fn solve(size: u8, seconds: u64, day: u64) -> Solve {
+        Solve {
+            size,
+            difficulty: Some(Difficulty::Easy),
+            seconds,
+            hints: 1,
+            hint_points: 2,
+            day,
+        }
+    }
+
+    #[test]
+    fn summaries_and_streaks() {
+        let mut stats = Stats::default();
+        assert_eq!(stats.total(), None);
+        assert_eq!(stats.streak(100), 0);
+        for (size, seconds, day) in [(6, 60, 95), (6, 120, 97), (8, 300, 98), (6, 30, 98)] {
+            stats.record(solve(size, seconds, day));
+        }
+        let sizes = stats.by_size();
+        assert_eq!(sizes.len(), 2);
+        assert_eq!(sizes[0].0, 6);
+        assert_eq!(sizes[0].1.solved, 3);
+        assert_eq!(sizes[0].1.average, Duration::from_secs(70));
+        assert_eq!(sizes[0].1.best, Duration::from_secs(30));
+        assert_eq!(stats.by_difficulty()[0].1.solved, 4);
+        assert_eq!(stats.hints(), 4);
+        assert_eq!(stats.hint_points(), 8);
+        assert_eq!(stats.streak(98), 2);
+        assert_eq!(stats.streak(99), 2);
+        assert_eq!(stats.streak(100), 0);
+        assert_eq!(stats.best_challenge(), None);
+        for (day, score) in [(1, 300), (2, 500), (3, 500)] {
+            stats.record_challenge(ChallengeRun {
+                size: 6,
+                difficulty: Difficulty::Easy,
+                seconds: 300,
+                solved: 2,
+                hints: 0,
+                hint_points: 0,
+                mistakes: 0,
+                score,
+                day,
+            });
+        }
+        assert_eq!(stats.best_challenge().map(|run| run.day), Some(2));
+        let reloaded = Stats::parse(&toml::to_string(&stats).unwrap()).unwrap();
+        assert_eq!(reloaded.solves, stats.solves);
+        assert_eq!(reloaded.challenges, stats.challenges);
+        assert!(Stats::parse("").unwrap().challenges.is_empty());
+    }
+
+    #[test]
+    fn ratings() {
+        let mut stats = Stats::default();
+        let puzzle = "X......O...X..XX....X..O....X..XX...";
+        assert_eq!(stats.average_rating(puzzle), None);
+        stats.rate(puzzle, 4, 10).unwrap();
+        stats.rate(puzzle, 5, 12).unwrap();
+        assert!(stats.rate(puzzle, 6, 12).is_err());
+        assert!(stats.rate(puzzle, 0, 12).is_err());
+        let mut played = Binox::new_from_string(puzzle.into());
+        played.set_cell(0, 1, BinoxCell::O).unwrap();
+        assert_eq!(stats.average_rating(&played.as_string()), Some(4.5));
+        let reloaded = Stats::parse(&toml::to_string(&stats).unwrap()).unwrap();
+        assert_eq!(reloaded.ratings, stats.ratings);
+    }
+}
@@ -0,0 +1,147 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::thread;
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use crate::binox::{Binox, BinoxSolution, Symmetry};
+use crate::puzzle::Puzzle;
+
+struct State<T> {
+    result: Option<T>,
+    waker: Option<Waker>,
+}
+
+struct Shared<T> {
+    state: Mutex<State<T>>,
+    stop: AtomicBool,
+}
+
+/// Work running on a thread of its own, awaited as a future. It does not
+/// depend on any async runtime, so the thread it is awaited on is never
+/// blocked. Dropping the task, or calling [`Task::cancel`], stops the work at
+/// its next step.
+pub struct Task<T> {
+    shared: Arc<Shared<T>>,
+}
+
+impl<T: Send + 'static> Task<T> {
+    fn spawn(work: impl FnOnce(&AtomicBool) -> T + Send + 'static) -> Self {
+        let shared = Arc::new(Shared {
+            state: Mutex::new(State {
+                result: None,
+                waker: None,
+            }),
+            stop: AtomicBool::new(false),
+        });
+        let worker = Arc::clone(&shared);
+        thread::spawn(move || {
+            let result = work(&worker.stop);
+            let mut state = worker.state.lock().unwrap();
+            state.result = Some(result);
+            if let Some(waker) = state.waker.take() {
+                waker.wake();
+            }
+        });
+        Task { shared }
+    }
+}
+
+impl<T> Task<T> {
+    /// Stops the work. The task then finishes with an error.
+    pub fn cancel(&self) {
+        self.shared.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+impl<T> Future for Task<T> {
+    type Output = T;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<T> {
+        let mut state = self.shared.state.lock().unwrap();
+        match state.result.take() {
+            Some(result) => Poll::Ready(result),
+            None => {
+                state.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl<T> Drop for Task<T> {
+    fn drop(&mut self) {
+        self.cancel();
+    }
+}
+
+/// Solves the board like [`Binox::solve`].
+pub fn solve(binox: Binox, multiple: bool) -> Task<Result<BinoxSolution, &'static str>> {
+    Task::spawn(move |stop| binox.search(multiple, stop).ok_or("solving was cancelled"))
+}
+
+/// Generates a puzzle and its solution like [`Binox::generate_with_rng`],
+/// seeded with `seed` if one is given.
+pub fn generate(
+    size: u8,
+    perfect: bool,
+    extras: usize,
+    symmetry: Symmetry,
+    seed: Option<u64>,
+) -> Task<Result<(Binox, Binox), &'static str>> {
+    Task::spawn(move |stop| {
+        let mut rng = match seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+        Binox::generate_with_stop(size, perfect, extras, symmetry, &mut rng, stop)
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use std::task::Wake;
+    use std::thread::Thread;
+
+    use super::*;
+
+    struct Unpark(Thread);
+
+    impl Wake for Unpark {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let mut future = std::pin::pin!(future);
+        let waker = Waker::from(Arc::new(Unpark(thread::current())));
+        let mut cx = Context::from_waker(&waker);
+        loop {
+            match future.as_mut().poll(&mut cx) {
+                Poll::Ready(output) => return output,
+                Poll::Pending => thread::park(),
+            }
+        }
+    }
+
+    #[test]
+    fn awaiting_and_cancelling() {
+        let (puzzle, solution) = block_on(generate(8, false, 0, Symmetry::None, Some(3))).unwrap();
+        let mut rng = StdRng::seed_from_u64(3);
+        let (same, _) = Binox::generate_with_rng(8, false, 0, Symmetry::None, &mut rng).unwrap();
+        assert_eq!(puzzle.as_string(), same.as_string());
+        match block_on(solve(puzzle, true)) {
+            Ok(BinoxSolution::One(found)) => assert!(found.changed_cells(&solution).is_empty()),
+            _ => panic!("expected one solution"),
+        }
+
+        let task = generate(16, true, 0, Symmetry::None, Some(3));
+        task.cancel();
+        assert_eq!(block_on(task).err(), Some("generation was cancelled"));
+    }
+}
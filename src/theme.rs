@@ -0,0 +1,249 @@
+use alloc::format;
+use alloc::string::{String, ToString};
+use core::str::FromStr;
+#[cfg(feature = "color")]
+use std::env;
+#[cfg(feature = "color")]
+use std::io::{self, IsTerminal};
+
+#[cfg(feature = "color")]
+use colored::{Color, ColoredString, Colorize};
+use serde::{Deserialize, Serialize};
+use spin::RwLock;
+
+use crate::binox::BinoxCell;
+
+/// Text styles that can mark the given or automatically filled cells of a puzzle.
+pub const GIVEN_STYLES: [&str; 5] = ["bold", "dimmed", "italic", "underline", "none"];
+
+/// How boards are drawn in text output.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BoardStyle {
+    /// Cells in a grid of `-`, `|` and `+`.
+    #[default]
+    Classic,
+    /// A tighter grid of box-drawing characters with shaded given cells.
+    Boxed,
+}
+
+impl FromStr for BoardStyle {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "classic" => Ok(BoardStyle::Classic),
+            "boxed" | "unicode" => Ok(BoardStyle::Boxed),
+            _ => Err("style must be classic or boxed"),
+        }
+    }
+}
+
+/// Symbols and colors used to draw the board. Color names are those understood
+/// by the `colored` crate, such as `red`, `bright blue` or `magenta`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Theme {
+    /// Character drawn for X, such as `1` or `■`.
+    pub x_symbol: char,
+    /// Character drawn for O, such as `0` or `□`.
+    pub o_symbol: char,
+    pub x_color: String,
+    pub o_color: String,
+    /// Style of the cells given by the puzzle, one of `GIVEN_STYLES`.
+    pub given_style: String,
+    /// Style of the cells filled by `auto`, one of `GIVEN_STYLES`.
+    pub auto_style: String,
+    /// Color of the cursor and of the selected puzzle in lists.
+    pub highlight_color: String,
+    pub style: BoardStyle,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme {
+            x_symbol: 'X',
+            o_symbol: 'O',
+            x_color: "red".into(),
+            o_color: "blue".into(),
+            given_style: "bold".into(),
+            auto_style: "underline".into(),
+            highlight_color: "yellow".into(),
+            style: BoardStyle::Classic,
+        }
+    }
+}
+
+impl Theme {
+    pub fn validate(&self) -> Result<(), String> {
+        for symbol in [self.x_symbol, self.o_symbol] {
+            if symbol.is_whitespace() || symbol.is_control() || symbol == '.' {
+                return Err(format!("symbol cannot be {symbol:?}"));
+            }
+        }
+        if self
+            .x_symbol
+            .to_lowercase()
+            .eq(self.o_symbol.to_lowercase())
+        {
+            return Err("the two symbols must be different".into());
+        }
+        #[cfg(feature = "color")]
+        for color in [&self.x_color, &self.o_color, &self.highlight_color] {
+            Color::from_str(color).map_err(|_| format!("unknown color: {color}"))?;
+        }
+        for style in [&self.given_style, &self.auto_style] {
+            if !GIVEN_STYLES.contains(&style.as_str()) {
+                return Err(format!("unknown style: {style}"));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn symbol(&self, cell: BinoxCell) -> char {
+        match cell {
+            BinoxCell::X => self.x_symbol,
+            BinoxCell::O => self.o_symbol,
+            BinoxCell::EMPTY => ' ',
+        }
+    }
+
+    /// Which cell a typed symbol stands for, ignoring case.
+    pub fn cell_for(&self, symbol: &str) -> Option<BinoxCell> {
+        let matches = |c: char| symbol.to_lowercase() == c.to_lowercase().to_string();
+        if matches(self.x_symbol) {
+            Some(BinoxCell::X)
+        } else if matches(self.o_symbol) {
+            Some(BinoxCell::O)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(feature = "color")]
+impl Theme {
+    fn color(name: &str, fallback: Color) -> Color {
+        Color::from_str(name).unwrap_or(fallback)
+    }
+
+    pub fn x_color(&self) -> Color {
+        Theme::color(&self.x_color, Color::Red)
+    }
+
+    pub fn o_color(&self) -> Color {
+        Theme::color(&self.o_color, Color::Blue)
+    }
+
+    pub fn highlight_color(&self) -> Color {
+        Theme::color(&self.highlight_color, Color::Yellow)
+    }
+
+    pub fn paint(&self, cell: BinoxCell) -> ColoredString {
+        let symbol = self.symbol(cell).to_string();
+        match cell {
+            BinoxCell::X => symbol.color(self.x_color()),
+            BinoxCell::O => symbol.color(self.o_color()),
+            BinoxCell::EMPTY => symbol.normal(),
+        }
+    }
+
+    /// Applies the given-cell style to `text`.
+    pub fn given(&self, text: ColoredString) -> ColoredString {
+        self.styled(text, &self.given_style)
+    }
+
+    /// Applies one of `GIVEN_STYLES` to `text`.
+    pub fn styled(&self, text: ColoredString, style: &str) -> ColoredString {
+        match style {
+            "dimmed" => text.dimmed(),
+            "italic" => text.italic(),
+            "underline" => text.underline(),
+            "none" => text,
+            _ => text.bold(),
+        }
+    }
+
+    pub fn highlight(&self, text: &str) -> ColoredString {
+        text.color(self.highlight_color()).bold()
+    }
+}
+
+static THEME: RwLock<Option<Theme>> = RwLock::new(None);
+
+/// Sets the theme used by `Display` and the interpreter.
+pub fn set_theme(theme: Theme) {
+    *THEME.write() = Some(theme);
+}
+
+pub fn current_theme() -> Theme {
+    THEME.read().clone().unwrap_or_default()
+}
+
+/// Decides whether to use ANSI colors. `NO_COLOR` turns them off and
+/// `CLICOLOR_FORCE` turns them on; otherwise they are used on terminals only.
+#[cfg(feature = "color")]
+fn use_color(no_color: Option<&str>, force: Option<&str>, terminal: bool) -> bool {
+    let set = |value: Option<&str>| value.is_some_and(|v| !v.is_empty() && v != "0");
+    if no_color.is_some_and(|v| !v.is_empty()) {
+        false
+    } else {
+        set(force) || terminal
+    }
+}
+
+/// Turns ANSI colors on or off for the whole program, following the
+/// environment and whether stdout is a terminal.
+#[cfg(feature = "color")]
+pub fn init_color() {
+    let no_color = env::var("NO_COLOR").ok();
+    let force = env::var("CLICOLOR_FORCE").ok();
+    colored::control::set_override(use_color(
+        no_color.as_deref(),
+        force.as_deref(),
+        io::stdout().is_terminal(),
+    ));
+}
+
+/// Whether ANSI colors are currently in use.
+#[cfg(feature = "color")]
+pub fn color_enabled() -> bool {
+    colored::control::SHOULD_COLORIZE.should_colorize()
+}
+
+#[cfg(all(test, feature = "color"))]
+mod test {
+    use super::*;
+
+    #[test]
+    fn color_detection() {
+        assert!(use_color(None, None, true));
+        assert!(!use_color(None, None, false));
+        assert!(!use_color(Some("1"), None, true));
+        assert!(!use_color(Some("1"), Some("1"), true));
+        assert!(use_color(Some(""), None, true));
+        assert!(use_color(None, Some("1"), false));
+        assert!(!use_color(None, Some("0"), false));
+        let theme = Theme {
+            given_style: "sparkly".into(),
+            ..Theme::default()
+        };
+        assert!(theme.validate().is_err());
+        assert!(Theme::default().validate().is_ok());
+        let theme = Theme {
+            x_symbol: '■',
+            o_symbol: '□',
+            ..Theme::default()
+        };
+        assert!(theme.validate().is_ok());
+        assert_eq!(theme.cell_for("□"), Some(BinoxCell::O));
+        assert_eq!(theme.cell_for("x"), None);
+        colored::control::set_override(false);
+        assert_eq!(theme.paint(BinoxCell::X).to_string(), "■");
+        let theme = Theme {
+            o_symbol: 'x',
+            ..Theme::default()
+        };
+        assert!(theme.validate().is_err());
+    }
+}
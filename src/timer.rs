@@ -0,0 +1,226 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// Time spent on each puzzle of a set. Only the selected puzzle's clock runs,
+/// and a puzzle's clock stops for good once it has been solved.
+#[derive(Clone, Debug)]
+pub struct Timers {
+    elapsed: HashMap<usize, Duration>,
+    finished: HashMap<usize, Duration>,
+    selected: usize,
+    started: Instant,
+}
+
+impl Timers {
+    pub fn new(selected: usize) -> Self {
+        Timers {
+            elapsed: HashMap::new(),
+            finished: HashMap::new(),
+            selected,
+            started: Instant::now(),
+        }
+    }
+
+    /// Pauses the selected puzzle's clock and starts the clock of `index`.
+    pub fn select(&mut self, index: usize) {
+        self.select_at(index, Instant::now());
+    }
+
+    fn select_at(&mut self, index: usize, now: Instant) {
+        let running = now.saturating_duration_since(self.started);
+        *self.elapsed.entry(self.selected).or_default() += running;
+        self.selected = index;
+        self.started = now;
+    }
+
+    /// The time spent on the selected puzzle so far.
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed_at(Instant::now())
+    }
+
+    fn elapsed_at(&self, now: Instant) -> Duration {
+        if let Some(&time) = self.finished.get(&self.selected) {
+            return time;
+        }
+        let before = self
+            .elapsed
+            .get(&self.selected)
+            .copied()
+            .unwrap_or_default();
+        before + now.saturating_duration_since(self.started)
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished.contains_key(&self.selected)
+    }
+
+    /// Stops the selected puzzle's clock and returns its final time, or
+    /// `None` if it had already been stopped.
+    pub fn finish(&mut self) -> Option<Duration> {
+        self.finish_at(Instant::now())
+    }
+
+    fn finish_at(&mut self, now: Instant) -> Option<Duration> {
+        if self.is_finished() {
+            return None;
+        }
+        let time = self.elapsed_at(now);
+        self.finished.insert(self.selected, time);
+        Some(time)
+    }
+
+    /// Sets the time already spent on a puzzle, as when a saved set is
+    /// loaded again.
+    pub fn restore(&mut self, index: usize, elapsed: Duration) {
+        self.elapsed.insert(index, elapsed);
+    }
+
+    /// Forgets the time of a puzzle removed from the set, and renumbers the
+    /// puzzles after it.
+    pub fn remove(&mut self, index: usize, selected: usize) {
+        self.select_at(self.selected, Instant::now());
+        for times in [&mut self.elapsed, &mut self.finished] {
+            *times = shift_down(std::mem::take(times), index);
+        }
+        self.selected = selected;
+    }
+
+    /// Renumbers the puzzles' times after they were put in `order`, as
+    /// [`reorder`] does.
+    pub fn reorder(&mut self, order: &[usize], selected: usize) {
+        self.select_at(self.selected, Instant::now());
+        for times in [&mut self.elapsed, &mut self.finished] {
+            *times = reorder(std::mem::take(times), order);
+        }
+        self.selected = selected;
+    }
+
+    /// Starts the selected puzzle's clock again from zero, for when its board
+    /// is replaced by a new one.
+    pub fn restart(&mut self) {
+        self.elapsed.remove(&self.selected);
+        self.finished.remove(&self.selected);
+        self.started = Instant::now();
+    }
+
+    /// Forgets every puzzle's time, for when a new set is loaded.
+    pub fn reset(&mut self, selected: usize) {
+        *self = Timers::new(selected);
+    }
+}
+
+/// Drops the entry for `index` and moves every later entry down by one.
+pub fn shift_down<T>(map: HashMap<usize, T>, index: usize) -> HashMap<usize, T> {
+    map.into_iter()
+        .filter(|&(i, _)| i != index)
+        .map(|(i, value)| (if i > index { i - 1 } else { i }, value))
+        .collect()
+}
+
+/// Renumbers the entries after the puzzles were put in `order`, where the
+/// puzzle now at `i` was at `order[i]` before.
+pub fn reorder<T>(map: HashMap<usize, T>, order: &[usize]) -> HashMap<usize, T> {
+    let mut map = map;
+    order
+        .iter()
+        .enumerate()
+        .filter_map(|(i, before)| Some((i, map.remove(before)?)))
+        .collect()
+}
+
+/// The number of days since 1970, in UTC.
+pub fn today() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+        / SECONDS_PER_DAY
+}
+
+/// Formats a day number from [`today`] as a date such as `2024-05-17`.
+pub fn format_day(day: u64) -> String {
+    // Counts from 0000-03-01 so that leap days fall at the end of a year.
+    let days = day + 719_468;
+    let era = days / 146_097;
+    let of_era = days % 146_097;
+    let year_of_era = (of_era - of_era / 1460 + of_era / 36_524 - of_era / 146_096) / 365;
+    let of_year = of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_from_march = (5 * of_year + 2) / 153;
+    let day_of_month = of_year - (153 * month_from_march + 2) / 5 + 1;
+    let month = if month_from_march < 10 {
+        month_from_march + 3
+    } else {
+        month_from_march - 9
+    };
+    let year = era * 400 + year_of_era + u64::from(month <= 2);
+    format!("{year:04}-{month:02}-{day_of_month:02}")
+}
+
+/// Formats a duration as `m:ss`, or `h:mm:ss` past an hour.
+pub fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    let (hours, minutes, seconds) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+    if hours > 0 {
+        format!("{hours}:{minutes:02}:{seconds:02}")
+    } else {
+        format!("{minutes}:{seconds:02}")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn clocks_pause_and_stop() {
+        let start = Instant::now();
+        let at = |seconds| start + Duration::from_secs(seconds);
+        let mut timers = Timers::new(0);
+        timers.started = start;
+        timers.select_at(1, at(10));
+        assert_eq!(timers.elapsed_at(at(15)), Duration::from_secs(5));
+        timers.select_at(0, at(20));
+        assert_eq!(timers.elapsed_at(at(22)), Duration::from_secs(12));
+        assert_eq!(timers.finish_at(at(25)), Some(Duration::from_secs(15)));
+        assert_eq!(timers.finish_at(at(30)), None);
+        assert_eq!(timers.elapsed_at(at(40)), Duration::from_secs(15));
+        timers.select_at(1, at(50));
+        assert_eq!(timers.elapsed_at(at(51)), Duration::from_secs(11));
+        timers.remove(0, 0);
+        assert!(!timers.is_finished());
+        assert!(timers.elapsed() >= Duration::from_secs(10));
+    }
+
+    #[test]
+    fn restarting() {
+        let mut timers = Timers::new(0);
+        timers.finish();
+        assert!(timers.is_finished());
+        timers.restart();
+        assert!(!timers.is_finished());
+        assert!(timers.elapsed() < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn renumbering() {
+        let map = HashMap::from([(0, 'a'), (2, 'c')]);
+        assert_eq!(
+            reorder(map, &[2, 0, 1]),
+            HashMap::from([(0, 'c'), (1, 'a')])
+        );
+        let map = HashMap::from([(0, 'a'), (1, 'b'), (2, 'c')]);
+        assert_eq!(shift_down(map, 1), HashMap::from([(0, 'a'), (1, 'c')]));
+    }
+
+    #[test]
+    fn durations() {
+        assert_eq!(format_duration(Duration::from_secs(5)), "0:05");
+        assert_eq!(format_duration(Duration::from_secs(754)), "12:34");
+        assert_eq!(format_duration(Duration::from_secs(3725)), "1:02:05");
+        assert_eq!(format_day(0), "1970-01-01");
+        assert_eq!(format_day(11_016), "2000-02-29");
+        assert_eq!(format_day(19_860), "2024-05-17");
+    }
+}
@@ -0,0 +1,328 @@
+use std::io;
+use std::time::{Duration, Instant};
+
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+
+use crate::binox::{column_name, Binox, BinoxCell, SolveStatus};
+use crate::config::KeyBindings;
+use crate::puzzle_set::PuzzleSet;
+use crate::theme::{self, current_theme};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Action {
+    Up,
+    Down,
+    Left,
+    Right,
+    X,
+    O,
+    Erase,
+    Verify,
+    Reset,
+    Solve,
+    Next,
+    Previous,
+    Help,
+    Quit,
+}
+
+impl KeyBindings {
+    fn action(&self, c: char) -> Option<Action> {
+        let c = c.to_ascii_lowercase();
+        [
+            (self.up, Action::Up),
+            (self.down, Action::Down),
+            (self.left, Action::Left),
+            (self.right, Action::Right),
+            (self.x, Action::X),
+            (self.o, Action::O),
+            (self.erase, Action::Erase),
+            (self.verify, Action::Verify),
+            (self.reset, Action::Reset),
+            (self.solve, Action::Solve),
+            (self.next, Action::Next),
+            (self.previous, Action::Previous),
+            (self.quit, Action::Quit),
+        ]
+        .into_iter()
+        .find(|&(key, _)| key.to_ascii_lowercase() == c)
+        .map(|(_, action)| action)
+    }
+
+    fn help(&self) -> String {
+        let erase = if self.erase == ' ' {
+            "space".into()
+        } else {
+            self.erase.to_string()
+        };
+        format!(
+            "arrows/{}{}{}{}: move  {}/{}: place  {erase}: erase  {}: verify  {}: reset  {}: solve  {}/{}: next/previous  {}: quit",
+            self.left, self.down, self.up, self.right, self.x, self.o, self.verify, self.reset,
+            self.solve, self.next, self.previous, self.quit
+        )
+    }
+}
+
+fn tui_color(color: colored::Color) -> Color {
+    use colored::Color as C;
+    match color {
+        C::Black => Color::Black,
+        C::Red => Color::Red,
+        C::Green => Color::Green,
+        C::Yellow => Color::Yellow,
+        C::Blue => Color::Blue,
+        C::Magenta => Color::Magenta,
+        C::Cyan => Color::Cyan,
+        C::White => Color::Gray,
+        C::BrightBlack => Color::DarkGray,
+        C::BrightRed => Color::LightRed,
+        C::BrightGreen => Color::LightGreen,
+        C::BrightYellow => Color::LightYellow,
+        C::BrightBlue => Color::LightBlue,
+        C::BrightMagenta => Color::LightMagenta,
+        C::BrightCyan => Color::LightCyan,
+        C::BrightWhite => Color::White,
+        C::TrueColor { r, g, b } => Color::Rgb(r, g, b),
+    }
+}
+
+fn given_modifier(style: &str) -> Modifier {
+    match style {
+        "dimmed" => Modifier::DIM,
+        "italic" => Modifier::ITALIC,
+        "underline" => Modifier::UNDERLINED,
+        "none" => Modifier::empty(),
+        _ => Modifier::BOLD,
+    }
+}
+
+/// State of the full-screen front end.
+pub struct App {
+    binox: Binox,
+    puzzles: PuzzleSet,
+    keys: KeyBindings,
+    row: u8,
+    col: u8,
+    status: String,
+    started: Instant,
+    quit: bool,
+}
+
+impl App {
+    pub fn new(puzzles: PuzzleSet, keys: KeyBindings) -> Self {
+        App {
+            binox: Binox::new_from_string(puzzles.current().puzzle.clone()),
+            puzzles,
+            status: keys.help(),
+            keys,
+            row: 0,
+            col: 0,
+            started: Instant::now(),
+            quit: false,
+        }
+    }
+
+    pub fn puzzles(&self) -> &PuzzleSet {
+        &self.puzzles
+    }
+
+    fn load_current(&mut self) {
+        self.binox = Binox::new_from_string(self.puzzles.current().puzzle.clone());
+        self.row = self.row.min(self.binox.size() - 1);
+        self.col = self.col.min(self.binox.size() - 1);
+        self.started = Instant::now();
+    }
+
+    fn place(&mut self, cell: BinoxCell) {
+        self.status = match self.binox.set_cell(self.row, self.col, cell) {
+            Ok(()) if self.binox.is_solved() => "the puzzle has been solved".into(),
+            Ok(()) => String::new(),
+            Err(error) => error.to_string(),
+        };
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) {
+        if key.kind != KeyEventKind::Press {
+            return;
+        }
+        let action = match key.code {
+            KeyCode::Up => Action::Up,
+            KeyCode::Down => Action::Down,
+            KeyCode::Left => Action::Left,
+            KeyCode::Right => Action::Right,
+            KeyCode::Backspace | KeyCode::Delete => Action::Erase,
+            KeyCode::Esc => Action::Quit,
+            KeyCode::Char('?') => Action::Help,
+            KeyCode::Char(c) => match self.keys.action(c) {
+                Some(action) => action,
+                None => return,
+            },
+            _ => return,
+        };
+        self.perform(action);
+    }
+
+    fn perform(&mut self, action: Action) {
+        let last = self.binox.size() - 1;
+        match action {
+            Action::Up => self.row = self.row.saturating_sub(1),
+            Action::Down => self.row = (self.row + 1).min(last),
+            Action::Left => self.col = self.col.saturating_sub(1),
+            Action::Right => self.col = (self.col + 1).min(last),
+            Action::X => self.place(BinoxCell::X),
+            Action::O => self.place(BinoxCell::O),
+            Action::Erase => self.place(BinoxCell::EMPTY),
+            Action::Verify => {
+                self.status = match (self.binox.is_full(), self.binox.is_valid()) {
+                    (true, true) => "the puzzle has been solved",
+                    (false, true) => "no mistakes so far",
+                    (_, false) => "a mistake has been made",
+                }
+                .into()
+            }
+            Action::Reset => {
+                self.binox.reset();
+                self.status = "the puzzle has been reset".into();
+            }
+            Action::Solve => {
+                if self.binox.solve_in_place(false) == SolveStatus::NoSolution {
+                    self.status = "puzzle has no solution".into();
+                }
+            }
+            Action::Next => {
+                self.puzzles.update_current(self.binox.as_string());
+                self.puzzles.next_puzzle();
+                self.load_current();
+            }
+            Action::Previous => {
+                self.puzzles.update_current(self.binox.as_string());
+                self.puzzles.previous_puzzle();
+                self.load_current();
+            }
+            Action::Help => self.status = self.keys.help(),
+            Action::Quit => {
+                self.puzzles.update_current(self.binox.as_string());
+                self.quit = true;
+            }
+        }
+    }
+
+    fn board_lines(&self) -> Vec<Line<'static>> {
+        let theme = current_theme();
+        let color = theme::color_enabled();
+        let size = self.binox.size();
+        let mut lines = Vec::new();
+        let mut header = vec![Span::raw("    ")];
+        for col in 0..size {
+            header.push(Span::raw(format!(" {} ", column_name(col))));
+        }
+        lines.push(Line::from(header));
+        for row in 0..size {
+            let mut spans = vec![Span::raw(format!("{row:>3} "))];
+            for col in 0..size {
+                let cell = self.binox.get_cell(row, col).unwrap();
+                let (text, fg) = match cell {
+                    BinoxCell::X => (theme.symbol(cell), tui_color(theme.x_color())),
+                    BinoxCell::O => (theme.symbol(cell), tui_color(theme.o_color())),
+                    BinoxCell::EMPTY => ('·', Color::DarkGray),
+                };
+                let mut style = Style::default();
+                if color {
+                    style = style.fg(fg);
+                }
+                if self.binox.is_default(row, col).unwrap() {
+                    style = style.add_modifier(given_modifier(&theme.given_style));
+                }
+                if (row, col) == (self.row, self.col) {
+                    style = if color {
+                        style.bg(tui_color(theme.highlight_color()))
+                    } else {
+                        style.add_modifier(Modifier::REVERSED)
+                    };
+                }
+                spans.push(Span::styled(format!(" {text} "), style));
+            }
+            lines.push(Line::from(spans));
+        }
+        lines
+    }
+
+    fn draw(&self, frame: &mut Frame) {
+        let [board, status] =
+            Layout::vertical([Constraint::Min(0), Constraint::Length(3)]).areas(frame.area());
+        let mut title = format!(
+            " {} - puzzle {}/{} ",
+            self.puzzles.name,
+            self.puzzles.selected() + 1,
+            self.puzzles.len()
+        );
+        if let Some(name) = &self.puzzles.current().title {
+            title = format!("{title}- {name} ");
+        }
+        frame.render_widget(
+            Paragraph::new(self.board_lines()).block(Block::bordered().title(title)),
+            board,
+        );
+        let elapsed = self.started.elapsed().as_secs();
+        let timer = format!(" {:02}:{:02} ", elapsed / 60, elapsed % 60);
+        frame.render_widget(
+            Paragraph::new(self.status.clone()).block(Block::bordered().title(timer)),
+            status,
+        );
+    }
+
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
+        while !self.quit {
+            terminal.draw(|frame| self.draw(frame))?;
+            if event::poll(Duration::from_millis(250))? {
+                if let Event::Key(key) = event::read()? {
+                    self.handle_key(key);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Runs the full-screen front end until the player quits, returning the
+/// collection with the player's progress.
+pub fn run_tui(puzzles: PuzzleSet, keys: KeyBindings) -> Result<PuzzleSet, String> {
+    if puzzles.is_empty() {
+        return Err("file contains no puzzles".into());
+    }
+    let mut app = App::new(puzzles, keys);
+    let mut terminal = ratatui::init();
+    let result = app.run(&mut terminal);
+    ratatui::restore();
+    result.map_err(|e| e.to_string())?;
+    Ok(app.puzzles)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn cursor_and_placement() {
+        let puzzles = PuzzleSet::from_lines("set".into(), "X...............\n", None);
+        let mut app = App::new(puzzles, KeyBindings::default());
+        app.handle_key(KeyCode::Char('x').into());
+        assert_eq!(app.status, "cell a0 is a given and cannot be modified");
+        app.handle_key(KeyCode::Right.into());
+        app.handle_key(KeyCode::Down.into());
+        app.handle_key(KeyCode::Char('o').into());
+        assert_eq!(app.binox.get_cell(1, 1).unwrap(), BinoxCell::O);
+        for _ in 0..5 {
+            app.handle_key(KeyCode::Right.into());
+        }
+        assert_eq!(app.col, 3);
+        app.handle_key(KeyCode::Char('q').into());
+        assert!(app.quit);
+        assert_eq!(app.puzzles().current().puzzle, "X....o..........");
+    }
+}
@@ -0,0 +1,162 @@
+use core::fmt::Display;
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use wasm_bindgen::prelude::*;
+
+use crate::binox::{Binox, BinoxCell, BinoxSolution, Symmetry};
+
+/// Turns one of the engine's error messages into a JavaScript `Error`.
+fn js_error(text: impl Display) -> JsError {
+    JsError::new(&text.to_string())
+}
+
+fn cell_from_symbol(symbol: &str) -> Result<BinoxCell, JsError> {
+    match symbol.to_uppercase().as_str() {
+        "X" => Ok(BinoxCell::X),
+        "O" => Ok(BinoxCell::O),
+        "" | "." => Ok(BinoxCell::EMPTY),
+        _ => Err(js_error("symbol must be X, O or empty")),
+    }
+}
+
+fn symbol_of(cell: BinoxCell) -> String {
+    match cell {
+        BinoxCell::EMPTY => String::new(),
+        cell => char::from(cell).to_string(),
+    }
+}
+
+/// A puzzle exposed to JavaScript as `Binox`. Cells are read and written as
+/// the strings `"X"`, `"O"` and `""`, and failures throw an `Error` with the
+/// same message the interpreter would print.
+#[wasm_bindgen(js_name = Binox)]
+pub struct WasmBinox {
+    binox: Binox,
+}
+
+/// The next cell that can be filled by logic, as returned by `hint()`.
+#[wasm_bindgen(getter_with_clone)]
+pub struct Hint {
+    pub row: u8,
+    pub col: u8,
+    pub symbol: String,
+    pub explanation: String,
+}
+
+#[wasm_bindgen(js_class = Binox)]
+impl WasmBinox {
+    /// An empty board of the given size.
+    #[wasm_bindgen(constructor)]
+    pub fn new(size: u8) -> Result<WasmBinox, JsError> {
+        Ok(WasmBinox {
+            binox: Binox::new(size).map_err(js_error)?,
+        })
+    }
+
+    /// Reads a puzzle string such as `XX.O..O.........`.
+    #[wasm_bindgen(js_name = fromString)]
+    pub fn from_string(puzzle: &str) -> Result<WasmBinox, JsError> {
+        Ok(WasmBinox {
+            binox: Binox::parse_strict(puzzle).map_err(js_error)?,
+        })
+    }
+
+    /// Generates a puzzle with a unique solution. The same seed always gives
+    /// the same puzzle.
+    pub fn generate(size: u8, seed: u32, perfect: bool) -> Result<WasmBinox, JsError> {
+        let mut rng = StdRng::seed_from_u64(seed.into());
+        let (binox, _) = Binox::generate_with_rng(size, perfect, 0, Symmetry::None, &mut rng)
+            .map_err(js_error)?;
+        Ok(WasmBinox { binox })
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn size(&self) -> u8 {
+        self.binox.size()
+    }
+
+    #[wasm_bindgen(js_name = getCell)]
+    pub fn get_cell(&self, row: u8, col: u8) -> Result<String, JsError> {
+        Ok(symbol_of(self.binox.get_cell(row, col).map_err(js_error)?))
+    }
+
+    /// Places a symbol, or clears the cell for `""`. Given cells cannot be
+    /// changed.
+    #[wasm_bindgen(js_name = setCell)]
+    pub fn set_cell(&mut self, row: u8, col: u8, symbol: &str) -> Result<(), JsError> {
+        let cell = cell_from_symbol(symbol)?;
+        self.binox.set_cell(row, col, cell).map_err(js_error)
+    }
+
+    #[wasm_bindgen(js_name = isGiven)]
+    pub fn is_given(&self, row: u8, col: u8) -> Result<bool, JsError> {
+        self.binox.is_default(row, col).map_err(js_error)
+    }
+
+    /// Whether the board breaks no rule so far.
+    pub fn validate(&self) -> bool {
+        self.binox.is_valid()
+    }
+
+    #[wasm_bindgen(js_name = isSolved)]
+    pub fn is_solved(&self) -> bool {
+        self.binox.is_solved()
+    }
+
+    /// The solved board. Throws if there is no solution or more than one.
+    pub fn solve(&self) -> Result<WasmBinox, JsError> {
+        match self.binox.solve(true) {
+            BinoxSolution::One(binox) => Ok(WasmBinox { binox }),
+            BinoxSolution::Zero => Err(js_error("puzzle has no solution")),
+            BinoxSolution::Multiple(..) => Err(js_error("multiple solutions found")),
+        }
+    }
+
+    /// The next cell that can be filled by logic, or `undefined` if none can.
+    pub fn hint(&self) -> Option<Hint> {
+        let deduction = self.binox.next_deduction()?;
+        Some(Hint {
+            row: deduction.row,
+            col: deduction.col,
+            symbol: symbol_of(deduction.cell),
+            explanation: deduction.explanation(),
+        })
+    }
+
+    /// The board as a puzzle string, which `fromString` reads back.
+    #[wasm_bindgen(js_name = toString)]
+    pub fn as_string(&self) -> String {
+        self.binox.as_string()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn playing_through_the_bindings() {
+        let mut puzzle = WasmBinox::from_string("XXO.O..X.O..X..O").unwrap();
+        assert_eq!(puzzle.size(), 4);
+        assert_eq!(puzzle.get_cell(0, 0).unwrap(), "X");
+        assert!(puzzle.is_given(0, 0).unwrap());
+        let hint = puzzle.hint().unwrap();
+        puzzle.set_cell(hint.row, hint.col, &hint.symbol).unwrap();
+        assert_eq!(puzzle.get_cell(hint.row, hint.col).unwrap(), hint.symbol);
+        assert!(puzzle.validate());
+        let solved = puzzle.solve().unwrap();
+        assert!(solved.is_solved());
+        assert_eq!(
+            WasmBinox::from_string(&solved.as_string())
+                .unwrap()
+                .as_string(),
+            solved.as_string()
+        );
+        let generated = WasmBinox::generate(6, 7, false).unwrap();
+        assert_eq!(
+            generated.as_string(),
+            WasmBinox::generate(6, 7, false).unwrap().as_string()
+        );
+    }
+}